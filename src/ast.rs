@@ -3,7 +3,7 @@ use tree_sitter::Node;
 
 use crate::utils::get_node_text;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, serde::Serialize, serde::Deserialize)]
 pub enum InferredType {
     Int,
     Long,
@@ -18,6 +18,66 @@ pub enum InferredType {
     Unknown,
 }
 
+impl std::fmt::Display for InferredType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InferredType::Int => f.write_str("int"),
+            InferredType::Long => f.write_str("long"),
+            InferredType::Boolean => f.write_str("boolean"),
+            InferredType::Char => f.write_str("char"),
+            InferredType::String => f.write_str("String"),
+            InferredType::Float => f.write_str("float"),
+            InferredType::Double => f.write_str("double"),
+            InferredType::Class(name) => f.write_str(name),
+            InferredType::Unknown => f.write_str("?"),
+        }
+    }
+}
+
+impl InferredType {
+    /// The type of a JVM field descriptor such as `I` or `Ljava/lang/String;`,
+    /// spelled the way `parse_java_type` sees it in source: boxed primitives
+    /// collapse to their primitive and classes keep their simple name.
+    pub fn from_descriptor(descriptor: &str) -> InferredType {
+        match descriptor {
+            "I" | "Ljava/lang/Integer;" => InferredType::Int,
+            "J" | "Ljava/lang/Long;" => InferredType::Long,
+            "Z" | "Ljava/lang/Boolean;" => InferredType::Boolean,
+            "C" | "Ljava/lang/Character;" => InferredType::Char,
+            "F" | "Ljava/lang/Float;" => InferredType::Float,
+            "D" | "Ljava/lang/Double;" => InferredType::Double,
+            "Ljava/lang/String;" => InferredType::String,
+            _ => match descriptor_text(descriptor) {
+                Some(text) => InferredType::Class(text),
+                None => InferredType::Unknown,
+            },
+        }
+    }
+}
+
+fn descriptor_text(descriptor: &str) -> Option<String> {
+    if let Some(element) = descriptor.strip_prefix('[') {
+        return descriptor_text(element).map(|e| format!("{}[]", e));
+    }
+    let text = match descriptor {
+        "B" => "byte",
+        "S" => "short",
+        "I" => "int",
+        "J" => "long",
+        "Z" => "boolean",
+        "C" => "char",
+        "F" => "float",
+        "D" => "double",
+        "V" => "void",
+        _ => {
+            let internal = descriptor.strip_prefix('L')?.strip_suffix(';')?;
+            let simple = internal.rsplit('/').next()?;
+            return Some(simple.replace('$', "."));
+        }
+    };
+    Some(text.to_string())
+}
+
 pub fn infer_expr_type(node: Node, rope: &Rope) -> InferredType {
     match node.kind() {
         // 1. 字面量处理
@@ -130,3 +190,76 @@ pub fn get_method_def_param_count(method_node: Node) -> Option<usize> {
 
     None
 }
+
+/// Renders the declaration header of `node` the way it reads in source:
+/// modifiers, type parameters, return type, name and parameter list.
+/// Annotations and bodies are dropped and whitespace is collapsed.
+pub fn declaration_signature(node: Node, rope: &Rope) -> Option<String> {
+    match node.kind() {
+        "method_declaration"
+        | "constructor_declaration"
+        | "class_declaration"
+        | "interface_declaration"
+        | "enum_declaration"
+        | "record_declaration"
+        | "annotation_type_declaration" => {
+            let end = node
+                .child_by_field_name("body")
+                .map(|b| b.start_byte())
+                .unwrap_or(node.end_byte());
+            let text = slice_bytes(rope, header_start(node), end);
+            Some(collapse_whitespace(text.trim_end_matches(';')))
+        }
+        "variable_declarator" => {
+            let decl = node.parent()?;
+            let type_node = decl.child_by_field_name("type")?;
+            let name = get_node_text(node.child_by_field_name("name")?, rope);
+            let head = slice_bytes(rope, header_start(decl), type_node.end_byte());
+            Some(format!("{} {}", collapse_whitespace(&head), name))
+        }
+        "enum_constant" => {
+            let enum_decl = node.parent()?.parent()?;
+            let enum_name = get_node_text(enum_decl.child_by_field_name("name")?, rope);
+            let name = get_node_text(node.child_by_field_name("name")?, rope);
+            Some(format!("{} {}", enum_name, name))
+        }
+        "formal_parameter" | "spread_parameter" | "resource" | "catch_formal_parameter" => {
+            let name = node.child_by_field_name("name")?;
+            let text = slice_bytes(rope, header_start(node), name.end_byte());
+            Some(collapse_whitespace(&text))
+        }
+        _ => None,
+    }
+}
+
+/// Byte offset where the declaration header starts once leading
+/// annotations inside `modifiers` are skipped.
+fn header_start(node: Node) -> usize {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.kind() != "modifiers" {
+            return child.start_byte();
+        }
+        let mut mod_cursor = child.walk();
+        if let Some(keyword) = child
+            .children(&mut mod_cursor)
+            .find(|m| m.kind() != "marker_annotation" && m.kind() != "annotation")
+        {
+            return keyword.start_byte();
+        }
+    }
+    node.start_byte()
+}
+
+fn slice_bytes(rope: &Rope, start: usize, end: usize) -> String {
+    let start = rope.byte_to_char(start);
+    let end = rope.byte_to_char(end);
+    if end <= start {
+        return String::new();
+    }
+    rope.slice(start..end).to_string()
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
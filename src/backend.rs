@@ -1,42 +1,102 @@
-use crate::filesystem::collect_files_with_ext;
+use crate::cache::{CachedSource, FileStamp, IndexCache};
+use crate::diagnostics::syntax_diagnostics;
+use crate::filesystem::SourceFilter;
+use crate::hierarchy;
+use crate::implementation::find_implementations;
+use crate::imports::ImportLayout;
 use crate::indexer::Indexer;
 use crate::lang::{LanguageService, java::JavaService};
-use crate::library::SourceArchiveRegistry;
+use crate::library::{
+    JdkClassArchives, JdkStubProvider, LibraryFile, SourceArchiveRegistry, attached_sources,
+    count_entries, for_each_attached_source, for_each_class_stub, for_each_source_file,
+    jar_entry_uri,
+};
+use crate::pool::{Job, JobCounter, index_parallel};
+use crate::progress::Progress;
+use crate::project::{BuildModel, SourceRoot, collect_sources, is_workspace_source, load_build};
+use crate::references::find_references;
+use crate::rename;
 use crate::state::{Document, GlobalIndex};
+use crate::stubs::Stub;
+use crate::utils::{byte_to_point, get_node_at_pos, position_to_char};
+use crate::workspace_symbols::workspace_symbols;
 use dashmap::DashMap;
 use ropey::Rope;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::request::{
+    GotoImplementationParams, GotoImplementationResponse, GotoTypeDefinitionParams,
+    GotoTypeDefinitionResponse,
+};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
-use tree_sitter::{InputEdit, Point};
-use zip::ZipArchive;
+use tree_sitter::InputEdit;
+
+/// Quiet period after the last edit before diagnostics are recomputed.
+const DIAGNOSTICS_DEBOUNCE: Duration = Duration::from_millis(300);
+
+type Services = HashMap<String, Arc<dyn LanguageService>>;
+
+/// Workspace folders by root directory.
+type Folders = Arc<RwLock<BTreeMap<PathBuf, FolderModel>>>;
+
+/// What is known about one workspace folder. Folders resolve against each
+/// other through the shared index; only their builds are kept apart.
+#[derive(Debug, Clone, Default)]
+struct FolderModel {
+    build: BuildModel,
+    /// Roots the last workspace pass indexed sources from.
+    source_roots: Vec<SourceRoot>,
+    /// Directory URIs of the folder's test source roots.
+    test_roots: Vec<Url>,
+}
 
 #[derive(Clone)]
 pub struct ServerConfig {
     pub keywords: Vec<String>,
+    pub import_layout: ImportLayout,
+    /// Library jars indexed from their class files.
+    pub classpath: Vec<PathBuf>,
+    /// Globs of workspace paths, relative to the root, never indexed.
+    pub exclude: Vec<String>,
+    /// Where index entries persist between runs; `None` disables caching.
+    pub cache_dir: Option<PathBuf>,
 }
 
 pub struct LspBackend {
     pub client: Client,
-    pub documents: DashMap<String, Document>,
+    pub documents: Arc<DashMap<String, Document>>,
     pub index: Arc<GlobalIndex>,
-    services: HashMap<String, Box<dyn LanguageService>>,
+    services: Arc<Services>,
     parsers: DashMap<String, Mutex<tree_sitter::Parser>>,
-    workspace_root: RwLock<Option<PathBuf>>,
+    folders: Folders,
     source_archives: Arc<SourceArchiveRegistry>,
+    cache: Option<Arc<IndexCache>>,
     config: ServerConfig,
+    diagnostics: DiagnosticScheduler,
+    /// Whether the client can show `$/progress` notifications.
+    work_done_progress: AtomicBool,
+    /// Whether the client lets us register file watchers.
+    watch_files: AtomicBool,
+    /// Whether the client takes a type hierarchy registration, which
+    /// `ServerCapabilities` cannot express statically here.
+    register_type_hierarchy: AtomicBool,
+    /// Classpath jars in the index, so a build change only touches the
+    /// difference.
+    indexed_jars: Arc<RwLock<Vec<PathBuf>>>,
 }
 
 impl LspBackend {
     pub fn new(client: Client, config: ServerConfig) -> Self {
-        let mut services: HashMap<String, Box<dyn LanguageService>> = HashMap::new();
+        let mut services: Services = HashMap::new();
 
         // TODO: register kotlin service, gradle service
-        services.insert("java".to_string(), Box::new(JavaService));
+        services.insert("java".to_string(), Arc::new(JavaService));
 
         let parsers = DashMap::new();
         for (ext, service) in &services {
@@ -47,15 +107,35 @@ impl LspBackend {
             parsers.insert(ext.clone(), Mutex::new(parser));
         }
 
+        let services = Arc::new(services);
+        let documents = Arc::new(DashMap::new());
+        let index = Arc::new(GlobalIndex::new());
         Self {
+            diagnostics: DiagnosticScheduler {
+                client: client.clone(),
+                documents: documents.clone(),
+                index: index.clone(),
+                services: services.clone(),
+                generations: Arc::new(DashMap::new()),
+                next_generation: Arc::new(AtomicU64::new(0)),
+                index_ready: Arc::new(AtomicBool::new(false)),
+            },
             client,
-            documents: DashMap::new(),
-            index: Arc::new(GlobalIndex::new()),
+            documents,
+            index,
             services,
             parsers,
-            workspace_root: RwLock::new(None),
+            folders: Arc::new(RwLock::new(BTreeMap::new())),
             source_archives: Arc::new(SourceArchiveRegistry::new()),
+            cache: config
+                .cache_dir
+                .clone()
+                .map(|dir| Arc::new(IndexCache::new(dir))),
             config,
+            work_done_progress: AtomicBool::new(false),
+            watch_files: AtomicBool::new(false),
+            register_type_hierarchy: AtomicBool::new(false),
+            indexed_jars: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
@@ -63,69 +143,493 @@ impl LspBackend {
         uri.split('.').next_back().map(|s| s.to_string())
     }
 
-    async fn index_workspace(&self) {
-        let root = match self.workspace_root.read() {
-            Ok(guard) => guard.clone(),
-            Err(_) => None,
+    /// Whether the innermost folder holding `path` indexes it as a source.
+    fn is_workspace_source(&self, path: &Path) -> bool {
+        let Ok(folders) = self.folders.read() else {
+            return false;
         };
+        folders
+            .iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .max_by_key(|(root, _)| root.components().count())
+            .is_some_and(|(root, folder)| {
+                let filter = source_filter(root, &self.config.exclude);
+                is_workspace_source(&folder.source_roots, &filter, path)
+            })
+    }
+
+    /// Text and tree for `uri`, taken from the open buffer when there is one
+    /// and parsed from disk otherwise.
+    fn load_document(&self, uri: &Url) -> Option<Document> {
+        if let Some(doc) = self.documents.get(uri.as_str()) {
+            return Some(Document {
+                text: doc.text.clone(),
+                tree: doc.tree.clone(),
+                version: doc.version,
+            });
+        }
+
+        let path = uri.to_file_path().ok()?;
+        let service = self.services.get(path.extension()?.to_str()?)?;
+        let rope = Rope::from_str(&std::fs::read_to_string(&path).ok()?);
+
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&service.language()).ok()?;
+        let tree = parser.parse_with_options(
+            &mut |offset, _| rope.byte_slice(offset..).chunks().next().unwrap_or(""),
+            None,
+            None,
+        )?;
+        Some(Document {
+            text: rope,
+            tree,
+            version: 0,
+        })
+    }
+
+    /// Locations as a goto response, with library sources materialized;
+    /// `None` when there are none.
+    fn location_response(&self, locations: Vec<Location>) -> Option<GotoDefinitionResponse> {
+        let locations: Vec<_> = locations
+            .into_iter()
+            .map(|location| {
+                self.source_archives
+                    .materialize(&location)
+                    .unwrap_or(location)
+            })
+            .collect();
+        match locations.len() {
+            0 => None,
+            1 => locations
+                .into_iter()
+                .next()
+                .map(GotoDefinitionResponse::Scalar),
+            _ => Some(GotoDefinitionResponse::Array(locations)),
+        }
+    }
 
-        let Some(root) = root else {
-            tracing::info!("Skip workspace indexing: no root provided by client");
+    fn schedule_diagnostics(&self, uri: &str) {
+        self.diagnostics.schedule(uri);
+    }
+
+    /// What the background indexing task needs, detached from `self`.
+    fn background_indexer(&self) -> BackgroundIndexer {
+        BackgroundIndexer {
+            client: self.client.clone(),
+            index: self.index.clone(),
+            documents: self.documents.clone(),
+            source_archives: self.source_archives.clone(),
+            cache: self.cache.clone(),
+            config: self.config.clone(),
+            folders: self.folders.clone(),
+            work_done_progress: self.work_done_progress.load(Ordering::Relaxed),
+            indexed_jars: self.indexed_jars.clone(),
+        }
+    }
+
+    /// Asks the client to report changes to sources and build files made
+    /// outside the editor.
+    async fn register_file_watchers(&self) {
+        let watchers = [
+            "**/*.java",
+            "**/pom.xml",
+            "**/build.gradle*",
+            "**/settings.gradle*",
+            "**/gradle/libs.versions.toml",
+        ]
+        .into_iter()
+        .map(|glob| FileSystemWatcher {
+            glob_pattern: GlobPattern::String(glob.to_string()),
+            kind: None,
+        })
+        .collect();
+        let options = DidChangeWatchedFilesRegistrationOptions { watchers };
+        let registration = Registration {
+            id: "jrsls-watched-files".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(options).ok(),
+        };
+        if let Err(err) = self.client.register_capability(vec![registration]).await {
+            tracing::warn!("Failed to register file watchers: {}", err);
+        }
+    }
+
+    async fn register_type_hierarchy(&self) {
+        let options = TypeHierarchyRegistrationOptions {
+            text_document_registration_options: TextDocumentRegistrationOptions {
+                document_selector: Some(
+                    self.services
+                        .keys()
+                        .map(|ext| DocumentFilter {
+                            language: None,
+                            scheme: None,
+                            pattern: Some(format!("**/*.{}", ext)),
+                        })
+                        .collect(),
+                ),
+            },
+            type_hierarchy_options: TypeHierarchyOptions::default(),
+            static_registration_options: StaticRegistrationOptions::default(),
+        };
+        let registration = Registration {
+            id: "jrsls-type-hierarchy".to_string(),
+            method: "textDocument/prepareTypeHierarchy".to_string(),
+            register_options: serde_json::to_value(options).ok(),
+        };
+        if let Err(err) = self.client.register_capability(vec![registration]).await {
+            tracing::warn!("Failed to register type hierarchy: {}", err);
+        }
+    }
+}
+
+/// Whether changing `path` can change a build model: a POM, a Gradle
+/// build or settings script, or the Gradle version catalog.
+fn is_build_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    match name {
+        "pom.xml" => true,
+        "libs.versions.toml" => path
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .is_some_and(|dir| dir == "gradle"),
+        _ => name.starts_with("build.gradle") || name.starts_with("settings.gradle"),
+    }
+}
+
+/// The workspace filter from `--exclude`, or one without globs when they
+/// do not parse.
+fn source_filter(root: &Path, exclude: &[String]) -> SourceFilter {
+    SourceFilter::new(root.to_path_buf(), exclude).unwrap_or_else(|err| {
+        tracing::warn!("Ignoring invalid exclude globs: {}", err);
+        SourceFilter::new(root.to_path_buf(), &[]).expect("no globs to parse")
+    })
+}
+
+/// Debounced diagnostics for open documents. Cheap to clone, so background
+/// tasks can trigger a refresh too.
+#[derive(Clone)]
+struct DiagnosticScheduler {
+    client: Client,
+    documents: Arc<DashMap<String, Document>>,
+    index: Arc<GlobalIndex>,
+    services: Arc<Services>,
+    /// Bumped on every edit so stale debounced diagnostic runs can bail out.
+    generations: Arc<DashMap<String, u64>>,
+    next_generation: Arc<AtomicU64>,
+    /// Set once startup indexing is done; semantic checks stay silent before.
+    index_ready: Arc<AtomicBool>,
+}
+
+impl DiagnosticScheduler {
+    /// Schedules syntax and semantic diagnostics for an open document once
+    /// edits have settled for `DIAGNOSTICS_DEBOUNCE`.
+    fn schedule(&self, uri: &str) {
+        let Some(service) = uri
+            .split('.')
+            .next_back()
+            .and_then(|ext| self.services.get(ext).cloned())
+        else {
+            return;
+        };
+        let Ok(url) = Url::parse(uri) else {
             return;
         };
 
-        let java_files =
-            match tokio::task::spawn_blocking(move || collect_files_with_ext(root, "java")).await {
-                Ok(list) => list,
-                Err(err) => {
-                    tracing::error!("Failed to collect files for indexing: {err}");
-                    return;
+        let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
+        self.generations.insert(uri.to_string(), generation);
+
+        let uri = uri.to_string();
+        let client = self.client.clone();
+        let documents = self.documents.clone();
+        let index = self.index.clone();
+        let generations = self.generations.clone();
+        let index_ready = self.index_ready.clone();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(DIAGNOSTICS_DEBOUNCE).await;
+            let is_current = || generations.get(&uri).is_some_and(|g| *g == generation);
+            if !is_current() {
+                return;
+            }
+
+            let Some(diagnostics) = documents.get(&uri).map(|doc| {
+                let mut diagnostics = syntax_diagnostics(&doc.tree, &doc.text);
+                if index_ready.load(Ordering::Acquire) {
+                    diagnostics
+                        .extend(service.semantic_diagnostics(&doc.tree, &doc.text, &index, &uri));
                 }
+                diagnostics
+            }) else {
+                return;
             };
 
-        if java_files.is_empty() {
-            tracing::info!("No Java files found during workspace indexing");
-            return;
+            if is_current() {
+                client.publish_diagnostics(url, diagnostics, None).await;
+            }
+        });
+    }
+
+    /// Drops `uri`'s generation so a pending debounced run does not
+    /// republish.
+    fn forget(&self, uri: &str) {
+        self.generations.remove(uri);
+    }
+
+    /// Enables semantic checks and re-runs diagnostics for every open
+    /// document.
+    fn index_finished(&self) {
+        self.index_ready.store(true, Ordering::Release);
+        self.refresh_all();
+    }
+
+    /// Re-runs diagnostics for every open document, e.g. after the index
+    /// changed underneath them.
+    fn refresh_all(&self) {
+        let uris: Vec<String> = self.documents.iter().map(|e| e.key().clone()).collect();
+        for uri in uris {
+            self.schedule(&uri);
+        }
+    }
+}
+
+/// Indexing of the workspace, the JDK and the classpath, at startup and
+/// again when build files change. Runs as a background task so requests
+/// are answered meanwhile, with one progress bar per phase.
+struct BackgroundIndexer {
+    client: Client,
+    index: Arc<GlobalIndex>,
+    documents: Arc<DashMap<String, Document>>,
+    source_archives: Arc<SourceArchiveRegistry>,
+    cache: Option<Arc<IndexCache>>,
+    config: ServerConfig,
+    folders: Folders,
+    work_done_progress: bool,
+    indexed_jars: Arc<RwLock<Vec<PathBuf>>>,
+}
+
+impl BackgroundIndexer {
+    async fn run(self) {
+        let roots = self.roots();
+        if roots.is_empty() {
+            tracing::info!("Skip workspace indexing: no folder provided by client");
+        }
+        for root in &roots {
+            self.index_folder(root).await;
         }
+        self.index_builtin_library().await;
+        self.index_classpath().await;
+    }
 
-        tracing::info!("Indexing {} Java files...", java_files.len());
-        for path in java_files {
-            if let Err(err) = self.index_single_file(&path).await {
-                tracing::warn!("Indexing failed for {:?}: {}", path, err);
+    /// Evicts the sources of `removed` folders, indexes `added` ones, and
+    /// brings the classpath in line with the folders left.
+    async fn update_folders(self, mut added: Vec<PathBuf>, removed: Vec<PathBuf>) {
+        for root in &removed {
+            if let Ok(mut folders) = self.folders.write() {
+                folders.remove(root);
+            }
+            if let Ok(uri) = Url::from_directory_path(root) {
+                let evicted = self.index.remove_files_under(uri.as_str());
+                tracing::info!("Evicted {} files of removed folder {:?}", evicted, root);
             }
         }
-        tracing::info!("Workspace indexing finished");
+        // Folders nested in a removed one lost their files along with it.
+        for root in self.roots() {
+            if removed.iter().any(|gone| root.starts_with(gone)) && !added.contains(&root) {
+                added.push(root);
+            }
+        }
+        self.update_test_roots();
+        for root in &added {
+            self.index_folder(root).await;
+        }
+        self.index_classpath().await;
     }
 
-    async fn index_single_file(&self, path: &std::path::Path) -> anyhow::Result<()> {
-        let ext = path
-            .extension()
-            .and_then(|s| s.to_str())
-            .ok_or_else(|| anyhow::anyhow!("Missing extension"))?;
-        if !self.services.contains_key(ext) {
-            return Ok(());
+    /// Re-reads the build of the folder at `root` after a build file
+    /// changed and brings the indexed classpath in line with it.
+    async fn reload_build(self, root: PathBuf) {
+        let build = self.load_build(&root).await;
+        if let Ok(mut folders) = self.folders.write()
+            && let Some(folder) = folders.get_mut(&root)
+        {
+            folder.build = build;
         }
+        self.index_classpath().await;
+    }
 
-        let uri = Url::from_file_path(path)
-            .map_err(|_| anyhow::anyhow!("Invalid file path for URL: {:?}", path))?;
-        let text = std::fs::read_to_string(path)?;
-        let rope = Rope::from_str(&text);
+    fn roots(&self) -> Vec<PathBuf> {
+        self.folders
+            .read()
+            .map(|folders| folders.keys().cloned().collect())
+            .unwrap_or_default()
+    }
 
-        let parser_lock = self
-            .parsers
-            .get(ext)
-            .ok_or_else(|| anyhow::anyhow!("No parser registered for extension '{}'", ext))?;
-        let mut parser = parser_lock.lock().await;
-        let tree = parser
-            .parse_with_options(
-                &mut |offset, _| rope.byte_slice(offset..).chunks().next().unwrap_or(""),
-                None,
-                None,
-            )
-            .ok_or_else(|| anyhow::anyhow!("Failed to parse file {:?}", path))?;
+    async fn index_folder(&self, root: &Path) {
+        let build = self.load_build(root).await;
+        self.index_workspace(root, build).await;
+    }
 
-        Indexer::update_file(&self.index, &uri.to_string(), &tree, &rope);
-        Ok(())
+    /// Test roots of every folder, for visibility checks.
+    fn update_test_roots(&self) {
+        let Ok(folders) = self.folders.read() else {
+            return;
+        };
+        // Jars only tests compile against are test sources too, unless some
+        // folder's main code compiles against them as well.
+        let main_jars: HashSet<_> = self
+            .config
+            .classpath
+            .iter()
+            .chain(folders.values().flat_map(|folder| &folder.build.classpath))
+            .collect();
+        let test_jars = folders
+            .values()
+            .flat_map(|folder| &folder.build.test_classpath)
+            .filter(|jar| !main_jars.contains(jar))
+            .filter_map(|jar| jar_entry_uri(jar, ""));
+        self.index.set_test_roots(
+            folders
+                .values()
+                .flat_map(|folder| folder.test_roots.iter().cloned())
+                .chain(test_jars)
+                .collect(),
+        );
+    }
+
+    async fn progress(&self, title: &str) -> Progress {
+        Progress::begin(&self.client, self.work_done_progress, title).await
+    }
+
+    /// The Maven or Gradle model of the folder at `root`, if it has one.
+    async fn load_build(&self, root: &Path) -> BuildModel {
+        let root = root.to_path_buf();
+        match tokio::task::spawn_blocking(move || load_build(&root)).await {
+            Ok(build) => build,
+            Err(err) => {
+                tracing::warn!("Project model task panicked: {}", err);
+                BuildModel::default()
+            }
+        }
+    }
+
+    /// Indexes the sources of the folder at `root` and records its model,
+    /// unless the folder was removed in the meantime.
+    async fn index_workspace(&self, root: &Path, build: BuildModel) {
+        let root = root.to_path_buf();
+        let filter = source_filter(&root, &self.config.exclude);
+        let walk_build = build.clone();
+        let walk_root = root.clone();
+        let sources = match tokio::task::spawn_blocking(move || {
+            collect_sources(&walk_root, &walk_build, &filter)
+        })
+        .await
+        {
+            Ok(sources) => sources,
+            Err(err) => {
+                tracing::error!("Failed to collect files for indexing: {err}");
+                return;
+            }
+        };
+        let test_roots = sources
+            .roots
+            .iter()
+            .filter(|r| r.is_test)
+            .filter_map(|r| Url::from_directory_path(&r.dir).ok())
+            .collect();
+        match self.folders.write() {
+            Ok(mut folders) => match folders.get_mut(&root) {
+                Some(folder) => {
+                    *folder = FolderModel {
+                        build,
+                        source_roots: sources.roots,
+                        test_roots,
+                    }
+                }
+                None => return,
+            },
+            Err(_) => return,
+        }
+        self.update_test_roots();
+        let java_files = sources.files;
+
+        if java_files.is_empty() {
+            tracing::info!("No Java files found in {:?}", root);
+            return;
+        }
+
+        tracing::info!("Indexing {} Java files in {:?}...", java_files.len(), root);
+        let name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| root.display().to_string());
+        let progress = self.progress(&format!("Indexing {}", name)).await;
+        let counter = Arc::new(JobCounter::default());
+        counter.add_total(java_files.len());
+        let index = self.index.clone();
+        let cache = self.cache.clone();
+        let documents = self.documents.clone();
+        let task_counter = counter.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            let mut cached = cache
+                .as_ref()
+                .map(|cache| cache.load_workspace(&root))
+                .unwrap_or_default();
+            let mut stamped = Vec::new();
+            let (reused, _) = index_parallel(&index, &task_counter, |submit| {
+                let mut reused = 0;
+                for path in java_files {
+                    let Ok(uri) = Url::from_file_path(&path) else {
+                        continue;
+                    };
+                    // Open buffers are indexed from their text and may
+                    // already hold unsaved declarations.
+                    if documents.contains_key(uri.as_str()) {
+                        task_counter.done.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    }
+                    let stamp = FileStamp::of(&path);
+                    match cached.remove(&path) {
+                        Some(source) if Some(source.stamp) == stamp => {
+                            submit(Job::Entry(source.entry));
+                            reused += 1;
+                        }
+                        _ => submit(Job::File {
+                            uri: uri.to_string(),
+                            path: path.clone(),
+                        }),
+                    }
+                    if let Some(stamp) = stamp {
+                        stamped.push((path, uri, stamp));
+                    }
+                }
+                reused
+            });
+            tracing::info!("Workspace indexing finished ({} files from cache)", reused);
+
+            let Some(cache) = cache else {
+                return;
+            };
+            // Open buffers may differ from disk; they are not worth keeping.
+            let fresh = stamped
+                .into_iter()
+                .filter(|(_, uri, _)| !documents.contains_key(uri.as_str()))
+                .filter_map(|(path, uri, stamp)| {
+                    let entry = index.file_entry(uri.as_str())?;
+                    Some(CachedSource { path, stamp, entry })
+                })
+                .collect();
+            // Writing the cache need not hold up startup.
+            std::thread::spawn(move || cache.store_workspace(&root, fresh));
+        });
+        let result = progress.track(&counter, task).await;
+        if let Err(err) = result {
+            tracing::error!("Workspace indexing task panicked: {}", err);
+        }
+        progress
+            .end(format!("Indexed {} files", counter.snapshot().1))
+            .await;
     }
 
     async fn index_builtin_library(&self) {
@@ -144,90 +648,368 @@ impl LspBackend {
         candidates.retain(|p| p.exists());
 
         let Some(zip_path) = candidates.into_iter().next() else {
-            tracing::info!("No src.zip found in JAVA_HOME; skip JDK source indexing");
+            match JdkClassArchives::locate(&java_home) {
+                Some(archives) => self.index_jdk_class_files(archives).await,
+                None => tracing::info!(
+                    "No src.zip, jmods or rt.jar found in JAVA_HOME; skip JDK indexing"
+                ),
+            }
             return;
         };
 
         tracing::info!("Indexing JDK sources from {:?}", zip_path);
         self.source_archives
             .register_zip("jrsls-std", zip_path.clone());
+        let progress = self.progress("Indexing JDK").await;
+        let counter = Arc::new(JobCounter::default());
         let index = self.index.clone();
+        let cache = self.cache.clone();
+        let task_counter = counter.clone();
 
-        let result = tokio::task::spawn_blocking(move || {
-            let file = std::fs::File::open(&zip_path)?;
-            let mut archive = ZipArchive::new(file)?;
+        let task = tokio::task::spawn_blocking(move || {
+            let (submitted, _) = index_parallel(&index, &task_counter, |submit| {
+                submit_archive(
+                    cache.as_deref(),
+                    &task_counter,
+                    &zip_path,
+                    "java",
+                    submit,
+                    |submit| {
+                        for_each_source_file(&zip_path, |name, text| {
+                            let uri = format!("jrsls-std:///{}", name);
+                            submit(Job::Source { uri, text });
+                        })
+                    },
+                )
+            });
+            let uris = submitted?;
+            store_archives(cache.as_deref(), &index, [(zip_path, uris)]);
+            anyhow::Ok(())
+        });
 
-            let mut parser = tree_sitter::Parser::new();
-            parser
-                .set_language(&tree_sitter_java::LANGUAGE.into())
-                .map_err(|e| anyhow::anyhow!("Failed to load Java grammar: {}", e))?;
+        match progress.track(&counter, task).await {
+            Ok(Ok(_)) => tracing::info!("JDK source indexing finished"),
+            Ok(Err(err)) => tracing::warn!("JDK source indexing failed: {}", err),
+            Err(err) => tracing::warn!("JDK source indexing task panicked: {}", err),
+        }
+        progress
+            .end(format!("Indexed {} files", counter.snapshot().1))
+            .await;
+    }
 
-            for i in 0..archive.len() {
-                let mut entry = archive.by_index(i)?;
-                if entry.is_dir() {
-                    continue;
-                }
-                let name = entry.name().to_string();
-                if !name.ends_with(".java") {
-                    continue;
+    /// Fallback for JDKs shipped without `src.zip`: index stubs rendered
+    /// from the class files in `jmods/` or `rt.jar`.
+    async fn index_jdk_class_files(&self, archives: JdkClassArchives) {
+        tracing::info!("Indexing JDK class files from {:?}", archives);
+        self.source_archives.register(
+            "jrsls-std",
+            Arc::new(JdkStubProvider::new(archives.clone())),
+        );
+        let progress = self.progress("Indexing JDK").await;
+        let counter = Arc::new(JobCounter::default());
+        let index = self.index.clone();
+        let cache = self.cache.clone();
+        let task_counter = counter.clone();
+
+        let task = tokio::task::spawn_blocking(move || {
+            let (submitted, _) = index_parallel(&index, &task_counter, |submit| {
+                let mut submitted = Vec::new();
+                for (archive_path, prefix) in archives.archives() {
+                    let uris = submit_archive(
+                        cache.as_deref(),
+                        &task_counter,
+                        &archive_path,
+                        "class",
+                        submit,
+                        |submit| {
+                            for_each_class_stub(&archive_path, |path, stub| {
+                                let uri = format!("jrsls-std:///{}{}", prefix, path);
+                                submit_stub(submit, &uri, &stub);
+                            })
+                        },
+                    );
+                    match uris {
+                        Ok(uris) => submitted.push((archive_path, uris)),
+                        Err(err) => tracing::warn!("Failed to index {:?}: {}", archive_path, err),
+                    }
                 }
+                submitted
+            });
+            store_archives(cache.as_deref(), &index, submitted);
+        });
+
+        match progress.track(&counter, task).await {
+            Ok(()) => tracing::info!("JDK class file indexing finished"),
+            Err(err) => tracing::warn!("JDK class file indexing task panicked: {}", err),
+        }
+        progress
+            .end(format!("Indexed {} classes", counter.snapshot().1))
+            .await;
+    }
+
+    /// Indexes the configured classpath plus the jars resolved from the
+    /// build of every folder. Each jar is indexed from its attached
+    /// `-sources.jar` when there is one, from its class files otherwise.
+    /// Jars indexed earlier are kept if still listed and dropped if not.
+    async fn index_classpath(&self) {
+        let mut jars = self.config.classpath.clone();
+        if let Ok(folders) = self.folders.read() {
+            jars.extend(folders.values().flat_map(|folder| {
+                folder
+                    .build
+                    .classpath
+                    .iter()
+                    .chain(&folder.build.test_classpath)
+                    .cloned()
+            }));
+        }
+        jars.retain(|p| p.extension().is_some_and(|ext| ext == "jar") && p.is_file());
+        let mut seen = HashSet::new();
+        jars.retain(|jar| seen.insert(jar.clone()));
 
-                let mut contents = String::new();
-                use std::io::Read;
-                entry.read_to_string(&mut contents)?;
-
-                let rope = Rope::from_str(&contents);
-                let tree = parser
-                    .parse_with_options(
-                        &mut |offset, _| rope.byte_slice(offset..).chunks().next().unwrap_or(""),
-                        None,
-                        None,
-                    )
-                    .ok_or_else(|| anyhow::anyhow!("Failed to parse {}", name))?;
-
-                let uri = format!("jrsls-std:///{}", name);
-                Indexer::update_file(&index, &uri, &tree, &rope);
+        let previous = match self.indexed_jars.write() {
+            Ok(mut indexed) => std::mem::replace(&mut *indexed, jars.clone()),
+            Err(_) => Vec::new(),
+        };
+        for jar in previous.iter().filter(|jar| !jars.contains(jar)) {
+            if let Some(prefix) = jar_entry_uri(jar, "") {
+                let removed = self.index.remove_files_under(prefix.as_str());
+                tracing::info!("Dropped {} entries of {:?} from the index", removed, jar);
             }
+        }
+        jars.retain(|jar| !previous.contains(jar));
+        if jars.is_empty() {
+            return;
+        }
 
-            anyhow::Ok(())
-        })
-        .await;
+        tracing::info!("Indexing {} classpath jars", jars.len());
+        let jars: Vec<_> = jars
+            .into_iter()
+            .map(|jar| {
+                let sources = attached_sources(&jar);
+                self.source_archives
+                    .register_jar(jar.clone(), sources.clone());
+                (jar, sources)
+            })
+            .collect();
+        let progress = self.progress("Indexing dependencies").await;
+        let counter = Arc::new(JobCounter::default());
+        let index = self.index.clone();
+        let cache = self.cache.clone();
+        let task_counter = counter.clone();
 
-        match result {
-            Ok(Ok(_)) => tracing::info!("JDK source indexing finished"),
-            Ok(Err(err)) => tracing::warn!("JDK source indexing failed: {}", err),
-            Err(err) => tracing::warn!("JDK source indexing task panicked: {}", err),
+        let task = tokio::task::spawn_blocking(move || {
+            let (submitted, _) = index_parallel(&index, &task_counter, |submit| {
+                let mut submitted = Vec::new();
+                for (jar, sources) in jars {
+                    // Entry URIs point into the binary jar either way, so the
+                    // archive actually read is what the cache is keyed on.
+                    let archive = sources.clone().unwrap_or_else(|| jar.clone());
+                    let extension = if sources.is_some() { "java" } else { "class" };
+                    let uris = submit_archive(
+                        cache.as_deref(),
+                        &task_counter,
+                        &archive,
+                        extension,
+                        submit,
+                        |submit| match &sources {
+                            Some(sources) => {
+                                for_each_attached_source(&jar, sources, |path, file| {
+                                    let Some(uri) = jar_entry_uri(&jar, path) else {
+                                        return;
+                                    };
+                                    match file {
+                                        LibraryFile::Source(text) => submit(Job::Source {
+                                            uri: uri.to_string(),
+                                            text,
+                                        }),
+                                        LibraryFile::Stub(stub) => {
+                                            submit_stub(submit, uri.as_str(), &stub)
+                                        }
+                                    }
+                                })
+                            }
+                            None => for_each_class_stub(&jar, |path, stub| {
+                                if let Some(uri) = jar_entry_uri(&jar, &path) {
+                                    submit_stub(submit, uri.as_str(), &stub);
+                                }
+                            }),
+                        },
+                    );
+                    match uris {
+                        Ok(uris) => submitted.push((archive, uris)),
+                        Err(err) => tracing::warn!("Failed to index {:?}: {}", jar, err),
+                    }
+                }
+                submitted
+            });
+            store_archives(cache.as_deref(), &index, submitted);
+        });
+
+        match progress.track(&counter, task).await {
+            Ok(()) => tracing::info!("Classpath indexing finished"),
+            Err(err) => tracing::warn!("Classpath indexing task panicked: {}", err),
+        }
+        progress
+            .end(format!("Indexed {} files", counter.snapshot().1))
+            .await;
+    }
+}
+
+/// Submits the jobs for one archive: its cached entries when the cache has
+/// it unchanged, whatever `fill` submits otherwise. Adds the expected job
+/// count, estimated from the archive's `extension` files, to `counter`.
+/// Returns the URIs `fill` submitted, which `store_archives` caches once
+/// they are indexed.
+fn submit_archive(
+    cache: Option<&IndexCache>,
+    counter: &JobCounter,
+    archive: &Path,
+    extension: &str,
+    submit: &mut dyn FnMut(Job),
+    fill: impl FnOnce(&mut dyn FnMut(Job)) -> anyhow::Result<()>,
+) -> anyhow::Result<Option<Vec<String>>> {
+    if let Some(entries) = cache.and_then(|cache| cache.load_archive(archive)) {
+        tracing::debug!(
+            "Loaded {} entries for {:?} from cache",
+            entries.len(),
+            archive
+        );
+        counter.add_total(entries.len());
+        for entry in entries {
+            submit(Job::Entry(entry));
         }
+        return Ok(None);
+    }
+
+    counter.add_total(count_entries(archive, extension)?);
+    let mut uris = Vec::new();
+    fill(&mut |job| {
+        uris.push(job.uri().to_string());
+        submit(job);
+    })?;
+    Ok(cache.is_some().then_some(uris))
+}
+
+fn submit_stub(submit: &mut dyn FnMut(Job), uri: &str, stub: &Stub) {
+    if let Some(entry) = Indexer::stub_entry(uri, stub) {
+        submit(Job::Entry(entry));
+    }
+}
+
+/// Caches what was indexed for freshly read archives.
+fn store_archives(
+    cache: Option<&IndexCache>,
+    index: &GlobalIndex,
+    archives: impl IntoIterator<Item = (PathBuf, Option<Vec<String>>)>,
+) {
+    let Some(cache) = cache else {
+        return;
+    };
+    for (archive, uris) in archives {
+        let Some(uris) = uris else {
+            continue;
+        };
+        let entries = uris
+            .iter()
+            .filter_map(|uri| index.file_entry(uri))
+            .collect();
+        cache.store_archive(&archive, entries);
     }
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for LspBackend {
     async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
-        if let Some(root) = params
-            .root_uri
-            .and_then(|u| u.to_file_path().ok())
-            .or_else(|| {
-                params
-                    .workspace_folders
-                    .as_ref()
-                    .and_then(|folders| folders.first())
-                    .and_then(|folder| folder.uri.to_file_path().ok())
-            })
-        {
-            if let Ok(mut guard) = self.workspace_root.write() {
-                *guard = Some(root);
+        let work_done_progress = params
+            .capabilities
+            .window
+            .as_ref()
+            .and_then(|window| window.work_done_progress)
+            .unwrap_or(false);
+        self.work_done_progress
+            .store(work_done_progress, Ordering::Relaxed);
+        let watch_files = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.did_change_watched_files)
+            .and_then(|watched| watched.dynamic_registration)
+            .unwrap_or(false);
+        self.watch_files.store(watch_files, Ordering::Relaxed);
+        let type_hierarchy = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|text_document| text_document.type_hierarchy.as_ref())
+            .and_then(|type_hierarchy| type_hierarchy.dynamic_registration)
+            .unwrap_or(false);
+        self.register_type_hierarchy
+            .store(type_hierarchy, Ordering::Relaxed);
+
+        let roots: Vec<PathBuf> = match params.workspace_folders {
+            Some(folders) if !folders.is_empty() => folders
+                .iter()
+                .filter_map(|folder| folder.uri.to_file_path().ok())
+                .collect(),
+            _ => params
+                .root_uri
+                .and_then(|u| u.to_file_path().ok())
+                .into_iter()
+                .collect(),
+        };
+        if let Ok(mut folders) = self.folders.write() {
+            for root in roots {
+                folders.insert(root, FolderModel::default());
             }
         }
 
         tracing::info!("Lsp Initialzed");
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
-                text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::INCREMENTAL,
+                text_document_sync: Some(TextDocumentSyncCapability::Options(
+                    TextDocumentSyncOptions {
+                        open_close: Some(true),
+                        change: Some(TextDocumentSyncKind::INCREMENTAL),
+                        save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
+                            include_text: Some(false),
+                        })),
+                        ..Default::default()
+                    },
                 )),
                 definition_provider: Some(OneOf::Left(true)),
+                type_definition_provider: Some(TypeDefinitionProviderCapability::Simple(true)),
+                implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                references_provider: Some(OneOf::Left(true)),
+                call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                }),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                })),
                 document_symbol_provider: Some(OneOf::Left(true)),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                        supported: Some(true),
+                        change_notifications: Some(OneOf::Left(true)),
+                    }),
+                    file_operations: None,
+                }),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![
+                            CodeActionKind::QUICKFIX,
+                            CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
+                        ]),
+                        ..CodeActionOptions::default()
+                    },
+                )),
                 completion_provider: Some(CompletionOptions {
                     resolve_provider: Some(false),
                     trigger_characters: Some(vec![".".to_string()]),
@@ -243,8 +1025,19 @@ impl LanguageServer for LspBackend {
         self.client
             .log_message(MessageType::INFO, "Server initialized!")
             .await;
-        self.index_workspace().await;
-        self.index_builtin_library().await;
+        // Requests are served meanwhile, against whatever is indexed so far.
+        let indexer = self.background_indexer();
+        let diagnostics = self.diagnostics.clone();
+        tokio::spawn(async move {
+            indexer.run().await;
+            diagnostics.index_finished();
+        });
+        if self.watch_files.load(Ordering::Relaxed) {
+            self.register_file_watchers().await;
+        }
+        if self.register_type_hierarchy.load(Ordering::Relaxed) {
+            self.register_type_hierarchy().await;
+        }
     }
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
@@ -272,11 +1065,18 @@ impl LanguageServer for LspBackend {
             .unwrap();
 
         tracing::info!("Parsed file {}", uri);
-        self.documents
-            .insert(uri.clone(), Document { text: rope, tree });
+        self.documents.insert(
+            uri.clone(),
+            Document {
+                text: rope,
+                tree,
+                version: params.text_document.version,
+            },
+        );
         if let Some(doc) = self.documents.get(&uri) {
             Indexer::update_file(&self.index, &uri, &doc.tree, &doc.text);
         }
+        self.schedule_diagnostics(&uri);
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
@@ -287,6 +1087,17 @@ impl LanguageServer for LspBackend {
         };
 
         if let Some(mut doc) = self.documents.get_mut(&uri) {
+            let version = params.text_document.version;
+            if version <= doc.version {
+                tracing::warn!(
+                    "Ignoring stale change to {} (version {}, have {})",
+                    uri,
+                    version,
+                    doc.version
+                );
+                return;
+            }
+
             let parser_lock = self.parsers.get(&ext);
             if parser_lock.is_none() {
                 return;
@@ -313,35 +1124,26 @@ impl LanguageServer for LspBackend {
                 }
 
                 let range = change.range.unwrap();
-                let start_line = range.start.line as usize;
-                let end_line = range.end.line as usize;
-
-                // 防御性检查
-                if start_line >= doc.text.len_lines() || end_line >= doc.text.len_lines() {
-                    continue;
-                }
-
-                let start_char_idx =
-                    doc.text.line_to_char(start_line) + range.start.character as usize;
-                let end_char_idx = doc.text.line_to_char(end_line) + range.end.character as usize;
+                // Out-of-range positions are clamped as the spec asks rather
+                // than skipped, which would leave the buffer out of sync.
+                let start_char_idx = position_to_char(&doc.text, range.start);
+                let end_char_idx = position_to_char(&doc.text, range.end).max(start_char_idx);
                 let start_byte = doc.text.char_to_byte(start_char_idx);
                 let old_end_byte = doc.text.char_to_byte(end_char_idx);
+                let start_position = byte_to_point(&doc.text, start_byte);
+                let old_end_position = byte_to_point(&doc.text, old_end_byte);
 
                 doc.text.remove(start_char_idx..end_char_idx);
                 doc.text.insert(start_char_idx, &change.text);
 
                 let new_end_byte = start_byte + change.text.len();
-                let new_end_char_idx = doc.text.byte_to_char(new_end_byte);
-                let new_end_line = doc.text.char_to_line(new_end_char_idx);
-                let new_end_col = new_end_char_idx - doc.text.line_to_char(new_end_line);
-
                 let edit = InputEdit {
                     start_byte,
                     old_end_byte,
                     new_end_byte,
-                    start_position: Point::new(start_line, range.start.character as usize),
-                    old_end_position: Point::new(end_line, range.end.character as usize),
-                    new_end_position: Point::new(new_end_line, new_end_col),
+                    start_position,
+                    old_end_position,
+                    new_end_position: byte_to_point(&doc.text, new_end_byte),
                 };
 
                 doc.tree.edit(&edit);
@@ -357,10 +1159,153 @@ impl LanguageServer for LspBackend {
                 .unwrap();
 
             doc.tree = new_tree;
+            doc.version = version;
 
             // update global index
             Indexer::update_file(&self.index, &uri, &doc.tree, &doc.text);
         }
+        self.schedule_diagnostics(&uri);
+    }
+
+    async fn did_save(&self, _: DidSaveTextDocumentParams) {
+        // The index already follows the buffer; other open files may depend
+        // on what was just saved, though.
+        self.diagnostics.refresh_all();
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        self.diagnostics.forget(uri.as_str());
+        self.documents.remove(uri.as_str());
+
+        // Unsaved edits are gone with the buffer; index what is on disk,
+        // unless the file is one the workspace pass leaves out, such as
+        // build output or a materialized library source.
+        if uri.scheme() == "file" {
+            let restored = uri
+                .to_file_path()
+                .is_ok_and(|path| self.is_workspace_source(&path))
+                .then(|| self.load_document(&uri))
+                .flatten();
+            match restored {
+                Some(doc) => Indexer::update_file(&self.index, uri.as_str(), &doc.tree, &doc.text),
+                None => {
+                    self.index.remove_file(uri.as_str());
+                }
+            }
+            self.diagnostics.refresh_all();
+        }
+
+        self.client.publish_diagnostics(uri, Vec::new(), None).await;
+    }
+
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        let paths = |folders: Vec<WorkspaceFolder>| -> Vec<PathBuf> {
+            folders
+                .into_iter()
+                .filter_map(|folder| folder.uri.to_file_path().ok())
+                .collect()
+        };
+        let added = paths(params.event.added);
+        let mut removed = paths(params.event.removed);
+        removed.retain(|root| !added.contains(root));
+        if let Ok(mut folders) = self.folders.write() {
+            for root in &added {
+                folders.entry(root.clone()).or_default();
+            }
+        }
+
+        tracing::info!(
+            "Workspace folders added: {:?}, removed: {:?}",
+            added,
+            removed
+        );
+        let indexer = self.background_indexer();
+        let diagnostics = self.diagnostics.clone();
+        tokio::spawn(async move {
+            indexer.update_folders(added, removed).await;
+            diagnostics.refresh_all();
+        });
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let folders: Vec<(PathBuf, SourceFilter)> = self
+            .folders
+            .read()
+            .map(|folders| {
+                folders
+                    .keys()
+                    .map(|root| (root.clone(), source_filter(root, &self.config.exclude)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        // The innermost folder holding `path`.
+        let folder_of = |path: &Path| {
+            folders
+                .iter()
+                .filter(|(root, _)| path.starts_with(root))
+                .max_by_key(|(root, _)| root.components().count())
+        };
+
+        let mut reindex = Vec::new();
+        let mut removed = false;
+        let mut changed_builds = BTreeSet::new();
+        for change in params.changes {
+            let Ok(path) = change.uri.to_file_path() else {
+                continue;
+            };
+            if is_build_file(&path) {
+                changed_builds.extend(folder_of(&path).map(|(root, _)| root.clone()));
+                continue;
+            }
+            if path.extension().is_none_or(|ext| ext != "java") {
+                continue;
+            }
+            let uri = change.uri.to_string();
+            // The open buffer, not the disk, is what the index reflects.
+            if self.documents.contains_key(&uri) {
+                continue;
+            }
+            if change.typ == FileChangeType::DELETED {
+                removed |= self.index.remove_file(&uri);
+            } else if self.index.file_info(&uri).is_some()
+                || folder_of(&path).is_some_and(|(_, filter)| filter.accepts(&path))
+            {
+                reindex.push(Job::File { uri, path });
+            }
+        }
+
+        let diagnostics = self.diagnostics.clone();
+        if !reindex.is_empty() {
+            tracing::info!("Re-indexing {} files changed on disk", reindex.len());
+            let index = self.index.clone();
+            tokio::spawn(async move {
+                let task = tokio::task::spawn_blocking(move || {
+                    index_parallel(&index, &JobCounter::default(), |submit| {
+                        reindex.into_iter().for_each(submit)
+                    })
+                });
+                if let Err(err) = task.await {
+                    tracing::warn!("Re-indexing task panicked: {}", err);
+                }
+                diagnostics.refresh_all();
+            });
+        } else if removed {
+            diagnostics.refresh_all();
+        }
+
+        for root in changed_builds {
+            tracing::info!(
+                "Build files of {:?} changed; recomputing the classpath",
+                root
+            );
+            let indexer = self.background_indexer();
+            let diagnostics = self.diagnostics.clone();
+            tokio::spawn(async move {
+                indexer.reload_build(root).await;
+                diagnostics.refresh_all();
+            });
+        }
     }
 
     async fn document_symbol(
@@ -413,6 +1358,335 @@ impl LanguageServer for LspBackend {
         Ok(None)
     }
 
+    async fn goto_type_definition(
+        &self,
+        params: GotoTypeDefinitionParams,
+    ) -> Result<Option<GotoTypeDefinitionResponse>> {
+        let uri = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .to_string();
+        let position = params.text_document_position_params.position;
+        let Some(service) = self.get_ext(&uri).and_then(|ext| self.services.get(&ext)) else {
+            return Ok(None);
+        };
+        let locations = match self.documents.get(&uri) {
+            Some(doc) => service.type_definition(&doc.tree, &doc.text, position, &self.index, &uri),
+            None => return Ok(None),
+        };
+        Ok(self.location_response(locations))
+    }
+
+    async fn goto_implementation(
+        &self,
+        params: GotoImplementationParams,
+    ) -> Result<Option<GotoImplementationResponse>> {
+        let uri = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .to_string();
+        let position = params.text_document_position_params.position;
+        let Some(service) = self.get_ext(&uri).and_then(|ext| self.services.get(&ext)) else {
+            return Ok(None);
+        };
+        let locations = match self.documents.get(&uri) {
+            Some(doc) => find_implementations(
+                service.as_ref(),
+                &doc.tree,
+                &doc.text,
+                position,
+                &self.index,
+                &uri,
+            ),
+            None => return Ok(None),
+        };
+        Ok(self.location_response(locations))
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let uri = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .to_string();
+        let position = params.text_document_position_params.position;
+        let ext = match self.get_ext(&uri) {
+            Some(e) => e,
+            None => return Ok(None),
+        };
+
+        if let Some(doc) = self.documents.get(&uri)
+            && let Some(service) = self.services.get(&ext)
+        {
+            return Ok(service.hover(&doc.tree, &doc.text, position, &self.index, &uri));
+        }
+
+        Ok(None)
+    }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let uri = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .to_string();
+        let position = params.text_document_position_params.position;
+        let ext = match self.get_ext(&uri) {
+            Some(e) => e,
+            None => return Ok(None),
+        };
+
+        if let Some(doc) = self.documents.get(&uri)
+            && let Some(service) = self.services.get(&ext)
+        {
+            return Ok(service.signature_help(&doc.tree, &doc.text, position, &self.index, &uri));
+        }
+
+        Ok(None)
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let uri = params.text_document_position.text_document.uri.to_string();
+        let position = params.text_document_position.position;
+        let ext = match self.get_ext(&uri) {
+            Some(e) => e,
+            None => return Ok(None),
+        };
+        let Some(service) = self.services.get(&ext) else {
+            return Ok(None);
+        };
+
+        // Resolve the target first and release the document before scanning,
+        // since the scan may read other open buffers.
+        let resolved = self.documents.get(&uri).and_then(|doc| {
+            let target =
+                service.declaration_location(&doc.tree, &doc.text, position, &self.index, &uri)?;
+            let (_, name) = get_node_at_pos(&doc.tree, &doc.text, position)?;
+            Some((target, name))
+        });
+        let Some((target, name)) = resolved else {
+            return Ok(None);
+        };
+
+        let locations = find_references(
+            service.as_ref(),
+            &self.index,
+            &target,
+            &name,
+            params.context.include_declaration,
+            &|uri| self.load_document(uri),
+        );
+        Ok(Some(locations))
+    }
+
+    async fn prepare_type_hierarchy(
+        &self,
+        params: TypeHierarchyPrepareParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let uri = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .to_string();
+        let position = params.text_document_position_params.position;
+        let Some(service) = self.get_ext(&uri).and_then(|ext| self.services.get(&ext)) else {
+            return Ok(None);
+        };
+        let Some(doc) = self.documents.get(&uri) else {
+            return Ok(None);
+        };
+        Ok(hierarchy::prepare_type_hierarchy(
+            service.as_ref(),
+            &doc.tree,
+            &doc.text,
+            position,
+            &self.index,
+            &uri,
+        ))
+    }
+
+    async fn supertypes(
+        &self,
+        params: TypeHierarchySupertypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        Ok(Some(hierarchy::supertypes(&self.index, &params.item)))
+    }
+
+    async fn subtypes(
+        &self,
+        params: TypeHierarchySubtypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        Ok(Some(hierarchy::subtypes(&self.index, &params.item)))
+    }
+
+    async fn prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        let uri = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .to_string();
+        let position = params.text_document_position_params.position;
+        let Some(service) = self.get_ext(&uri).and_then(|ext| self.services.get(&ext)) else {
+            return Ok(None);
+        };
+        let Some(doc) = self.documents.get(&uri) else {
+            return Ok(None);
+        };
+        Ok(hierarchy::prepare_call_hierarchy(
+            service.as_ref(),
+            &doc.tree,
+            &doc.text,
+            position,
+            &self.index,
+            &uri,
+        ))
+    }
+
+    async fn incoming_calls(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        let Some(service) = self
+            .get_ext(params.item.uri.as_str())
+            .and_then(|ext| self.services.get(&ext))
+        else {
+            return Ok(None);
+        };
+        Ok(Some(hierarchy::incoming_calls(
+            service.as_ref(),
+            &self.index,
+            &params.item,
+            &|uri| self.load_document(uri),
+        )))
+    }
+
+    async fn outgoing_calls(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        let Some(service) = self
+            .get_ext(params.item.uri.as_str())
+            .and_then(|ext| self.services.get(&ext))
+        else {
+            return Ok(None);
+        };
+        Ok(Some(hierarchy::outgoing_calls(
+            service.as_ref(),
+            &self.index,
+            &params.item,
+            &|uri| self.load_document(uri),
+        )))
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri.to_string();
+        let ext = match self.get_ext(&uri) {
+            Some(e) => e,
+            None => return Ok(None),
+        };
+
+        let wants = |kind: &CodeActionKind| {
+            params
+                .context
+                .only
+                .as_ref()
+                .is_none_or(|only| only.iter().any(|o| kind.as_str().starts_with(o.as_str())))
+        };
+
+        if let Some(doc) = self.documents.get(&uri)
+            && let Some(service) = self.services.get(&ext)
+        {
+            let mut actions = Vec::new();
+            if wants(&CodeActionKind::QUICKFIX) {
+                actions.extend(service.code_actions(
+                    &doc.tree,
+                    &doc.text,
+                    params.range,
+                    &self.index,
+                    &uri,
+                ));
+            }
+            if wants(&CodeActionKind::SOURCE_ORGANIZE_IMPORTS)
+                && let Some(edit) = service.organize_imports(
+                    &doc.tree,
+                    &doc.text,
+                    &self.index,
+                    &uri,
+                    &self.config.import_layout,
+                )
+            {
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: "Organize imports".to_string(),
+                    kind: Some(CodeActionKind::SOURCE_ORGANIZE_IMPORTS),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(
+                            params.text_document.uri.clone(),
+                            vec![edit],
+                        )])),
+                        ..WorkspaceEdit::default()
+                    }),
+                    ..CodeAction::default()
+                }));
+            }
+            return Ok((!actions.is_empty()).then_some(actions));
+        }
+
+        Ok(None)
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        Ok(Some(workspace_symbols(&self.index, &params.query)))
+    }
+
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let uri = params.text_document.uri.to_string();
+        let Some(service) = self.get_ext(&uri).and_then(|ext| self.services.get(&ext)) else {
+            return Ok(None);
+        };
+        let Some(doc) = self.documents.get(&uri) else {
+            return Ok(None);
+        };
+
+        rename::prepare_rename(service.as_ref(), &self.index, &doc, &uri, params.position)
+            .map(|range| Some(PrepareRenameResponse::Range(range)))
+            .map_err(|err| tower_lsp::jsonrpc::Error::invalid_params(err.to_string()))
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let uri = params.text_document_position.text_document.uri.to_string();
+        let Some(service) = self.get_ext(&uri).and_then(|ext| self.services.get(&ext)) else {
+            return Ok(None);
+        };
+        // Snapshot the buffer so the reference scan can read other documents.
+        let Some(doc) = self.load_document(&params.text_document_position.text_document.uri) else {
+            return Ok(None);
+        };
+
+        rename::rename(
+            service.as_ref(),
+            &self.index,
+            &doc,
+            &uri,
+            params.text_document_position.position,
+            &params.new_name,
+            &self.config.keywords,
+            &|uri| self.load_document(uri),
+        )
+        .map(Some)
+        .map_err(|err| tower_lsp::jsonrpc::Error::invalid_params(err.to_string()))
+    }
+
     async fn shutdown(&self) -> Result<()> {
         Ok(())
     }
@@ -427,17 +1701,16 @@ impl LanguageServer for LspBackend {
 
         if let Some(doc) = self.documents.get(&uri)
             && let Some(service) = self.services.get(&ext)
-        {
-            if let Some(items) = service.completion(
+            && let Some(items) = service.completion(
                 &doc.tree,
                 &doc.text,
                 position,
                 &self.index,
                 &uri,
                 &self.config.keywords,
-            ) {
-                return Ok(Some(CompletionResponse::Array(items)));
-            }
+            )
+        {
+            return Ok(Some(CompletionResponse::Array(items)));
         }
 
         Ok(None)
@@ -0,0 +1,173 @@
+//! On-disk cache of index entries, so a warm start only reparses what
+//! changed.
+//!
+//! Workspace files are cached per workspace root and revalidated one by one
+//! against their size and modification time. Archives (`src.zip`, jmods,
+//! classpath jars) are cached whole: one cache file per archive, dropped as
+//! soon as the archive itself changes.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::FileEntry;
+
+/// Bumped whenever what the indexer records changes shape or meaning.
+const FORMAT: u32 = 3;
+
+fn cache_version() -> String {
+    format!("{}/{}", env!("CARGO_PKG_VERSION"), FORMAT)
+}
+
+/// Size and modification time of a file; a different stamp means the
+/// cached entries are stale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub size: u64,
+    pub modified_secs: u64,
+    pub modified_nanos: u32,
+}
+
+impl FileStamp {
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(Self {
+            size: metadata.len(),
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ArchiveCache {
+    version: String,
+    stamp: FileStamp,
+    entries: Vec<FileEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WorkspaceCache {
+    version: String,
+    files: Vec<CachedSource>,
+}
+
+/// A workspace file with the entry indexed from it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedSource {
+    pub path: PathBuf,
+    pub stamp: FileStamp,
+    pub entry: FileEntry,
+}
+
+pub struct IndexCache {
+    dir: PathBuf,
+}
+
+impl IndexCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// `$XDG_CACHE_HOME/jrsls`, falling back to `~/.cache/jrsls`.
+    pub fn default_dir() -> Option<PathBuf> {
+        std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .or_else(|| std::env::var_os("LOCALAPPDATA"))
+                    .filter(|home| !home.is_empty())
+                    .map(|home| PathBuf::from(home).join(".cache"))
+            })
+            .map(|dir| dir.join("jrsls"))
+    }
+
+    /// Entries indexed from `archive`, unless it changed since they were
+    /// stored.
+    pub fn load_archive(&self, archive: &Path) -> Option<Vec<FileEntry>> {
+        let stamp = FileStamp::of(archive)?;
+        let cached: ArchiveCache = self.read(&self.file_for("archive", archive))?;
+        (cached.version == cache_version() && cached.stamp == stamp).then_some(cached.entries)
+    }
+
+    pub fn store_archive(&self, archive: &Path, entries: Vec<FileEntry>) {
+        let Some(stamp) = FileStamp::of(archive) else {
+            return;
+        };
+        let cached = ArchiveCache {
+            version: cache_version(),
+            stamp,
+            entries,
+        };
+        self.write(&self.file_for("archive", archive), &cached);
+    }
+
+    /// Cached workspace files under `root` by path. Callers compare stamps
+    /// themselves, since only they know which files still exist.
+    pub fn load_workspace(&self, root: &Path) -> HashMap<PathBuf, CachedSource> {
+        self.read::<WorkspaceCache>(&self.file_for("workspace", root))
+            .filter(|cached| cached.version == cache_version())
+            .map(|cached| {
+                cached
+                    .files
+                    .into_iter()
+                    .map(|file| (file.path.clone(), file))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn store_workspace(&self, root: &Path, files: Vec<CachedSource>) {
+        let cached = WorkspaceCache {
+            version: cache_version(),
+            files,
+        };
+        self.write(&self.file_for("workspace", root), &cached);
+    }
+
+    /// `<dir>/<kind>-<hash of key>.json`.
+    fn file_for(&self, kind: &str, key: &Path) -> PathBuf {
+        // FNV-1a: stable across runs and toolchains, unlike `DefaultHasher`.
+        let hash = key
+            .as_os_str()
+            .as_encoded_bytes()
+            .iter()
+            .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+                (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+            });
+        self.dir.join(format!("{}-{:016x}.json", kind, hash))
+    }
+
+    fn read<T: serde::de::DeserializeOwned>(&self, path: &Path) -> Option<T> {
+        let file = std::fs::File::open(path).ok()?;
+        match serde_json::from_reader(std::io::BufReader::new(file)) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                tracing::warn!("Ignoring unreadable index cache {:?}: {}", path, err);
+                None
+            }
+        }
+    }
+
+    /// Writes through a temporary file so a crash never leaves a truncated
+    /// cache behind.
+    fn write<T: Serialize>(&self, path: &Path, value: &T) {
+        let result = (|| -> anyhow::Result<()> {
+            std::fs::create_dir_all(&self.dir)?;
+            let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+            let mut writer = std::io::BufWriter::new(std::fs::File::create(&temp)?);
+            serde_json::to_writer(&mut writer, value)?;
+            std::io::Write::flush(&mut writer)?;
+            drop(writer);
+            std::fs::rename(&temp, path)?;
+            Ok(())
+        })();
+        if let Err(err) = result {
+            tracing::warn!("Failed to write index cache {:?}: {}", path, err);
+        }
+    }
+}
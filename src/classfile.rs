@@ -0,0 +1,493 @@
+//! A minimal reader for JVM `.class` files.
+//!
+//! Only the parts the index needs are decoded: names, supertypes, member
+//! descriptors and the `Signature`, `InnerClasses` and `MethodParameters`
+//! attributes. Bytecode and everything else is skipped.
+
+use anyhow::{anyhow, bail};
+
+pub const ACC_PUBLIC: u16 = 0x0001;
+pub const ACC_PRIVATE: u16 = 0x0002;
+pub const ACC_PROTECTED: u16 = 0x0004;
+pub const ACC_STATIC: u16 = 0x0008;
+pub const ACC_FINAL: u16 = 0x0010;
+pub const ACC_BRIDGE: u16 = 0x0040;
+pub const ACC_VARARGS: u16 = 0x0080;
+pub const ACC_NATIVE: u16 = 0x0100;
+pub const ACC_INTERFACE: u16 = 0x0200;
+pub const ACC_ABSTRACT: u16 = 0x0400;
+pub const ACC_SYNTHETIC: u16 = 0x1000;
+pub const ACC_ANNOTATION: u16 = 0x2000;
+pub const ACC_ENUM: u16 = 0x4000;
+
+#[derive(Debug, Clone)]
+pub struct ClassFile {
+    pub access_flags: u16,
+    /// Internal name, e.g. `java/util/Map$Entry`.
+    pub this_class: String,
+    pub super_class: Option<String>,
+    pub interfaces: Vec<String>,
+    /// Generic class signature, when the class has one.
+    pub signature: Option<String>,
+    pub fields: Vec<MemberInfo>,
+    pub methods: Vec<MemberInfo>,
+    pub inner_classes: Vec<InnerClassInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemberInfo {
+    pub access_flags: u16,
+    pub name: String,
+    pub descriptor: String,
+    pub signature: Option<String>,
+    /// Names from `MethodParameters`; empty when compiled without `-parameters`.
+    pub param_names: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InnerClassInfo {
+    pub inner_class: String,
+    pub outer_class: Option<String>,
+    /// `None` for anonymous classes.
+    pub simple_name: Option<String>,
+    pub access_flags: u16,
+}
+
+impl ClassFile {
+    pub fn parse(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut r = Reader { bytes, pos: 0 };
+        if r.u4()? != 0xCAFE_BABE {
+            bail!("not a class file");
+        }
+        r.skip(4)?; // minor, major version
+
+        let pool = ConstantPool::parse(&mut r)?;
+        let access_flags = r.u2()?;
+        let this_class = pool.class_name(r.u2()?)?;
+        let super_index = r.u2()?;
+        let super_class = if super_index == 0 {
+            None
+        } else {
+            Some(pool.class_name(super_index)?)
+        };
+
+        let interface_count = r.u2()?;
+        let mut interfaces = Vec::with_capacity(interface_count as usize);
+        for _ in 0..interface_count {
+            interfaces.push(pool.class_name(r.u2()?)?);
+        }
+
+        let fields = parse_members(&mut r, &pool)?;
+        let methods = parse_members(&mut r, &pool)?;
+
+        let mut signature = None;
+        let mut inner_classes = Vec::new();
+        for _ in 0..r.u2()? {
+            let name = pool.utf8(r.u2()?)?;
+            let len = r.u4()? as usize;
+            let mut attr = Reader {
+                bytes: r.take(len)?,
+                pos: 0,
+            };
+            match name.as_str() {
+                "Signature" => signature = Some(pool.utf8(attr.u2()?)?),
+                "InnerClasses" => {
+                    for _ in 0..attr.u2()? {
+                        let inner = attr.u2()?;
+                        let outer = attr.u2()?;
+                        let simple = attr.u2()?;
+                        let flags = attr.u2()?;
+                        inner_classes.push(InnerClassInfo {
+                            inner_class: pool.class_name(inner)?,
+                            outer_class: (outer != 0)
+                                .then(|| pool.class_name(outer))
+                                .transpose()?,
+                            simple_name: (simple != 0).then(|| pool.utf8(simple)).transpose()?,
+                            access_flags: flags,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            access_flags,
+            this_class,
+            super_class,
+            interfaces,
+            signature,
+            fields,
+            methods,
+            inner_classes,
+        })
+    }
+
+    /// The `InnerClasses` entry describing this class itself, if it is nested.
+    pub fn nesting(&self) -> Option<&InnerClassInfo> {
+        self.inner_classes
+            .iter()
+            .find(|ic| ic.inner_class == self.this_class)
+    }
+}
+
+fn parse_members(r: &mut Reader, pool: &ConstantPool) -> anyhow::Result<Vec<MemberInfo>> {
+    let count = r.u2()?;
+    let mut members = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let access_flags = r.u2()?;
+        let name = pool.utf8(r.u2()?)?;
+        let descriptor = pool.utf8(r.u2()?)?;
+
+        let mut signature = None;
+        let mut param_names = Vec::new();
+        for _ in 0..r.u2()? {
+            let attr_name = pool.utf8(r.u2()?)?;
+            let len = r.u4()? as usize;
+            let mut attr = Reader {
+                bytes: r.take(len)?,
+                pos: 0,
+            };
+            match attr_name.as_str() {
+                "Signature" => signature = Some(pool.utf8(attr.u2()?)?),
+                "MethodParameters" => {
+                    for _ in 0..attr.u1()? {
+                        let name_index = attr.u2()?;
+                        attr.skip(2)?; // access flags
+                        param_names.push(if name_index == 0 {
+                            String::new()
+                        } else {
+                            pool.utf8(name_index)?
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        members.push(MemberInfo {
+            access_flags,
+            name,
+            descriptor,
+            signature,
+            param_names,
+        });
+    }
+    Ok(members)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| anyhow!("truncated class file"))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn skip(&mut self, len: usize) -> anyhow::Result<()> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u1(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u2(&mut self) -> anyhow::Result<u16> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u4(&mut self) -> anyhow::Result<u32> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+enum Constant {
+    Utf8(String),
+    Class(u16),
+    Other,
+}
+
+struct ConstantPool {
+    entries: Vec<Constant>,
+}
+
+impl ConstantPool {
+    fn parse(r: &mut Reader) -> anyhow::Result<Self> {
+        let count = r.u2()? as usize;
+        // Index 0 is unused; long and double constants take two slots.
+        let mut entries = Vec::with_capacity(count);
+        entries.push(Constant::Other);
+        while entries.len() < count {
+            let tag = r.u1()?;
+            let entry = match tag {
+                1 => {
+                    let len = r.u2()? as usize;
+                    Constant::Utf8(String::from_utf8_lossy(r.take(len)?).into_owned())
+                }
+                7 => Constant::Class(r.u2()?),
+                3 | 4 => {
+                    r.skip(4)?;
+                    Constant::Other
+                }
+                5 | 6 => {
+                    r.skip(8)?;
+                    entries.push(Constant::Other);
+                    Constant::Other
+                }
+                8 | 16 | 19 | 20 => {
+                    r.skip(2)?;
+                    Constant::Other
+                }
+                9 | 10 | 11 | 12 | 17 | 18 => {
+                    r.skip(4)?;
+                    Constant::Other
+                }
+                15 => {
+                    r.skip(3)?;
+                    Constant::Other
+                }
+                other => bail!("unknown constant pool tag {}", other),
+            };
+            entries.push(entry);
+        }
+        Ok(Self { entries })
+    }
+
+    fn utf8(&self, index: u16) -> anyhow::Result<String> {
+        match self.entries.get(index as usize) {
+            Some(Constant::Utf8(s)) => Ok(s.clone()),
+            _ => bail!("constant {} is not a UTF-8 entry", index),
+        }
+    }
+
+    fn class_name(&self, index: u16) -> anyhow::Result<String> {
+        match self.entries.get(index as usize) {
+            Some(Constant::Class(name_index)) => self.utf8(*name_index),
+            _ => bail!("constant {} is not a class entry", index),
+        }
+    }
+}
+
+/// Splits a method descriptor like `(I[Ljava/lang/String;)V` into the
+/// descriptors of its parameters and its return type.
+pub fn split_method_descriptor(descriptor: &str) -> Option<(Vec<&str>, &str)> {
+    let rest = descriptor.strip_prefix('(')?;
+    let (params, return_type) = rest.split_once(')')?;
+    let bytes = params.as_bytes();
+    let mut types = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'[' => {
+                i += 1;
+                continue;
+            }
+            b'L' => i += params[i..].find(';')? + 1,
+            _ => i += 1,
+        }
+        types.push(&params[start..i]);
+        start = i;
+    }
+    Some((types, return_type))
+}
+
+/// Parses field/method descriptors and generic signatures into Java source
+/// text. Class names are handed to `name_of` as internal names
+/// (`java/util/Map$Entry`) so the caller decides how to spell them.
+pub struct SignatureParser<'a, 'n> {
+    s: &'a [u8],
+    pos: usize,
+    name_of: &'n mut dyn FnMut(&str) -> String,
+}
+
+/// A method type as Java text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MethodType {
+    /// `<T extends Comparable<T>>`, or empty.
+    pub type_params: String,
+    pub params: Vec<String>,
+    pub return_type: String,
+}
+
+/// A class signature as Java text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClassType {
+    pub type_params: String,
+    pub super_class: String,
+    pub interfaces: Vec<String>,
+}
+
+impl<'a, 'n> SignatureParser<'a, 'n> {
+    pub fn new(s: &'a str, name_of: &'n mut dyn FnMut(&str) -> String) -> Self {
+        Self {
+            s: s.as_bytes(),
+            pos: 0,
+            name_of,
+        }
+    }
+
+    /// A field descriptor or field signature.
+    pub fn field_type(mut self) -> Option<String> {
+        let ty = self.type_sig()?;
+        (self.pos == self.s.len()).then_some(ty)
+    }
+
+    /// A method descriptor or method signature. Thrown types are ignored.
+    pub fn method_type(mut self) -> Option<MethodType> {
+        let type_params = self.type_params()?;
+        self.expect(b'(')?;
+        let mut params = Vec::new();
+        while self.peek()? != b')' {
+            params.push(self.type_sig()?);
+        }
+        self.pos += 1;
+        let return_type = self.type_sig()?;
+        Some(MethodType {
+            type_params,
+            params,
+            return_type,
+        })
+    }
+
+    pub fn class_type(mut self) -> Option<ClassType> {
+        let type_params = self.type_params()?;
+        let super_class = self.type_sig()?;
+        let mut interfaces = Vec::new();
+        while self.pos < self.s.len() {
+            interfaces.push(self.type_sig()?);
+        }
+        Some(ClassType {
+            type_params,
+            super_class,
+            interfaces,
+        })
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.s.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn expect(&mut self, c: u8) -> Option<()> {
+        (self.next()? == c).then_some(())
+    }
+
+    fn ident(&mut self, stops: &[u8]) -> Option<String> {
+        let start = self.pos;
+        while !stops.contains(&self.peek()?) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.s[start..self.pos])
+            .ok()
+            .map(str::to_string)
+    }
+
+    fn type_params(&mut self) -> Option<String> {
+        if self.peek() != Some(b'<') {
+            return Some(String::new());
+        }
+        self.pos += 1;
+        let mut params = Vec::new();
+        while self.peek()? != b'>' {
+            let name = self.ident(b":")?;
+            let mut bounds = Vec::new();
+            while self.peek()? == b':' {
+                self.pos += 1;
+                // The class bound may be empty when only interfaces bound it.
+                if self.peek()? != b':' {
+                    bounds.push(self.type_sig()?);
+                }
+            }
+            bounds.retain(|b| b != "Object");
+            params.push(if bounds.is_empty() {
+                name
+            } else {
+                format!("{} extends {}", name, bounds.join(" & "))
+            });
+        }
+        self.pos += 1;
+        Some(format!("<{}>", params.join(", ")))
+    }
+
+    fn type_sig(&mut self) -> Option<String> {
+        let ty = match self.next()? {
+            b'B' => "byte".to_string(),
+            b'C' => "char".to_string(),
+            b'D' => "double".to_string(),
+            b'F' => "float".to_string(),
+            b'I' => "int".to_string(),
+            b'J' => "long".to_string(),
+            b'S' => "short".to_string(),
+            b'Z' => "boolean".to_string(),
+            b'V' => "void".to_string(),
+            b'[' => format!("{}[]", self.type_sig()?),
+            b'T' => {
+                let name = self.ident(b";")?;
+                self.pos += 1;
+                name
+            }
+            b'L' => self.class_type_sig()?,
+            _ => return None,
+        };
+        Some(ty)
+    }
+
+    fn class_type_sig(&mut self) -> Option<String> {
+        let mut internal = self.ident(b"<.;")?;
+        let mut args = self.type_args()?;
+        // `Outer<K>.Inner<V>`: only the innermost arguments are kept.
+        while self.peek()? == b'.' {
+            self.pos += 1;
+            let inner = self.ident(b"<.;")?;
+            internal = format!("{}${}", internal, inner);
+            args = self.type_args()?;
+        }
+        self.expect(b';')?;
+        Some(format!("{}{}", (self.name_of)(&internal), args))
+    }
+
+    fn type_args(&mut self) -> Option<String> {
+        if self.peek() != Some(b'<') {
+            return Some(String::new());
+        }
+        self.pos += 1;
+        let mut args = Vec::new();
+        while self.peek()? != b'>' {
+            let arg = match self.peek()? {
+                b'*' => {
+                    self.pos += 1;
+                    "?".to_string()
+                }
+                b'+' => {
+                    self.pos += 1;
+                    format!("? extends {}", self.type_sig()?)
+                }
+                b'-' => {
+                    self.pos += 1;
+                    format!("? super {}", self.type_sig()?)
+                }
+                _ => self.type_sig()?,
+            };
+            args.push(arg);
+        }
+        self.pos += 1;
+        Some(format!("<{}>", args.join(", ")))
+    }
+}
@@ -0,0 +1,74 @@
+use ropey::Rope;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+use tree_sitter::{Node, Tree};
+
+use crate::utils::{get_node_text, node_range};
+
+/// Longest token snippet quoted in an "unexpected" message.
+const MAX_SNIPPET_CHARS: usize = 32;
+
+/// Turns tree-sitter `ERROR` and `MISSING` nodes into LSP diagnostics.
+pub fn syntax_diagnostics(tree: &Tree, rope: &Rope) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut stack = vec![tree.root_node()];
+
+    while let Some(node) = stack.pop() {
+        if node.is_missing() {
+            diagnostics.push(syntax_error(node, rope, missing_message(node)));
+            continue;
+        }
+        if node.is_error() {
+            // One report per error region; nested errors are usually noise.
+            diagnostics.push(syntax_error(node, rope, unexpected_message(node, rope)));
+            continue;
+        }
+        if !node.has_error() {
+            continue;
+        }
+
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+
+    diagnostics.sort_by_key(|d| (d.range.start.line, d.range.start.character));
+    diagnostics
+}
+
+fn syntax_error(node: Node, rope: &Rope, message: String) -> Diagnostic {
+    Diagnostic {
+        range: node_range(node, rope),
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("jrsls".to_string()),
+        message,
+        ..Diagnostic::default()
+    }
+}
+
+fn missing_message(node: Node) -> String {
+    if node.is_named() {
+        format!("missing {}", node.kind().replace('_', " "))
+    } else {
+        format!("missing '{}'", node.kind())
+    }
+}
+
+fn unexpected_message(node: Node, rope: &Rope) -> String {
+    // Quote the first token of the error region rather than all of it.
+    let mut first = node;
+    while let Some(child) = first.child(0) {
+        first = child;
+    }
+    let text = get_node_text(first, rope);
+    let text = text.lines().next().unwrap_or("").trim();
+    if text.is_empty() {
+        return "syntax error".to_string();
+    }
+
+    let snippet: String = text.chars().take(MAX_SNIPPET_CHARS).collect();
+    let ellipsis = if text.chars().count() > MAX_SNIPPET_CHARS {
+        "…"
+    } else {
+        ""
+    };
+    format!("unexpected token `{}{}`", snippet, ellipsis)
+}
@@ -1,4 +1,9 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+/// Build output and dependency directories never worth indexing.
+const EXCLUDED_DIRS: [&str; 4] = ["build", "target", "out", "node_modules"];
 
 pub fn collect_files_with_ext(root: PathBuf, ext: &str) -> Vec<PathBuf> {
     let mut stack = vec![root];
@@ -29,3 +34,86 @@ pub fn collect_files_with_ext(root: PathBuf, ext: &str) -> Vec<PathBuf> {
 
     results
 }
+
+/// Which workspace files are worth indexing: `.gitignore`d paths, hidden
+/// directories, build output and user exclude globs are skipped.
+#[derive(Debug, Clone)]
+pub struct SourceFilter {
+    root: PathBuf,
+    excludes: GlobSet,
+}
+
+impl SourceFilter {
+    /// `excludes` are globs matched against paths relative to `root`, e.g.
+    /// `**/generated/**`.
+    pub fn new(root: PathBuf, excludes: &[String]) -> anyhow::Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in excludes {
+            builder.add(Glob::new(pattern)?);
+        }
+        Ok(Self {
+            root,
+            excludes: builder.build()?,
+        })
+    }
+
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        path.strip_prefix(&self.root)
+            .is_ok_and(|relative| self.excludes.is_match(relative))
+    }
+
+    /// Whether a single file under the root passes the filter. Unlike a
+    /// walk, this does not consult `.gitignore`.
+    pub fn accepts(&self, path: &Path) -> bool {
+        self.accepts_in(&self.root, path)
+    }
+
+    /// Whether walking `dir` would collect `path`: only directories below
+    /// `dir` are skipped, so files of a declared root under `build/` pass.
+    pub fn accepts_in(&self, dir: &Path, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(dir) else {
+            return false;
+        };
+        let in_skipped_dir = relative
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .any(|c| {
+                c.as_os_str()
+                    .to_str()
+                    .is_some_and(|name| name.starts_with('.') || EXCLUDED_DIRS.contains(&name))
+            });
+        !in_skipped_dir && !self.is_excluded(path)
+    }
+
+    /// Files under `dir` with extension `ext`, minus everything filtered
+    /// out. `dir` itself is walked even when it would be excluded, so
+    /// declared source roots under e.g. `build/` still count.
+    pub fn collect_files_with_ext(&self, dir: &Path, ext: &str) -> Vec<PathBuf> {
+        let filter = self.clone();
+        ignore::WalkBuilder::new(dir)
+            .require_git(false)
+            .filter_entry(move |entry| {
+                if entry.depth() == 0 {
+                    return true;
+                }
+                let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+                let build_output = is_dir
+                    && entry
+                        .file_name()
+                        .to_str()
+                        .is_some_and(|name| EXCLUDED_DIRS.contains(&name));
+                !build_output && !filter.is_excluded(entry.path())
+            })
+            .build()
+            .flatten()
+            .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+            .map(ignore::DirEntry::into_path)
+            .filter(|path| {
+                path.extension()
+                    .and_then(|s| s.to_str())
+                    .is_some_and(|name| name.eq_ignore_ascii_case(ext))
+            })
+            .collect()
+    }
+}
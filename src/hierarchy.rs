@@ -0,0 +1,261 @@
+//! Type hierarchy (`textDocument/prepareTypeHierarchy`) and call hierarchy
+//! (`textDocument/prepareCallHierarchy`) on top of the index.
+//!
+//! Type items carry their FQCN in `data`; call items are identified by
+//! their URI and the range of the method name, as stored in the index.
+
+use std::collections::{BTreeMap, HashMap};
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, Location, Position,
+    Range, SymbolKind, TypeHierarchyItem, Url,
+};
+use tree_sitter::Tree;
+
+use crate::lang::LanguageService;
+use crate::state::{ClassLocation, Document, GlobalIndex, MemberLocation, TypeKind};
+use crate::utils::position_to_char;
+
+/// Callers found so far, by URI and start of the caller's name, with the
+/// call sites in each.
+type Callers = BTreeMap<(Url, (u32, u32)), (CallHierarchyItem, Vec<Range>)>;
+
+/// The class at `position`, whether named by its declaration or a reference.
+pub fn prepare_type_hierarchy(
+    service: &dyn LanguageService,
+    tree: &Tree,
+    rope: &Rope,
+    position: Position,
+    index: &GlobalIndex,
+    current_uri: &str,
+) -> Option<Vec<TypeHierarchyItem>> {
+    let location = service.declaration_location(tree, rope, position, index, current_uri)?;
+    let class = index.class_at(&location)?;
+    Some(vec![type_item(class)])
+}
+
+/// Indexed direct supertypes of `item`, superclass first.
+pub fn supertypes(index: &GlobalIndex, item: &TypeHierarchyItem) -> Vec<TypeHierarchyItem> {
+    let Some(fqcn) = item_fqcn(item) else {
+        return Vec::new();
+    };
+    index
+        .supertypes(&fqcn)
+        .iter()
+        .filter_map(|supertype| index.class_by_fqcn(supertype))
+        .map(type_item)
+        .collect()
+}
+
+/// Indexed classes directly extending or implementing `item`.
+pub fn subtypes(index: &GlobalIndex, item: &TypeHierarchyItem) -> Vec<TypeHierarchyItem> {
+    let Some(fqcn) = item_fqcn(item) else {
+        return Vec::new();
+    };
+    index.subtypes(&fqcn).into_iter().map(type_item).collect()
+}
+
+/// The method or constructor at `position`, whether named by its
+/// declaration or a call.
+pub fn prepare_call_hierarchy(
+    service: &dyn LanguageService,
+    tree: &Tree,
+    rope: &Rope,
+    position: Position,
+    index: &GlobalIndex,
+    current_uri: &str,
+) -> Option<Vec<CallHierarchyItem>> {
+    // Unlike references, a `new` expression stands for the constructor.
+    let location = if service.is_declaration_name(tree, rope, position) {
+        service.declaration_location(tree, rope, position, index, current_uri)
+    } else {
+        service.goto_definition(tree, rope, position, index, current_uri)
+    }?;
+    let member = index.member_at(&location).filter(|m| !m.is_field)?;
+    Some(vec![call_item(member)])
+}
+
+/// Callers of `item`, one entry per calling method with every call site
+/// in it. Call sites are the occurrences of the name that goto-definition
+/// takes to `item`, so `new` expressions count for constructors. Calls
+/// outside any method, e.g. in field initializers, are left out.
+pub fn incoming_calls(
+    service: &dyn LanguageService,
+    index: &GlobalIndex,
+    item: &CallHierarchyItem,
+    load: &dyn Fn(&Url) -> Option<Document>,
+) -> Vec<CallHierarchyIncomingCall> {
+    let target = Location::new(item.uri.clone(), item.selection_range);
+    let name = match index.member_at(&target) {
+        Some(member) if member.is_constructor => class_name(&member.fqmn).to_string(),
+        Some(member) => member_name(&member.fqmn).to_string(),
+        None => item.name.clone(),
+    };
+
+    let mut documents: HashMap<Url, Option<Document>> = HashMap::new();
+    let mut callers = Callers::new();
+    for reference in index.occurrences_of(&name) {
+        let doc = documents
+            .entry(reference.uri.clone())
+            .or_insert_with(|| load(&reference.uri));
+        let Some(doc) = doc else {
+            continue;
+        };
+        let position = reference.range.start;
+        if service.is_declaration_name(&doc.tree, &doc.text, position) {
+            continue;
+        }
+        let resolved = service.goto_definition(
+            &doc.tree,
+            &doc.text,
+            position,
+            index,
+            reference.uri.as_str(),
+        );
+        if resolved.as_ref() != Some(&target) {
+            continue;
+        }
+        let Some(caller) = service.enclosing_callable(&doc.tree, &doc.text, position) else {
+            continue;
+        };
+
+        let key = (
+            reference.uri.clone(),
+            (caller.start.line, caller.start.character),
+        );
+        callers
+            .entry(key)
+            .or_insert_with(|| {
+                let location = Location::new(reference.uri.clone(), caller);
+                (callable_item(index, &location, &doc.text), Vec::new())
+            })
+            .1
+            .push(reference.range);
+    }
+
+    callers
+        .into_values()
+        .map(|(from, from_ranges)| CallHierarchyIncomingCall { from, from_ranges })
+        .collect()
+}
+
+/// Methods and constructors `item` calls, one entry per callee with every
+/// call site. Calls that resolve to nothing indexed are left out.
+pub fn outgoing_calls(
+    service: &dyn LanguageService,
+    index: &GlobalIndex,
+    item: &CallHierarchyItem,
+    load: &dyn Fn(&Url) -> Option<Document>,
+) -> Vec<CallHierarchyOutgoingCall> {
+    let Some(doc) = load(&item.uri) else {
+        return Vec::new();
+    };
+
+    let mut callees: Vec<(MemberLocation, Vec<Range>)> = Vec::new();
+    for site in service.call_sites(&doc.tree, &doc.text, item.selection_range) {
+        let Some(callee) = service
+            .goto_definition(&doc.tree, &doc.text, site.start, index, item.uri.as_str())
+            .and_then(|location| index.member_at(&location))
+            .filter(|m| !m.is_field)
+        else {
+            continue;
+        };
+        match callees
+            .iter_mut()
+            .find(|(known, _)| known.uri == callee.uri && known.range == callee.range)
+        {
+            Some((_, ranges)) => ranges.push(site),
+            None => callees.push((callee, vec![site])),
+        }
+    }
+
+    callees
+        .into_iter()
+        .map(|(callee, from_ranges)| CallHierarchyOutgoingCall {
+            to: call_item(callee),
+            from_ranges,
+        })
+        .collect()
+}
+
+fn type_item(class: ClassLocation) -> TypeHierarchyItem {
+    let name = member_name(&class.fqcn).to_string();
+    TypeHierarchyItem {
+        kind: match class.kind {
+            TypeKind::Class => SymbolKind::CLASS,
+            TypeKind::Interface | TypeKind::Annotation => SymbolKind::INTERFACE,
+            TypeKind::Enum => SymbolKind::ENUM,
+            TypeKind::Record => SymbolKind::STRUCT,
+        },
+        tags: None,
+        detail: class
+            .fqcn
+            .rsplit_once('.')
+            .map(|(package, _)| package.to_string()),
+        uri: class.uri,
+        range: class.range,
+        // Class ranges span the whole declaration; reveal its first line.
+        selection_range: Range::new(class.range.start, class.range.start),
+        data: Some(serde_json::Value::String(class.fqcn)),
+        name,
+    }
+}
+
+fn item_fqcn(item: &TypeHierarchyItem) -> Option<String> {
+    match &item.data {
+        Some(serde_json::Value::String(fqcn)) => Some(fqcn.clone()),
+        _ => None,
+    }
+}
+
+fn call_item(member: MemberLocation) -> CallHierarchyItem {
+    let name = if member.is_constructor {
+        class_name(&member.fqmn)
+    } else {
+        member_name(&member.fqmn)
+    };
+    CallHierarchyItem {
+        name: name.to_string(),
+        kind: if member.is_constructor {
+            SymbolKind::CONSTRUCTOR
+        } else {
+            SymbolKind::METHOD
+        },
+        tags: None,
+        detail: Some(member.signature),
+        uri: member.uri,
+        range: member.range,
+        selection_range: member.range,
+        data: None,
+    }
+}
+
+/// The call item for the method named at `location`, from the index when
+/// it is indexed (it is not inside anonymous classes) or else from the text.
+fn callable_item(index: &GlobalIndex, location: &Location, rope: &Rope) -> CallHierarchyItem {
+    if let Some(member) = index.member_at(location) {
+        return call_item(member);
+    }
+    let start = position_to_char(rope, location.range.start);
+    let end = position_to_char(rope, location.range.end);
+    CallHierarchyItem {
+        name: rope.slice(start..end).to_string(),
+        kind: SymbolKind::METHOD,
+        tags: None,
+        detail: None,
+        uri: location.uri.clone(),
+        range: location.range,
+        selection_range: location.range,
+        data: None,
+    }
+}
+
+/// Declaring class of a member, e.g. `Order` for `shop.Order.<init>`.
+fn class_name(fqmn: &str) -> &str {
+    fqmn.rsplit('.').nth(1).unwrap_or(fqmn)
+}
+
+fn member_name(fqmn: &str) -> &str {
+    fqmn.rsplit('.').next().unwrap_or(fqmn)
+}
@@ -0,0 +1,90 @@
+use ropey::Rope;
+use tower_lsp::lsp_types::{Location, Position};
+use tree_sitter::Tree;
+
+use crate::ast::InferredType;
+use crate::lang::LanguageService;
+use crate::state::{GlobalIndex, MemberLocation, TypeKind};
+
+/// Implementations of the type or method at `position`, from the index.
+///
+/// For an interface or class these are all classes extending or
+/// implementing it, directly or not. For a method they are the concrete
+/// methods overriding it in those classes; the method itself is left out.
+pub fn find_implementations(
+    service: &dyn LanguageService,
+    tree: &Tree,
+    rope: &Rope,
+    position: Position,
+    index: &GlobalIndex,
+    current_uri: &str,
+) -> Vec<Location> {
+    let target = if service.is_declaration_name(tree, rope, position) {
+        service.declaration_location(tree, rope, position, index, current_uri)
+    } else {
+        service.goto_definition(tree, rope, position, index, current_uri)
+    };
+    let Some(target) = target else {
+        return Vec::new();
+    };
+
+    if let Some(class) = index.class_at(&target) {
+        return index
+            .subtype_closure(&class.fqcn)
+            .into_iter()
+            .filter(|c| !matches!(c.kind, TypeKind::Interface | TypeKind::Annotation))
+            .filter(|c| index.is_visible_from(&c.uri, current_uri))
+            .map(|c| Location::new(c.uri, c.range))
+            .collect();
+    }
+
+    let Some(method) = index
+        .member_at(&target)
+        .filter(|m| !m.is_field && !m.is_constructor && !m.modifiers.is_static)
+    else {
+        return Vec::new();
+    };
+    let Some((owner, name)) = method.fqmn.rsplit_once('.') else {
+        return Vec::new();
+    };
+
+    index
+        .subtype_closure(owner)
+        .into_iter()
+        .filter(|c| index.is_visible_from(&c.uri, current_uri))
+        .flat_map(|c| {
+            let prefix = format!("{}.", c.fqcn);
+            index
+                .members_of_class(&c.fqcn)
+                .into_iter()
+                .filter(move |m| m.fqmn.strip_prefix(&prefix) == Some(name))
+        })
+        .filter(|m| overrides(m, &method) && !m.modifiers.is_abstract)
+        .map(|m| Location::new(m.uri, m.range))
+        .collect()
+}
+
+/// Whether `candidate`, declared in a subtype, overrides `method`.
+/// Parameters typed with a type variable, such as `T`, match any type.
+fn overrides(candidate: &MemberLocation, method: &MemberLocation) -> bool {
+    !candidate.is_field
+        && !candidate.is_constructor
+        && !candidate.modifiers.is_static
+        && candidate.param_types.len() == method.param_types.len()
+        && candidate
+            .param_types
+            .iter()
+            .zip(&method.param_types)
+            .all(|(a, b)| a == b || is_type_variable(a) || is_type_variable(b))
+}
+
+fn is_type_variable(ty: &InferredType) -> bool {
+    match ty {
+        InferredType::Class(name) => {
+            let mut chars = name.chars();
+            chars.next().is_some_and(|c| c.is_ascii_uppercase())
+                && chars.all(|c| c.is_ascii_digit())
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,331 @@
+use ropey::Rope;
+use tower_lsp::lsp_types::{Position, Range, TextEdit};
+use tree_sitter::{Node, Tree};
+
+use crate::utils::{collect_identifiers, get_node_text, node_range};
+
+/// An `import` declaration as written in the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportDecl {
+    /// Imported path without `import`, `static` or `.*`.
+    pub path: String,
+    pub is_static: bool,
+    pub is_on_demand: bool,
+}
+
+impl ImportDecl {
+    pub fn from_node(node: Node, rope: &Rope) -> Option<Self> {
+        let mut cursor = node.walk();
+        let mut decl = ImportDecl {
+            path: String::new(),
+            is_static: false,
+            is_on_demand: false,
+        };
+        for child in node.children(&mut cursor) {
+            match child.kind() {
+                "static" => decl.is_static = true,
+                "asterisk" => decl.is_on_demand = true,
+                "identifier" | "scoped_identifier" => decl.path = get_node_text(child, rope),
+                _ => {}
+            }
+        }
+        (!decl.path.is_empty()).then_some(decl)
+    }
+
+    /// Whether this declaration already makes `fqcn` visible by its short name.
+    pub fn covers(&self, fqcn: &str) -> bool {
+        if self.is_static {
+            return false;
+        }
+        if self.is_on_demand {
+            return fqcn
+                .rsplit_once('.')
+                .is_some_and(|(package, _)| package == self.path);
+        }
+        self.path == fqcn
+    }
+}
+
+pub fn import_declarations<'a>(tree: &'a Tree) -> Vec<Node<'a>> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    root.children(&mut cursor)
+        .filter(|n| n.kind() == "import_declaration")
+        .collect()
+}
+
+/// Edit adding `import <fqcn>;`, or `None` when an existing import already
+/// covers it.
+///
+/// The new line goes before the first single-type import that sorts after
+/// `fqcn`, after the last one otherwise. Files without imports get it after
+/// the `package` declaration, or above the first type declaration and the
+/// comments attached to it.
+pub fn import_edit(tree: &Tree, rope: &Rope, fqcn: &str) -> Option<TextEdit> {
+    let line = format!("import {};", fqcn);
+    let imports: Vec<_> = import_declarations(tree)
+        .into_iter()
+        .filter_map(|node| Some((node, ImportDecl::from_node(node, rope)?)))
+        .collect();
+    if imports.iter().any(|(_, decl)| decl.covers(fqcn)) {
+        return None;
+    }
+
+    let regular: Vec<_> = imports.iter().filter(|(_, d)| !d.is_static).collect();
+    if let Some((node, _)) = regular.iter().find(|(_, d)| d.path.as_str() > fqcn) {
+        return Some(insert_at(
+            node_range(*node, rope).start,
+            format!("{}\n", line),
+        ));
+    }
+    if let Some((node, _)) = regular.last() {
+        return Some(insert_at(
+            node_range(*node, rope).end,
+            format!("\n{}", line),
+        ));
+    }
+    if let Some((node, _)) = imports.first() {
+        return Some(insert_at(
+            node_range(*node, rope).start,
+            format!("{}\n", line),
+        ));
+    }
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let children: Vec<_> = root.children(&mut cursor).collect();
+    if let Some(package) = children.iter().find(|n| n.kind() == "package_declaration") {
+        return Some(insert_at(
+            node_range(*package, rope).end,
+            format!("\n\n{}", line),
+        ));
+    }
+    // Comments directly above the declaration, such as its Javadoc, stay
+    // with it; a header set off by a blank line stays on top.
+    let Some(mut first) = children.iter().position(|n| !n.kind().ends_with("comment")) else {
+        return Some(insert_at(Position::default(), format!("{}\n\n", line)));
+    };
+    while first > 0
+        && children[first - 1].kind().ends_with("comment")
+        && children[first - 1].end_position().row + 1 >= children[first].start_position().row
+    {
+        first -= 1;
+    }
+    Some(insert_at(
+        node_range(children[first], rope).start,
+        format!("{}\n\n", line),
+    ))
+}
+
+fn insert_at(position: Position, new_text: String) -> TextEdit {
+    TextEdit::new(Range::new(position, position), new_text)
+}
+
+/// One block of the organized import section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportGroup {
+    /// Imports under a package prefix such as `java` or `org.junit`.
+    Prefix(String),
+    /// Packages belonging to the workspace.
+    Project,
+    /// Anything no other group claims.
+    ThirdParty,
+    /// Every `import static`, regardless of package.
+    Static,
+}
+
+/// Order of the import groups written by organize imports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportLayout {
+    pub groups: Vec<ImportGroup>,
+}
+
+impl Default for ImportLayout {
+    fn default() -> Self {
+        Self {
+            groups: vec![
+                ImportGroup::Prefix("java".to_string()),
+                ImportGroup::Prefix("javax".to_string()),
+                ImportGroup::ThirdParty,
+                ImportGroup::Project,
+                ImportGroup::Static,
+            ],
+        }
+    }
+}
+
+impl std::str::FromStr for ImportLayout {
+    type Err = String;
+
+    /// Parses a comma-separated layout such as `java,javax,*,project,static`,
+    /// where `*` is third-party code and every other entry is a package prefix.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut groups = Vec::new();
+        for entry in s.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let group = match entry {
+                "*" => ImportGroup::ThirdParty,
+                "project" => ImportGroup::Project,
+                "static" => ImportGroup::Static,
+                prefix => {
+                    let valid = prefix.split('.').all(|segment| {
+                        !segment.is_empty()
+                            && segment
+                                .chars()
+                                .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+                    });
+                    if !valid {
+                        return Err(format!("invalid import group `{}`", prefix));
+                    }
+                    ImportGroup::Prefix(prefix.to_string())
+                }
+            };
+            if groups.contains(&group) {
+                return Err(format!("duplicate import group `{}`", entry));
+            }
+            groups.push(group);
+        }
+
+        // Every import needs a home even if the layout forgets these.
+        for required in [ImportGroup::ThirdParty, ImportGroup::Static] {
+            if !groups.contains(&required) {
+                groups.push(required);
+            }
+        }
+        Ok(Self { groups })
+    }
+}
+
+impl ImportLayout {
+    fn group_of(&self, decl: &ImportDecl, is_project: &dyn Fn(&ImportDecl) -> bool) -> usize {
+        let position = |group: &ImportGroup| self.groups.iter().position(|g| g == group);
+        if decl.is_static {
+            return position(&ImportGroup::Static).unwrap_or(self.groups.len());
+        }
+
+        let by_prefix = self
+            .groups
+            .iter()
+            .enumerate()
+            .filter_map(|(i, g)| match g {
+                ImportGroup::Prefix(prefix)
+                    if decl.path == *prefix || decl.path.starts_with(&format!("{}.", prefix)) =>
+                {
+                    Some((prefix.len(), i))
+                }
+                _ => None,
+            })
+            .max();
+        if let Some((_, i)) = by_prefix {
+            return i;
+        }
+        if is_project(decl)
+            && let Some(i) = position(&ImportGroup::Project)
+        {
+            return i;
+        }
+        position(&ImportGroup::ThirdParty).unwrap_or(self.groups.len())
+    }
+}
+
+impl std::fmt::Display for ImportDecl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "import ")?;
+        if self.is_static {
+            write!(f, "static ")?;
+        }
+        write!(f, "{}", self.path)?;
+        if self.is_on_demand {
+            write!(f, ".*")?;
+        }
+        write!(f, ";")
+    }
+}
+
+/// Rewrites the import section: drops imports whose name is never used,
+/// collapses duplicates, and sorts the rest into the groups of `layout`,
+/// separated by blank lines.
+///
+/// On-demand imports are always kept. Returns `None` when nothing changes,
+/// or when comments sit between imports and would be lost.
+pub fn organize_imports(
+    tree: &Tree,
+    rope: &Rope,
+    layout: &ImportLayout,
+    is_project: &dyn Fn(&ImportDecl) -> bool,
+) -> Option<TextEdit> {
+    let nodes = import_declarations(tree);
+    let first = *nodes.first()?;
+    let last = *nodes.last()?;
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    if root.children(&mut cursor).any(|n| {
+        n.kind().ends_with("comment")
+            && n.start_byte() > first.start_byte()
+            && n.end_byte() < last.end_byte()
+    }) {
+        return None;
+    }
+
+    let used = referenced_names(root, rope);
+    let mut groups: Vec<Vec<ImportDecl>> = vec![Vec::new(); layout.groups.len() + 1];
+    for decl in nodes.iter().filter_map(|n| ImportDecl::from_node(*n, rope)) {
+        let short_name = decl.path.rsplit('.').next().unwrap_or(&decl.path);
+        if !decl.is_on_demand && !used.contains(short_name) {
+            continue;
+        }
+        let group = &mut groups[layout.group_of(&decl, is_project)];
+        if !group.contains(&decl) {
+            group.push(decl);
+        }
+    }
+
+    let blocks: Vec<String> = groups
+        .iter_mut()
+        .filter(|group| !group.is_empty())
+        .map(|group| {
+            group.sort_by(|a, b| (&a.path, a.is_on_demand).cmp(&(&b.path, b.is_on_demand)));
+            group
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect();
+    let new_text = blocks.join("\n\n");
+
+    let mut range = Range::new(node_range(first, rope).start, node_range(last, rope).end);
+    let old_text = rope
+        .byte_slice(first.start_byte()..last.end_byte())
+        .to_string();
+    if old_text == new_text {
+        return None;
+    }
+    // With every import gone, take the line break after them along too.
+    if new_text.is_empty() && (range.end.line as usize) + 1 < rope.len_lines() {
+        range.end = Position::new(range.end.line + 1, 0);
+    }
+    Some(TextEdit::new(range, new_text))
+}
+
+/// Names used outside `package`/`import` declarations, including words in
+/// Javadoc comments so `{@link Foo}` keeps `Foo` imported.
+fn referenced_names(root: Node, rope: &Rope) -> std::collections::HashSet<String> {
+    let mut names = collect_identifiers(root, rope, &["package_declaration", "import_declaration"]);
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.kind() == "block_comment" {
+            let text = get_node_text(node, rope);
+            if text.starts_with("/**") {
+                names.extend(
+                    text.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+                        .filter(|w| !w.is_empty())
+                        .map(str::to_string),
+                );
+            }
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+    names
+}
@@ -1,7 +1,10 @@
+use crate::javadoc::{doc_comment, javadoc_to_markdown};
 use crate::utils::{get_node_text, node_range};
 use crate::{
-    ast::parse_java_type,
-    state::{IndexedClass, IndexedMember},
+    ast::{InferredType, declaration_signature, parse_java_type},
+    state::{
+        FileEntry, IndexedClass, IndexedMember, IndexedOccurrence, Modifiers, TypeKind, Visibility,
+    },
 };
 use ropey::Rope;
 use tower_lsp::lsp_types;
@@ -9,12 +12,13 @@ use tree_sitter::StreamingIterator;
 use tree_sitter::{Query, QueryCursor};
 
 use crate::state::GlobalIndex;
+use crate::stubs::Stub;
 
 lazy_static::lazy_static! {
     static ref JAVA_QUERY: Query = Query::new(
         &tree_sitter_java::LANGUAGE.into(),
         r#"
-        (package_declaration (scoped_identifier) @package)
+        (package_declaration [(identifier) (scoped_identifier)] @package)
         (import_declaration (scoped_identifier) @import)
         (class_declaration name: (identifier) @class)
         (interface_declaration name: (identifier) @interface)
@@ -29,6 +33,14 @@ pub struct Indexer;
 
 impl Indexer {
     pub fn update_file(index: &GlobalIndex, uri: &str, tree: &tree_sitter::Tree, rope: &Rope) {
+        if let Some(entry) = Self::file_entry(uri, tree, rope) {
+            index.insert_entry(entry);
+        }
+    }
+
+    /// What `update_file` would store for a parsed file, without touching
+    /// the index; `None` when `uri` is not a valid URL.
+    pub fn file_entry(uri: &str, tree: &tree_sitter::Tree, rope: &Rope) -> Option<FileEntry> {
         let mut cursor = QueryCursor::new();
         let source = rope.to_string();
 
@@ -40,10 +52,7 @@ impl Indexer {
         let mut indexed_classes = Vec::new();
         let mut indexed_members = Vec::new();
 
-        let url = match lsp_types::Url::parse(uri) {
-            Ok(u) => u,
-            Err(_) => return,
-        };
+        let url = lsp_types::Url::parse(uri).ok()?;
 
         while let Some((m, capture_idx_ref)) = capture_iter.next() {
             let capture_index = *capture_idx_ref;
@@ -67,42 +76,178 @@ impl Indexer {
                         .map(|pkg| format!("{}.{}", pkg, text))
                         .unwrap_or(text.clone());
 
-                    let class_range = node_range(node.parent().unwrap_or(node), rope);
+                    let class_decl = node.parent().unwrap_or(node);
+                    let class_range = node_range(class_decl, rope);
+
+                    let kind = match capture_name {
+                        "interface" => TypeKind::Interface,
+                        "enum" => TypeKind::Enum,
+                        "record" => TypeKind::Record,
+                        "annotation" => TypeKind::Annotation,
+                        _ => TypeKind::Class,
+                    };
+                    let (superclass, interfaces) = supertypes(class_decl, kind, &fqcn, rope);
 
                     indexed_classes.push(IndexedClass {
                         short_name: text.clone(),
                         fqcn: fqcn.clone(),
                         uri: url.clone(),
                         range: class_range,
+                        signature: declaration_signature(class_decl, rope).unwrap_or(text.clone()),
+                        doc: indexed_doc(class_decl, rope),
+                        kind,
+                        superclass,
+                        interfaces,
                     });
 
                     // collect members from class body
                     if let Some(class_node) = node.parent()
                         && let Some(body) = class_node.child_by_field_name("body")
                     {
-                        collect_members(body, &fqcn, &mut indexed_members, &url, rope);
+                        collect_members(body, &fqcn, kind, &mut indexed_members, &url, rope);
                     }
+                    collect_implicit_members(
+                        class_decl,
+                        &fqcn,
+                        kind,
+                        &mut indexed_members,
+                        &url,
+                        rope,
+                    );
                 }
                 _ => {}
             }
         }
 
-        index.upsert_file(uri, package_name, imports, indexed_classes, indexed_members);
+        // Library archives are never searched for references, so skip the
+        // (large) occurrence table for anything outside the workspace.
+        let occurrences = if url.scheme() == "file" || url.scheme() == "untitled" {
+            collect_occurrences(tree.root_node(), rope)
+        } else {
+            Vec::new()
+        };
 
         tracing::debug!("Indexed {}: classes={:?}", uri, defined_classes);
+        Some(FileEntry {
+            uri: uri.to_string(),
+            package_name,
+            imports,
+            classes: indexed_classes,
+            members: indexed_members,
+            occurrences,
+        })
+    }
+
+    /// Indexes a stub rendered from a class file, using the declarations
+    /// recorded while rendering instead of parsing the source.
+    pub fn update_stub(index: &GlobalIndex, uri: &str, stub: &Stub) {
+        if let Some(entry) = Self::stub_entry(uri, stub) {
+            index.insert_entry(entry);
+        }
+    }
+
+    /// What `update_stub` would store, without touching the index.
+    pub fn stub_entry(uri: &str, stub: &Stub) -> Option<FileEntry> {
+        let url = lsp_types::Url::parse(uri).ok()?;
+        let qualify = |simple: &str| match &stub.package {
+            Some(pkg) => format!("{}.{}", pkg, simple),
+            None => simple.to_string(),
+        };
+
+        let classes = stub
+            .classes
+            .iter()
+            .map(|class| IndexedClass {
+                short_name: class.simple_name.clone(),
+                fqcn: qualify(&class.simple_name),
+                uri: url.clone(),
+                range: class.range,
+                signature: class.signature.clone(),
+                doc: None,
+                kind: class.kind,
+                superclass: class.superclass.clone(),
+                interfaces: class.interfaces.clone(),
+            })
+            .collect();
+        let members = stub
+            .members
+            .iter()
+            .map(|member| IndexedMember {
+                name: member.name.clone(),
+                fqmn: format!("{}.{}", qualify(&member.class_name), member.name),
+                uri: url.clone(),
+                range: member.range,
+                param_count: member.param_types.len(),
+                is_varargs: member.is_varargs,
+                param_types: member.param_types.clone(),
+                param_names: member.param_names.clone(),
+                is_field: member.is_field,
+                is_constructor: member.is_constructor,
+                field_type: member.field_type.clone(),
+                return_type: member.return_type.clone(),
+                modifiers: member.modifiers,
+                declaring_kind: member.declaring_kind,
+                signature: member.signature.clone(),
+                doc: None,
+            })
+            .collect();
+
+        Some(FileEntry {
+            uri: uri.to_string(),
+            package_name: stub.package.clone(),
+            imports: stub.imports.clone(),
+            classes,
+            members,
+            occurrences: Vec::new(),
+        })
     }
 }
 
 fn collect_members(
     class_body: tree_sitter::Node,
     fqcn: &str,
+    kind: TypeKind,
     members: &mut Vec<IndexedMember>,
     uri: &lsp_types::Url,
     rope: &Rope,
 ) {
     let mut cursor = class_body.walk();
     for child in class_body.children(&mut cursor) {
-        if child.kind() == "method_declaration" {
+        // Enum bodies keep their methods and fields one level deeper.
+        if child.kind() == "enum_body_declarations" {
+            collect_members(child, fqcn, kind, members, uri, rope);
+            continue;
+        }
+
+        if child.kind() == "enum_constant"
+            && let Some(name_node) = child.child_by_field_name("name")
+        {
+            let name = get_node_text(name_node, rope);
+            let enum_name = fqcn.rsplit('.').next().unwrap_or(fqcn);
+            members.push(IndexedMember {
+                fqmn: format!("{}.{}", fqcn, name),
+                name,
+                uri: uri.clone(),
+                range: node_range(name_node, rope),
+                param_count: 0,
+                is_varargs: false,
+                param_types: Vec::new(),
+                param_names: Vec::new(),
+                is_field: true,
+                is_constructor: false,
+                field_type: Some(InferredType::Class(enum_name.to_string())),
+                return_type: None,
+                modifiers: Modifiers {
+                    visibility: Visibility::Public,
+                    is_static: true,
+                    ..Modifiers::default()
+                },
+                declaring_kind: kind,
+                signature: declaration_signature(child, rope).unwrap_or_default(),
+                doc: indexed_doc(child, rope),
+            });
+        } else if child.kind() == "method_declaration" || child.kind() == "constructor_declaration"
+        {
             if let Some(name_node) = child.child_by_field_name("name") {
                 let name = get_node_text(name_node, rope);
                 let fqmn = format!("{}.{}", fqcn, name);
@@ -122,11 +267,15 @@ fn collect_members(
                     .last()
                     .is_some_and(|p| p.kind() == "spread_parameter");
                 let param_count = params.len();
-                let param_types = params
+                // One entry per parameter, even one too broken to read, so
+                // positions line up with `param_count`.
+                let (param_types, param_names) = params
                     .iter()
-                    .filter_map(|p| p.child_by_field_name("type"))
-                    .map(|t| parse_java_type(t, rope))
-                    .collect();
+                    .map(|p| {
+                        parameter_type_and_name(*p, rope)
+                            .unwrap_or((InferredType::Unknown, String::new()))
+                    })
+                    .unzip();
 
                 members.push(IndexedMember {
                     name,
@@ -136,15 +285,26 @@ fn collect_members(
                     param_count,
                     is_varargs,
                     param_types,
+                    param_names,
                     is_field: false,
+                    is_constructor: child.kind() == "constructor_declaration",
                     field_type: None,
+                    return_type: child
+                        .child_by_field_name("type")
+                        .filter(|t| t.kind() != "void_type")
+                        .map(|t| parse_java_type(t, rope)),
+                    modifiers: member_modifiers(child, kind),
+                    declaring_kind: kind,
+                    signature: declaration_signature(child, rope).unwrap_or_default(),
+                    doc: indexed_doc(child, rope),
                 });
             }
-        } else if child.kind() == "field_declaration" {
+        } else if child.kind() == "field_declaration" || child.kind() == "constant_declaration" {
             let mut sub_cursor = child.walk();
             let field_type = child
                 .child_by_field_name("type")
                 .map(|t| parse_java_type(t, rope));
+            let modifiers = member_modifiers(child, kind);
             for sub in child.children(&mut sub_cursor) {
                 if sub.kind() == "variable_declarator"
                     && let Some(name_node) = sub.child_by_field_name("name")
@@ -159,11 +319,261 @@ fn collect_members(
                         param_count: 0,
                         is_varargs: false,
                         param_types: Vec::new(),
+                        param_names: Vec::new(),
                         is_field: true,
+                        is_constructor: false,
                         field_type: field_type.clone(),
+                        return_type: None,
+                        modifiers,
+                        declaring_kind: kind,
+                        signature: declaration_signature(sub, rope).unwrap_or_default(),
+                        doc: indexed_doc(child, rope),
                     });
                 }
             }
         }
     }
 }
+
+/// Members the compiler declares for enums (`values`, `valueOf`) and
+/// records (one accessor per component, unless written out by hand).
+fn collect_implicit_members(
+    decl: tree_sitter::Node,
+    fqcn: &str,
+    kind: TypeKind,
+    members: &mut Vec<IndexedMember>,
+    uri: &lsp_types::Url,
+    rope: &Rope,
+) {
+    let Some(name_node) = decl.child_by_field_name("name") else {
+        return;
+    };
+    let type_name = get_node_text(name_node, rope);
+    let implicit = |name: &str,
+                    is_static: bool,
+                    return_type: InferredType,
+                    params: Vec<(InferredType, String)>| {
+        let (param_types, param_names): (Vec<_>, Vec<_>) = params.into_iter().unzip();
+        let param_list = param_types
+            .iter()
+            .zip(&param_names)
+            .map(|(ty, name)| format!("{} {}", ty, name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        IndexedMember {
+            name: name.to_string(),
+            fqmn: format!("{}.{}", fqcn, name),
+            uri: uri.clone(),
+            range: node_range(name_node, rope),
+            param_count: param_types.len(),
+            is_varargs: false,
+            signature: format!(
+                "public {}{} {}({})",
+                if is_static { "static " } else { "" },
+                return_type,
+                name,
+                param_list
+            ),
+            param_types,
+            param_names,
+            is_field: false,
+            is_constructor: false,
+            field_type: None,
+            return_type: Some(return_type),
+            modifiers: Modifiers {
+                visibility: Visibility::Public,
+                is_static,
+                ..Modifiers::default()
+            },
+            declaring_kind: kind,
+            doc: None,
+        }
+    };
+
+    match kind {
+        TypeKind::Enum => {
+            let values = InferredType::Class(format!("{}[]", type_name));
+            members.push(implicit("values", true, values, Vec::new()));
+            let name = (InferredType::String, "name".to_string());
+            let this = InferredType::Class(type_name);
+            members.push(implicit("valueOf", true, this, vec![name]));
+        }
+        TypeKind::Record => {
+            let Some(components) = decl.child_by_field_name("parameters") else {
+                return;
+            };
+            let mut cursor = components.walk();
+            for component in components.named_children(&mut cursor) {
+                let Some((ty, name)) = parameter_type_and_name(component, rope) else {
+                    continue;
+                };
+                let fqmn = format!("{}.{}", fqcn, name);
+                if name.is_empty()
+                    || members
+                        .iter()
+                        .any(|m| m.fqmn == fqmn && !m.is_field && m.param_count == 0)
+                {
+                    continue;
+                }
+                let mut accessor = implicit(&name, false, ty, Vec::new());
+                if let Some(component_name) = component.child_by_field_name("name") {
+                    accessor.range = node_range(component_name, rope);
+                }
+                members.push(accessor);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Explicit modifiers of a member declaration plus the ones its declaring
+/// type implies.
+fn member_modifiers(decl: tree_sitter::Node, kind: TypeKind) -> Modifiers {
+    let mut modifiers = Modifiers::default();
+    let mut explicit_visibility = false;
+    let mut cursor = decl.walk();
+    if let Some(list) = decl.children(&mut cursor).find(|c| c.kind() == "modifiers") {
+        let mut list_cursor = list.walk();
+        for keyword in list.children(&mut list_cursor) {
+            match keyword.kind() {
+                "public" => modifiers.visibility = Visibility::Public,
+                "protected" => modifiers.visibility = Visibility::Protected,
+                "private" => modifiers.visibility = Visibility::Private,
+                "static" => modifiers.is_static = true,
+                "abstract" => modifiers.is_abstract = true,
+                "default" => modifiers.is_default = true,
+                _ => continue,
+            }
+            explicit_visibility |= matches!(keyword.kind(), "public" | "protected" | "private");
+        }
+    }
+
+    match kind {
+        TypeKind::Interface | TypeKind::Annotation => {
+            if !explicit_visibility {
+                modifiers.visibility = Visibility::Public;
+            }
+            match decl.kind() {
+                "field_declaration" | "constant_declaration" => modifiers.is_static = true,
+                "method_declaration"
+                    if decl.child_by_field_name("body").is_none()
+                        && !modifiers.is_static
+                        && modifiers.visibility != Visibility::Private =>
+                {
+                    modifiers.is_abstract = true
+                }
+                _ => {}
+            }
+        }
+        TypeKind::Enum if decl.kind() == "constructor_declaration" => {
+            modifiers.visibility = Visibility::Private;
+        }
+        _ => {}
+    }
+    modifiers
+}
+
+/// Type and name of a formal or varargs parameter. Varargs parameters have
+/// no `type` field, so their element type is the first type-like child.
+fn parameter_type_and_name(
+    param: tree_sitter::Node,
+    rope: &Rope,
+) -> Option<(InferredType, String)> {
+    if param.kind() == "spread_parameter" {
+        let mut cursor = param.walk();
+        let children: Vec<_> = param.named_children(&mut cursor).collect();
+        let type_node = children
+            .iter()
+            .find(|c| c.kind() != "modifiers" && c.kind() != "variable_declarator")?;
+        let name = children
+            .iter()
+            .find(|c| c.kind() == "variable_declarator")
+            .and_then(|d| d.child_by_field_name("name"))
+            .map(|n| get_node_text(n, rope))
+            .unwrap_or_default();
+        return Some((parse_java_type(*type_node, rope), name));
+    }
+
+    let type_node = param.child_by_field_name("type")?;
+    let name = param
+        .child_by_field_name("name")
+        .map(|n| get_node_text(n, rope))
+        .unwrap_or_default();
+    Some((parse_java_type(type_node, rope), name))
+}
+
+fn collect_occurrences(root: tree_sitter::Node, rope: &Rope) -> Vec<IndexedOccurrence> {
+    let mut occurrences = Vec::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.kind() == "identifier" || node.kind() == "type_identifier" {
+            occurrences.push(IndexedOccurrence {
+                name: get_node_text(node, rope),
+                range: node_range(node, rope),
+            });
+            continue;
+        }
+
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+    occurrences
+}
+
+fn indexed_doc(node: tree_sitter::Node, rope: &Rope) -> Option<String> {
+    doc_comment(node, rope).map(|raw| javadoc_to_markdown(&raw))
+}
+
+/// The superclass and interfaces named in a type declaration's header,
+/// without type arguments. Classes, enums and records that extend nothing
+/// get their implicit superclass.
+fn supertypes(
+    decl: tree_sitter::Node,
+    kind: TypeKind,
+    fqcn: &str,
+    rope: &Rope,
+) -> (Option<String>, Vec<String>) {
+    let mut superclass = None;
+    let mut interfaces = Vec::new();
+    let mut cursor = decl.walk();
+    for child in decl.children(&mut cursor) {
+        match child.kind() {
+            "superclass" => {
+                let mut inner = child.walk();
+                superclass = child
+                    .named_children(&mut inner)
+                    .next()
+                    .map(|t| erased_type_name(t, rope));
+            }
+            "super_interfaces" | "extends_interfaces" => {
+                let mut inner = child.walk();
+                let lists: Vec<_> = child
+                    .named_children(&mut inner)
+                    .filter(|n| n.kind() == "type_list")
+                    .collect();
+                for list in lists {
+                    let mut types = list.walk();
+                    interfaces.extend(
+                        list.named_children(&mut types)
+                            .map(|t| erased_type_name(t, rope)),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let implicit = match kind {
+        TypeKind::Class if fqcn != "java.lang.Object" => Some("java.lang.Object"),
+        TypeKind::Enum => Some("java.lang.Enum"),
+        TypeKind::Record => Some("java.lang.Record"),
+        _ => None,
+    };
+    (superclass.or(implicit.map(String::from)), interfaces)
+}
+
+fn erased_type_name(type_node: tree_sitter::Node, rope: &Rope) -> String {
+    let text = get_node_text(type_node, rope);
+    let erased = text.split('<').next().unwrap_or(&text);
+    erased.split_whitespace().collect()
+}
@@ -37,6 +37,12 @@ impl<'a> TypeSolver<'a> {
 
             "method_invocation" => self.resolve_method_return_type(node),
 
+            "field_access" => self.resolve_field_access_type(node),
+
+            "this" => enclosing_type_name(node, self.rope)
+                .map(InferredType::Class)
+                .unwrap_or(InferredType::Unknown),
+
             "object_creation_expression" => {
                 if let Some(type_node) = node.child_by_field_name("type") {
                     return parse_java_type(type_node, self.rope);
@@ -108,9 +114,98 @@ impl<'a> TypeSolver<'a> {
                     return parse_java_type(type_node, self.rope);
                 }
             }
+
+            return self.indexed_member_type(invocation_node, &method_name, false);
         }
         InferredType::Unknown
     }
+
+    fn resolve_field_access_type(&self, access_node: Node) -> InferredType {
+        match access_node.child_by_field_name("field") {
+            Some(field) => {
+                let field_name = get_node_text(field, self.rope);
+                self.indexed_member_type(access_node, &field_name, true)
+            }
+            None => InferredType::Unknown,
+        }
+    }
+
+    /// Type of a method or field declared in another file, looked up by
+    /// name in the index and narrowed by the type of the `object` it is
+    /// called on, when that type is known.
+    fn indexed_member_type(&self, node: Node, name: &str, is_field: bool) -> InferredType {
+        let owner = node
+            .child_by_field_name("object")
+            .map(|object| self.infer(object))
+            .and_then(|owner| match owner {
+                InferredType::Class(name) => Some(erased_simple_name(&name).to_string()),
+                InferredType::String => Some("String".to_string()),
+                _ => None,
+            });
+        let arg_count = node
+            .child_by_field_name("arguments")
+            .map(|args| args.named_child_count())
+            .unwrap_or(0);
+
+        let owners: Option<Vec<String>> = owner.map(|owner| {
+            self.index
+                .classes_by_short_name(&owner)
+                .iter()
+                .flat_map(|class| self.index.supertype_closure(&class.fqcn))
+                .collect()
+        });
+        let mut candidates: Vec<_> = self
+            .index
+            .members_by_name(name)
+            .into_iter()
+            .filter(|m| m.is_field == is_field && !m.is_constructor)
+            .filter(|m| {
+                let declared_by = m.fqmn.rsplit_once('.').map_or("", |(owner, _)| owner);
+                owners
+                    .as_ref()
+                    .is_none_or(|owners| owners.iter().any(|o| o == declared_by))
+            })
+            .collect();
+        candidates.sort_by_key(|m| m.param_count != arg_count);
+
+        candidates
+            .into_iter()
+            .find_map(|m| {
+                if is_field {
+                    m.field_type
+                } else {
+                    m.return_type
+                }
+            })
+            .unwrap_or(InferredType::Unknown)
+    }
+}
+
+/// `List` for `java.util.List<String>`.
+pub fn erased_simple_name(type_name: &str) -> &str {
+    let erased = type_name.split('<').next().unwrap_or(type_name).trim();
+    let erased = erased.trim_end_matches("[]");
+    erased.rsplit('.').next().unwrap_or(erased)
+}
+
+/// Name of the class, interface, enum or record declaring `node`.
+fn enclosing_type_name(node: Node, rope: &Rope) -> Option<String> {
+    let mut curr = node.parent();
+    while let Some(n) = curr {
+        if matches!(
+            n.kind(),
+            "class_declaration"
+                | "interface_declaration"
+                | "enum_declaration"
+                | "record_declaration"
+        ) {
+            return n
+                .child_by_field_name("name")
+                .map(|name| get_node_text(name, rope));
+        }
+        curr = n.parent();
+    }
+    None
 }
 
 pub fn find_declaration_node<'tree>(
@@ -126,18 +221,17 @@ pub fn find_declaration_node<'tree>(
         // ---------------------------------------------------------
         // 1. 检查方法/构造函数参数 (Parameters)
         // ---------------------------------------------------------
-        if kind == "method_declaration" || kind == "constructor_declaration" {
-            if let Some(params) = parent.child_by_field_name("parameters") {
-                let mut cursor = params.walk();
-                for param in params.children(&mut cursor) {
-                    // 支持普通参数 (int a) 和变长参数 (int... a)
-                    if param.kind() == "formal_parameter" || param.kind() == "spread_parameter" {
-                        if let Some(name) = param.child_by_field_name("name") {
-                            if get_node_text(name, rope) == target_name {
-                                return Some(param); // 返回参数定义节点
-                            }
-                        }
-                    }
+        if (kind == "method_declaration" || kind == "constructor_declaration")
+            && let Some(params) = parent.child_by_field_name("parameters")
+        {
+            let mut cursor = params.walk();
+            for param in params.children(&mut cursor) {
+                // 支持普通参数 (int a) 和变长参数 (int... a)
+                if (param.kind() == "formal_parameter" || param.kind() == "spread_parameter")
+                    && let Some(name) = param.child_by_field_name("name")
+                    && get_node_text(name, rope) == target_name
+                {
+                    return Some(param); // 返回参数定义节点
                 }
             }
         }
@@ -150,10 +244,10 @@ pub fn find_declaration_node<'tree>(
             let mut cursor = parent.walk();
             for child in parent.children(&mut cursor) {
                 // 局部变量声明: int a = 1, b = 2;
-                if child.kind() == "local_variable_declaration" {
-                    if let Some(node) = find_in_declarators(child, target_name, rope) {
-                        return Some(node);
-                    }
+                if child.kind() == "local_variable_declaration"
+                    && let Some(node) = find_in_declarators(child, target_name, rope)
+                {
+                    return Some(node);
                 }
             }
         }
@@ -168,23 +262,22 @@ pub fn find_declaration_node<'tree>(
             // 或者 (enhanced_for_statement (formal_parameter ...))
 
             // 方式 A: 直接包含 type 和 name
-            if let Some(name_node) = parent.child_by_field_name("name") {
-                if get_node_text(name_node, rope) == target_name {
-                    // 这里 parent 本身就是定义语句，我们可以返回 parent 或者 name_node
-                    // 为了让 TypeSolver 方便找 type，我们返回 parent
-                    return Some(parent);
-                }
+            if let Some(name_node) = parent.child_by_field_name("name")
+                && get_node_text(name_node, rope) == target_name
+            {
+                // 这里 parent 本身就是定义语句，我们可以返回 parent 或者 name_node
+                // 为了让 TypeSolver 方便找 type，我们返回 parent
+                return Some(parent);
             }
 
             // 方式 B: 使用 formal_parameter 作为子节点
             let mut cursor = parent.walk();
             for child in parent.children(&mut cursor) {
-                if child.kind() == "formal_parameter" {
-                    if let Some(name) = child.child_by_field_name("name") {
-                        if get_node_text(name, rope) == target_name {
-                            return Some(child);
-                        }
-                    }
+                if child.kind() == "formal_parameter"
+                    && let Some(name) = child.child_by_field_name("name")
+                    && get_node_text(name, rope) == target_name
+                {
+                    return Some(child);
                 }
             }
         }
@@ -192,16 +285,16 @@ pub fn find_declaration_node<'tree>(
         // ---------------------------------------------------------
         // 4. 检查类成员字段 (Class Fields)
         // ---------------------------------------------------------
-        if kind == "class_declaration" {
-            if let Some(body) = parent.child_by_field_name("body") {
-                let mut cursor = body.walk();
-                for child in body.children(&mut cursor) {
-                    // 字段声明: private int a = 1;
-                    if child.kind() == "field_declaration" {
-                        if let Some(node) = find_in_declarators(child, target_name, rope) {
-                            return Some(node);
-                        }
-                    }
+        if kind == "class_declaration"
+            && let Some(body) = parent.child_by_field_name("body")
+        {
+            let mut cursor = body.walk();
+            for child in body.children(&mut cursor) {
+                // 字段声明: private int a = 1;
+                if child.kind() == "field_declaration"
+                    && let Some(node) = find_in_declarators(child, target_name, rope)
+                {
+                    return Some(node);
                 }
             }
         }
@@ -213,12 +306,11 @@ pub fn find_declaration_node<'tree>(
         if kind == "resource_specification" {
             let mut cursor = parent.walk();
             for resource in parent.children(&mut cursor) {
-                if resource.kind() == "resource" {
-                    if let Some(name) = resource.child_by_field_name("name") {
-                        if get_node_text(name, rope) == target_name {
-                            return Some(resource);
-                        }
-                    }
+                if resource.kind() == "resource"
+                    && let Some(name) = resource.child_by_field_name("name")
+                    && get_node_text(name, rope) == target_name
+                {
+                    return Some(resource);
                 }
             }
         }
@@ -237,12 +329,11 @@ fn find_in_declarators<'tree>(
 ) -> Option<Node<'tree>> {
     let mut cursor = declaration_node.walk();
     for child in declaration_node.children(&mut cursor) {
-        if child.kind() == "variable_declarator" {
-            if let Some(name_node) = child.child_by_field_name("name") {
-                if get_node_text(name_node, rope) == target_name {
-                    return Some(child);
-                }
-            }
+        if child.kind() == "variable_declarator"
+            && let Some(name_node) = child.child_by_field_name("name")
+            && get_node_text(name_node, rope) == target_name
+        {
+            return Some(child);
         }
     }
     None
@@ -255,17 +346,16 @@ fn find_method_definition_node<'tree>(
 ) -> Option<Node<'tree>> {
     let mut curr = start_node;
     while let Some(parent) = curr.parent() {
-        if parent.kind() == "class_declaration" {
-            if let Some(body) = parent.child_by_field_name("body") {
-                let mut cursor = body.walk();
-                for child in body.children(&mut cursor) {
-                    if child.kind() == "method_declaration" {
-                        if let Some(name) = child.child_by_field_name("name") {
-                            if get_node_text(name, rope) == target_name {
-                                return Some(child);
-                            }
-                        }
-                    }
+        if parent.kind() == "class_declaration"
+            && let Some(body) = parent.child_by_field_name("body")
+        {
+            let mut cursor = body.walk();
+            for child in body.children(&mut cursor) {
+                if child.kind() == "method_declaration"
+                    && let Some(name) = child.child_by_field_name("name")
+                    && get_node_text(name, rope) == target_name
+                {
+                    return Some(child);
                 }
             }
         }
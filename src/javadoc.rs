@@ -0,0 +1,190 @@
+use ropey::Rope;
+use tree_sitter::Node;
+
+use crate::utils::get_node_text;
+
+/// Returns the `/** ... */` comment attached to a declaration, if any.
+///
+/// Field and local declarators carry their comment on the enclosing
+/// declaration, so we climb to it before looking at the previous sibling.
+pub fn doc_comment(node: Node, rope: &Rope) -> Option<String> {
+    let decl = if node.kind() == "variable_declarator" {
+        node.parent()?
+    } else {
+        node
+    };
+
+    let prev = decl.prev_sibling()?;
+    if prev.kind() != "block_comment" {
+        return None;
+    }
+
+    let text = get_node_text(prev, rope);
+    text.starts_with("/**").then_some(text)
+}
+
+/// Converts a raw Javadoc comment into Markdown suitable for hover popups.
+pub fn javadoc_to_markdown(raw: &str) -> String {
+    let body = raw
+        .trim()
+        .trim_start_matches("/**")
+        .trim_end_matches("*/")
+        .lines()
+        .map(|line| {
+            let line = line.trim_start();
+            let line = line.strip_prefix('*').unwrap_or(line);
+            line.strip_prefix(' ').unwrap_or(line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut description = Vec::new();
+    let mut params = Vec::new();
+    let mut returns = None;
+    let mut throws = Vec::new();
+    let mut others = Vec::new();
+
+    // Block tags swallow every following line until the next tag.
+    let mut current: Option<(String, String)> = None;
+    let mut flush = |tag: Option<(String, String)>, description: &mut Vec<String>| {
+        let Some((name, text)) = tag else {
+            return;
+        };
+        let text = text.trim().to_string();
+        match name.as_str() {
+            "param" => {
+                let (param, rest) = split_first_word(&text);
+                params.push(format!("- `{}` {}", param, rest));
+            }
+            "return" => returns = Some(text),
+            "throws" | "exception" => {
+                let (ty, rest) = split_first_word(&text);
+                throws.push(format!("- `{}` {}", ty, rest));
+            }
+            "deprecated" => description.insert(0, format!("**Deprecated.** {}\n", text)),
+            "see" | "since" | "author" => others.push(format!("*@{}* {}", name, text)),
+            _ => {}
+        }
+    };
+
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if let Some(tag_line) = trimmed.strip_prefix('@') {
+            flush(current.take(), &mut description);
+            let (name, rest) = split_first_word(tag_line);
+            current = Some((name.to_string(), rest.to_string()));
+        } else if let Some((_, text)) = current.as_mut() {
+            text.push(' ');
+            text.push_str(trimmed);
+        } else {
+            description.push(line.to_string());
+        }
+    }
+    flush(current.take(), &mut description);
+
+    let mut out = convert_inline(description.join("\n").trim());
+    if !params.is_empty() {
+        out.push_str("\n\n**Parameters:**\n");
+        out.push_str(&convert_inline(&params.join("\n")));
+    }
+    if let Some(ret) = returns {
+        out.push_str("\n\n**Returns:** ");
+        out.push_str(&convert_inline(&ret));
+    }
+    if !throws.is_empty() {
+        out.push_str("\n\n**Throws:**\n");
+        out.push_str(&convert_inline(&throws.join("\n")));
+    }
+    if !others.is_empty() {
+        out.push_str("\n\n");
+        out.push_str(&convert_inline(&others.join("  \n")));
+    }
+
+    out.trim().to_string()
+}
+
+fn split_first_word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    match text.find(char::is_whitespace) {
+        Some(idx) => (&text[..idx], text[idx..].trim_start()),
+        None => (text, ""),
+    }
+}
+
+/// Rewrites inline tags (`{@code}`, `{@link}`) and the handful of HTML
+/// elements that commonly appear in JDK docs.
+fn convert_inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{@") {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start + 2..];
+        let Some(end) = matching_brace(tail) else {
+            out.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+
+        let (tag, content) = split_first_word(&tail[..end]);
+        match tag {
+            "code" | "literal" => out.push_str(&format!("`{}`", content.trim())),
+            "link" | "linkplain" => {
+                let (target, label) = split_first_word(content);
+                let label = if label.is_empty() {
+                    target.trim_start_matches('#').replace('#', ".")
+                } else {
+                    label.to_string()
+                };
+                out.push_str(&format!("`{}`", label));
+            }
+            _ => out.push_str(content),
+        }
+        rest = &tail[end + 1..];
+    }
+    out.push_str(rest);
+
+    let replacements = [
+        ("<p>", "\n\n"),
+        ("</p>", ""),
+        ("<P>", "\n\n"),
+        ("<br>", "  \n"),
+        ("<br/>", "  \n"),
+        ("<code>", "`"),
+        ("</code>", "`"),
+        ("<b>", "**"),
+        ("</b>", "**"),
+        ("<strong>", "**"),
+        ("</strong>", "**"),
+        ("<i>", "*"),
+        ("</i>", "*"),
+        ("<em>", "*"),
+        ("</em>", "*"),
+        ("<ul>", "\n"),
+        ("</ul>", "\n"),
+        ("<li>", "\n- "),
+        ("</li>", ""),
+        ("<pre>", "\n```java\n"),
+        ("</pre>", "\n```\n"),
+        ("&lt;", "<"),
+        ("&gt;", ">"),
+        ("&amp;", "&"),
+    ];
+    for (from, to) in replacements {
+        out = out.replace(from, to);
+    }
+    out
+}
+
+fn matching_brace(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (idx, ch) in text.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(idx),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
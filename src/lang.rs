@@ -1,7 +1,11 @@
 use ropey::Rope;
-use tower_lsp::lsp_types::{CompletionItem, DocumentSymbol, Location, Position};
+use tower_lsp::lsp_types::{
+    CodeActionOrCommand, CompletionItem, Diagnostic, DocumentSymbol, Hover, Location, Position,
+    Range, SignatureHelp, TextEdit,
+};
 use tree_sitter::Tree;
 
+use crate::imports::ImportLayout;
 use crate::state::GlobalIndex;
 
 pub trait LanguageService: Send + Sync {
@@ -18,6 +22,99 @@ pub trait LanguageService: Send + Sync {
         current_uri: &str,
     ) -> Option<Location>;
 
+    /// Whether the identifier at `position` is the name a declaration introduces.
+    fn is_declaration_name(&self, tree: &Tree, rope: &Rope, position: Position) -> bool;
+
+    /// Like `goto_definition`, but also resolves a cursor that already sits
+    /// on a declaration name to that declaration.
+    fn declaration_location(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        position: Position,
+        index: &GlobalIndex,
+        current_uri: &str,
+    ) -> Option<Location>;
+
+    /// Edits renaming a local variable or parameter within its scope.
+    /// Returns `None` when the symbol at `position` is not a local.
+    fn local_rename_edits(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        position: Position,
+        new_name: &str,
+    ) -> Option<Vec<TextEdit>>;
+
+    /// Declarations of the static type of the variable, field, parameter
+    /// or call at `position`; several when the type is declared more than
+    /// once, e.g. in the workspace and a library.
+    fn type_definition(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        position: Position,
+        index: &GlobalIndex,
+        current_uri: &str,
+    ) -> Vec<Location>;
+
+    /// Name range of the method or constructor enclosing `position`.
+    fn enclosing_callable(&self, tree: &Tree, rope: &Rope, position: Position) -> Option<Range>;
+
+    /// Name ranges of the calls made by the method or constructor whose
+    /// name is at `declaration`, in source order. Calls inside nested type
+    /// declarations belong to those types.
+    fn call_sites(&self, tree: &Tree, rope: &Rope, declaration: Range) -> Vec<Range>;
+
+    fn hover(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        position: Position,
+        index: &GlobalIndex,
+        current_uri: &str,
+    ) -> Option<Hover>;
+
+    fn signature_help(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        position: Position,
+        index: &GlobalIndex,
+        current_uri: &str,
+    ) -> Option<SignatureHelp>;
+
+    /// Index-backed checks (unresolved types, imports and members). Only
+    /// meaningful once the index has finished warming up.
+    fn semantic_diagnostics(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        index: &GlobalIndex,
+        current_uri: &str,
+    ) -> Vec<Diagnostic>;
+
+    /// Quick fixes for problems within `range`, such as missing imports.
+    fn code_actions(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        range: Range,
+        index: &GlobalIndex,
+        current_uri: &str,
+    ) -> Vec<CodeActionOrCommand>;
+
+    /// Edit rewriting the import section per `layout`, or `None` if it is
+    /// already organized.
+    fn organize_imports(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        index: &GlobalIndex,
+        current_uri: &str,
+        layout: &ImportLayout,
+    ) -> Option<TextEdit>;
+
     fn completion(
         &self,
         tree: &Tree,
@@ -1,14 +1,19 @@
 use super::LanguageService;
 use crate::{
-    ast::get_call_args,
-    inference::TypeSolver,
+    ast::{InferredType, declaration_signature, get_call_args},
+    imports::{ImportDecl, ImportLayout, import_edit, organize_imports},
+    inference::{TypeSolver, erased_simple_name, find_declaration_node},
+    javadoc::{doc_comment, javadoc_to_markdown},
     state::{self, GlobalIndex},
     utils::{calculate_score, find_definition_in_file, get_node_at_pos, get_node_text, node_range},
 };
 use ropey::Rope;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use tower_lsp::lsp_types::{
-    self, CompletionItem, CompletionItemKind, DocumentSymbol, Location, Position, SymbolKind,
+    self, CodeAction, CodeActionKind, CodeActionOrCommand, CompletionItem, CompletionItemKind,
+    Diagnostic, DiagnosticSeverity, DocumentSymbol, Documentation, Hover, HoverContents, Location,
+    MarkupContent, MarkupKind, NumberOrString, ParameterInformation, ParameterLabel, Position,
+    SignatureHelp, SignatureInformation, SymbolKind, TextEdit, Url, WorkspaceEdit,
 };
 use tree_sitter::{Node, Tree};
 
@@ -37,18 +42,22 @@ impl LanguageService for JavaService {
 
         if let Some(ctx) = member_completion_context(tree, rope, position, prev_char) {
             let file_info = index.file_info(current_uri)?;
-            let qualifier_fqcn =
+            let (qualifier_fqcn, static_only) =
                 resolve_qualifier_for_completion(&ctx.qualifier, index, &file_info, tree, rope)?;
 
-            let members = index.members_of_class(&qualifier_fqcn);
+            let members = index
+                .members_with_inherited(&qualifier_fqcn)
+                .into_iter()
+                .filter(|m| !m.is_constructor)
+                .filter(|m| !static_only || m.modifiers.is_static)
+                .filter(|m| is_accessible_from(m, &file_info, current_uri));
             let mut seen = HashSet::new();
             let items = members
-                .into_iter()
                 .filter(|m| seen.insert(m.fqmn.clone()))
                 .filter(|m| {
                     m.fqmn
                         .split('.')
-                        .last()
+                        .next_back()
                         .map(|name| name.starts_with(&ctx.prefix))
                         .unwrap_or(true)
                 })
@@ -56,8 +65,8 @@ impl LanguageService for JavaService {
                     label: m
                         .fqmn
                         .split('.')
-                        .last()
-                        .unwrap_or_else(|| m.fqmn.as_str())
+                        .next_back()
+                        .unwrap_or(m.fqmn.as_str())
                         .to_string(),
                     kind: Some(if m.is_field {
                         CompletionItemKind::FIELD
@@ -80,9 +89,7 @@ impl LanguageService for JavaService {
         }
 
         // Offer classes defined in the current file and imported types as a light baseline
-        let Some(file_info) = index.file_info(current_uri) else {
-            return None;
-        };
+        let file_info = index.file_info(current_uri)?;
 
         let mut items = Vec::new();
         let mut seen = HashSet::new();
@@ -98,15 +105,15 @@ impl LanguageService for JavaService {
         }
 
         for import in &file_info.imports {
-            if let Some(short) = import.split('.').last() {
-                if seen.insert(short.to_string()) {
-                    items.push(CompletionItem {
-                        label: short.to_string(),
-                        kind: Some(CompletionItemKind::CLASS),
-                        detail: Some(import.clone()),
-                        ..CompletionItem::default()
-                    });
-                }
+            if let Some(short) = import.split('.').next_back()
+                && seen.insert(short.to_string())
+            {
+                items.push(CompletionItem {
+                    label: short.to_string(),
+                    kind: Some(CompletionItemKind::CLASS),
+                    detail: Some(import.clone()),
+                    ..CompletionItem::default()
+                });
             }
         }
 
@@ -130,6 +137,38 @@ impl LanguageService for JavaService {
             }
         }
 
+        // Any other indexed class, importing it on accept when needed.
+        let prefix = typed_prefix(tree, rope, position, byte_idx);
+        if !prefix.is_empty() {
+            let mut offered = 0;
+            for name in index.class_names_with_prefix(&prefix, MAX_IMPORT_COMPLETIONS) {
+                if offered >= MAX_IMPORT_COMPLETIONS {
+                    break;
+                }
+                if seen.contains(&name) {
+                    continue;
+                }
+                for class in import_candidates(&name, index, current_uri) {
+                    let additional_text_edits = if needs_import(&class, &file_info, current_uri) {
+                        if import_conflicts(&name, &class.fqcn, &file_info) {
+                            continue;
+                        }
+                        import_edit(tree, rope, &class.fqcn).map(|edit| vec![edit])
+                    } else {
+                        None
+                    };
+                    items.push(CompletionItem {
+                        label: name.clone(),
+                        kind: Some(CompletionItemKind::CLASS),
+                        detail: Some(class.fqcn),
+                        additional_text_edits,
+                        ..CompletionItem::default()
+                    });
+                    offered += 1;
+                }
+            }
+        }
+
         if !prev_char.map(|c| c.is_alphanumeric()).unwrap_or(false) {
             for kw in keywords {
                 items.push(CompletionItem {
@@ -164,94 +203,1053 @@ impl LanguageService for JavaService {
         index: &GlobalIndex,
         current_uri: &str,
     ) -> Option<Location> {
-        let (node, target_name) = get_node_at_pos(tree, rope, position)?;
+        definition(tree, rope, position, index, current_uri, true)
+    }
+
+    fn is_declaration_name(&self, tree: &Tree, rope: &Rope, position: Position) -> bool {
+        get_node_at_pos(tree, rope, position)
+            .is_some_and(|(node, _)| declaration_for_name(node).is_some())
+    }
 
-        let call_args = get_call_args(node);
+    fn declaration_location(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        position: Position,
+        index: &GlobalIndex,
+        current_uri: &str,
+    ) -> Option<Location> {
+        let (node, _) = get_node_at_pos(tree, rope, position)?;
+        let Some(decl) = declaration_for_name(node) else {
+            return definition(tree, rope, position, index, current_uri, false);
+        };
 
-        tracing::info!(
-            "Jump target: {}, Arg count: {:?}",
-            target_name,
-            call_args.len()
-        );
+        // Type declarations are indexed by their whole span, everything else
+        // by the name, mirroring what goto-definition reports.
+        let range = if TYPE_DECLARATION_KINDS.contains(&decl.kind()) {
+            node_range(decl, rope)
+        } else {
+            node_range(node, rope)
+        };
+        Some(Location::new(
+            lsp_types::Url::parse(current_uri).ok()?,
+            range,
+        ))
+    }
 
-        if let Some(range) = find_local_variable(node, rope, &target_name) {
-            return Some(Location::new(
-                lsp_types::Url::parse(current_uri).unwrap(),
-                range,
-            ));
+    fn local_rename_edits(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        position: Position,
+        new_name: &str,
+    ) -> Option<Vec<TextEdit>> {
+        let (node, name) = get_node_at_pos(tree, rope, position)?;
+        if node.kind() != "identifier" || is_member_name(node) {
+            return None;
         }
 
-        if node.kind() != "identifier"
-            && node.kind() != "type_identifier"
-            && node.kind() != "field_identifier"
+        let decl = find_declaration_node(node, &name, rope)?;
+        // Fields are members and get renamed across the workspace instead.
+        if decl
+            .parent()
+            .is_some_and(|p| p.kind() == "field_declaration")
         {
             return None;
         }
-        let global_candidates = index.classes_by_short_name(&target_name);
-        let global_members = index.members_by_name(&target_name);
-        let qualifier = resolve_qualifier(node, rope);
-
-        if qualifier.is_none() {
-            if let Some(range) =
-                find_definition_in_file(node, &target_name, rope, &call_args, index, current_uri)
+        let scope = local_scope(decl)?;
+
+        let mut edits = Vec::new();
+        let mut stack = vec![scope];
+        while let Some(n) = stack.pop() {
+            if n.kind() == "identifier"
+                && !is_member_name(n)
+                && get_node_text(n, rope) == name
+                && find_declaration_node(n, &name, rope).is_some_and(|d| d.id() == decl.id())
             {
-                return Some(Location::new(
-                    lsp_types::Url::parse(current_uri).unwrap(),
-                    range,
-                ));
+                edits.push(TextEdit::new(node_range(n, rope), new_name.to_string()));
+                continue;
             }
+            let mut cursor = n.walk();
+            stack.extend(n.children(&mut cursor));
+        }
+
+        edits.sort_by_key(|e| (e.range.start.line, e.range.start.character));
+        Some(edits)
+    }
+
+    fn signature_help(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        position: Position,
+        index: &GlobalIndex,
+        current_uri: &str,
+    ) -> Option<SignatureHelp> {
+        let byte_idx = offset_for_position(rope, position)?;
+        let args = enclosing_argument_list(tree, byte_idx)?;
+        let call = args.parent()?;
+        let file_info = index.file_info(current_uri)?;
+
+        let (name, overloads) = invoked_overloads(call, rope, index, &file_info, current_uri)?;
+        if overloads.is_empty() {
+            return None;
         }
 
+        let mut cursor = args.walk();
+        let call_args: Vec<Node> = args
+            .named_children(&mut cursor)
+            .filter(|n| !n.kind().ends_with("comment") && !n.is_error())
+            .collect();
+        // A trailing `, ` before the closing paren parses as an ERROR node.
+        let mut cursor = args.walk();
+        let active_param = args
+            .children(&mut cursor)
+            .flat_map(|c| {
+                let mut inner = c.walk();
+                if c.kind() == "ERROR" {
+                    c.children(&mut inner).collect::<Vec<_>>()
+                } else {
+                    vec![c]
+                }
+            })
+            .filter(|c| c.kind() == "," && c.end_byte() <= byte_idx)
+            .count();
+        let arg_count = call_args.len().max(active_param + 1);
+
+        // Rank with the same scoring goto-definition uses, preferring overloads
+        // whose arity already fits over ones still waiting for arguments.
+        let active_signature = overloads
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.is_varargs || m.param_count >= arg_count)
+            .map(|(i, m)| {
+                let score = score_member(m, &call_args, rope, index, current_uri);
+                (i, (match_member_arity(m, arg_count), score))
+            })
+            .max_by_key(|(i, key)| (*key, std::cmp::Reverse(*i)))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+
+        let signatures: Vec<_> = overloads
+            .iter()
+            .map(|m| signature_information(&name, m, active_param))
+            .collect();
+        let active_parameter = signatures[active_signature].active_parameter;
+
+        Some(SignatureHelp {
+            signatures,
+            active_signature: Some(active_signature as u32),
+            active_parameter,
+        })
+    }
+
+    fn semantic_diagnostics(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        index: &GlobalIndex,
+        current_uri: &str,
+    ) -> Vec<Diagnostic> {
         let Some(file_info) = index.file_info(current_uri) else {
-            return select_fallback(global_candidates);
+            return Vec::new();
         };
+        // Without the JDK every `String` would be reported; stay quiet instead.
+        let jdk_indexed = index
+            .classes_by_short_name("Object")
+            .iter()
+            .any(|c| c.fqcn == "java.lang.Object");
+        let type_params = collect_type_parameters(tree.root_node(), rope);
+
+        let mut diagnostics = Vec::new();
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            let diagnostic = match node.kind() {
+                "package_declaration" => continue,
+                "import_declaration" => {
+                    if let Some(d) = check_import(node, rope, index, current_uri, jdk_indexed) {
+                        diagnostics.push(d);
+                    }
+                    continue;
+                }
+                "type_identifier" if jdk_indexed => {
+                    check_type(node, rope, index, &file_info, current_uri, &type_params)
+                }
+                "field_access" => check_member_access(node, "field", rope, index, &file_info),
+                "method_invocation" => check_member_access(node, "name", rope, index, &file_info),
+                _ => None,
+            };
+            diagnostics.extend(diagnostic);
 
-        if let Some(loc) =
-            match_imported_symbol(&global_candidates, &file_info.imports, &target_name)
-        {
-            return Some(Location::new(loc.uri, loc.range));
+            let mut cursor = node.walk();
+            stack.extend(node.children(&mut cursor));
         }
 
-        if let Some(pkg) = &file_info.package_name
-            && let Some(loc) = match_same_package(&global_candidates, pkg, &target_name)
-        {
-            return Some(Location::new(loc.uri, loc.range));
-        }
-
-        if let Some(loc) = match_same_file(&global_candidates, current_uri) {
-            return Some(Location::new(loc.uri, loc.range));
-        }
-
-        let allow_member_lookup = qualifier.is_some()
-            || node.kind() == "field_identifier"
-            || node
-                .parent()
-                .is_some_and(|p| p.kind() == "method_invocation" || p.kind() == "field_access");
-
-        if allow_member_lookup
-            && let Some(loc) = match_member(
-                node,
-                rope,
-                &global_members,
-                &file_info,
-                index,
-                qualifier.as_deref(),
-                &call_args,
-                current_uri,
-                node.parent()
-                    .is_some_and(|p| p.kind() == "method_invocation"),
-            )
+        diagnostics.sort_by_key(|d| (d.range.start.line, d.range.start.character));
+        diagnostics
+    }
+
+    fn code_actions(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        range: lsp_types::Range,
+        index: &GlobalIndex,
+        current_uri: &str,
+    ) -> Vec<CodeActionOrCommand> {
+        let Some(file_info) = index.file_info(current_uri) else {
+            return Vec::new();
+        };
+        let Ok(url) = Url::parse(current_uri) else {
+            return Vec::new();
+        };
+        let type_params = collect_type_parameters(tree.root_node(), rope);
+
+        let mut actions = Vec::new();
+        let mut offered = HashSet::new();
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            let node_lsp_range = node_range(node, rope);
+            if node_lsp_range.end < range.start || range.end < node_lsp_range.start {
+                continue;
+            }
+            if matches!(node.kind(), "package_declaration" | "import_declaration") {
+                continue;
+            }
+            let mut cursor = node.walk();
+            stack.extend(node.children(&mut cursor));
+            if node.kind() != "type_identifier" {
+                continue;
+            }
+
+            let Some(diagnostic) =
+                check_type(node, rope, index, &file_info, current_uri, &type_params)
+            else {
+                continue;
+            };
+            let name = get_node_text(node, rope);
+            let candidates = import_candidates(&name, index, current_uri);
+            let single = candidates.len() == 1;
+            for fqcn in candidates.into_iter().map(|c| c.fqcn) {
+                if !offered.insert(fqcn.clone()) {
+                    continue;
+                }
+                let Some(edit) = import_edit(tree, rope, &fqcn) else {
+                    continue;
+                };
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: format!("Import '{}'", fqcn),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diagnostic.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(url.clone(), vec![edit])])),
+                        ..WorkspaceEdit::default()
+                    }),
+                    is_preferred: Some(single),
+                    ..CodeAction::default()
+                }));
+            }
+        }
+        actions
+    }
+
+    fn organize_imports(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        index: &GlobalIndex,
+        current_uri: &str,
+        layout: &ImportLayout,
+    ) -> Option<TextEdit> {
+        // Packages sharing the file's first two segments (`com.acme`) count
+        // as project code, as do classes indexed from workspace files.
+        let project_root = index
+            .file_info(current_uri)
+            .and_then(|info| info.package_name)
+            .map(|pkg| pkg.split('.').take(2).collect::<Vec<_>>().join("."));
+        let is_project = |decl: &ImportDecl| {
+            if let Some(root) = &project_root
+                && (decl.path == *root || decl.path.starts_with(&format!("{}.", root)))
+            {
+                return true;
+            }
+            let short_name = decl.path.rsplit('.').next().unwrap_or(&decl.path);
+            !decl.is_on_demand
+                && index
+                    .classes_by_short_name(short_name)
+                    .iter()
+                    .any(|c| c.fqcn == decl.path && c.uri.scheme() == "file")
+        };
+        organize_imports(tree, rope, layout, &is_project)
+    }
+
+    fn type_definition(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        position: Position,
+        index: &GlobalIndex,
+        current_uri: &str,
+    ) -> Vec<Location> {
+        let Some((node, _)) = get_node_at_pos(tree, rope, position) else {
+            return Vec::new();
+        };
+        // A called method or accessed field stands for the whole expression.
+        let expression = match node.parent() {
+            Some(parent)
+                if (parent.kind() == "method_invocation"
+                    && parent.child_by_field_name("name") == Some(node))
+                    || (parent.kind() == "field_access"
+                        && parent.child_by_field_name("field") == Some(node)) =>
+            {
+                parent
+            }
+            _ => node,
+        };
+
+        let type_name = match TypeSolver::new(rope, index, current_uri).infer(expression) {
+            InferredType::Class(name) => erased_simple_name(&name).to_string(),
+            InferredType::String => "String".to_string(),
+            _ => return Vec::new(),
+        };
+        let file_info = index.file_info(current_uri);
+        let Some(fqcn) = resolve_class_from_name(&type_name, index, file_info.as_ref()) else {
+            return Vec::new();
+        };
+        index
+            .classes_by_short_name(&type_name)
+            .into_iter()
+            .filter(|c| c.fqcn == fqcn && index.is_visible_from(&c.uri, current_uri))
+            .map(|c| Location::new(c.uri, c.range))
+            .collect()
+    }
+
+    fn enclosing_callable(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        position: Position,
+    ) -> Option<lsp_types::Range> {
+        let byte = offset_for_position(rope, position)?;
+        let mut node = tree.root_node().descendant_for_byte_range(byte, byte);
+        while let Some(n) = node {
+            if CALLABLE_KINDS.contains(&n.kind()) {
+                return Some(node_range(n.child_by_field_name("name")?, rope));
+            }
+            if n.kind() == "class_body" {
+                return None;
+            }
+            node = n.parent();
+        }
+        None
+    }
+
+    fn call_sites(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        declaration: lsp_types::Range,
+    ) -> Vec<lsp_types::Range> {
+        let Some(body) = offset_for_position(rope, declaration.start)
+            .and_then(|byte| tree.root_node().descendant_for_byte_range(byte, byte))
+            .and_then(|name| name.parent())
+            .filter(|decl| CALLABLE_KINDS.contains(&decl.kind()))
+            .and_then(|decl| decl.child_by_field_name("body"))
+        else {
+            return Vec::new();
+        };
+
+        let mut sites = Vec::new();
+        collect_call_sites(body, rope, &mut sites);
+        sites.sort_by_key(|range| (range.start.line, range.start.character));
+        sites
+    }
+
+    fn hover(
+        &self,
+        tree: &Tree,
+        rope: &Rope,
+        position: Position,
+        index: &GlobalIndex,
+        current_uri: &str,
+    ) -> Option<Hover> {
+        let (node, _) = get_node_at_pos(tree, rope, position)?;
+
+        // Hovering a declaration name describes it directly; anything else
+        // goes through the goto-definition chain first.
+        let info = match declaration_for_name(node) {
+            Some(decl) => describe_declaration(decl, rope),
+            None => {
+                let location = self.goto_definition(tree, rope, position, index, current_uri)?;
+                let local = (location.uri.as_str() == current_uri)
+                    .then(|| declaration_at_range(tree, location.range))
+                    .flatten()
+                    .and_then(|decl| describe_declaration(decl, rope));
+                local.or_else(|| index.declaration_at(&location))
+            }
+        }?;
+
+        let mut value = format!("```java\n{}\n```", info.signature);
+        if let Some(doc) = info.doc.filter(|d| !d.is_empty()) {
+            value.push_str("\n\n---\n\n");
+            value.push_str(&doc);
+        }
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(node_range(node, rope)),
+        })
+    }
+}
+
+/// Resolves the symbol at `position`. With `constructors`, the type in a
+/// `new` expression resolves to the matching constructor rather than the
+/// class, as goto-definition wants but references and rename do not.
+fn definition(
+    tree: &Tree,
+    rope: &Rope,
+    position: Position,
+    index: &GlobalIndex,
+    current_uri: &str,
+    constructors: bool,
+) -> Option<Location> {
+    let (node, target_name) = get_node_at_pos(tree, rope, position)?;
+
+    let call_args = get_call_args(node);
+
+    tracing::info!(
+        "Jump target: {}, Arg count: {:?}",
+        target_name,
+        call_args.len()
+    );
+
+    if let Some(range) = find_local_variable(node, rope, &target_name) {
+        return Some(Location::new(
+            lsp_types::Url::parse(current_uri).unwrap(),
+            range,
+        ));
+    }
+
+    if node.kind() != "identifier"
+        && node.kind() != "type_identifier"
+        && node.kind() != "field_identifier"
+    {
+        return None;
+    }
+    let global_candidates: Vec<_> = index
+        .classes_by_short_name(&target_name)
+        .into_iter()
+        .filter(|c| index.is_visible_from(&c.uri, current_uri))
+        .collect();
+    let global_members: Vec<_> = index
+        .members_by_name(&target_name)
+        .into_iter()
+        .filter(|m| !m.is_constructor && index.is_visible_from(&m.uri, current_uri))
+        .collect();
+    let qualifier = resolve_qualifier(node, rope);
+
+    if qualifier.is_none()
+        && let Some(range) =
+            find_definition_in_file(node, &target_name, rope, &call_args, index, current_uri)
+    {
+        return Some(Location::new(
+            lsp_types::Url::parse(current_uri).unwrap(),
+            range,
+        ));
+    }
+
+    let Some(file_info) = index.file_info(current_uri) else {
+        return select_fallback(global_candidates);
+    };
+
+    if constructors
+        && let Some(creation) = constructed_by(node)
+        && let Some(loc) =
+            match_constructor(creation, rope, &target_name, index, &file_info, current_uri)
+    {
+        return Some(loc);
+    }
+
+    if let Some(loc) = match_imported_symbol(&global_candidates, &file_info.imports, &target_name) {
+        return Some(Location::new(loc.uri, loc.range));
+    }
+
+    if let Some(pkg) = &file_info.package_name
+        && let Some(loc) = match_same_package(&global_candidates, pkg, &target_name)
+    {
+        return Some(Location::new(loc.uri, loc.range));
+    }
+
+    if let Some(loc) = match_same_file(&global_candidates, current_uri) {
+        return Some(Location::new(loc.uri, loc.range));
+    }
+
+    let allow_member_lookup = qualifier.is_some()
+        || node.kind() == "field_identifier"
+        || node
+            .parent()
+            .is_some_and(|p| p.kind() == "method_invocation" || p.kind() == "field_access");
+
+    if allow_member_lookup
+        && let Some(loc) = match_member(
+            node,
+            rope,
+            &global_members,
+            &file_info,
+            index,
+            qualifier.as_deref(),
+            &call_args,
+            current_uri,
+            node.parent()
+                .is_some_and(|p| p.kind() == "method_invocation"),
+        )
+    {
+        return Some(loc);
+    }
+
+    if let Some(loc) = match_java_lang(&global_candidates) {
+        return Some(Location::new(loc.uri, loc.range));
+    }
+
+    // Respect Java import rules: if nothing matched, do not jump.
+    None
+}
+
+const CALLABLE_KINDS: &[&str] = &[
+    "method_declaration",
+    "constructor_declaration",
+    "compact_constructor_declaration",
+];
+
+/// Invoked method names and instantiated type names under `node`, skipping
+/// the bodies of nested and anonymous classes.
+fn collect_call_sites(node: Node, rope: &Rope, sites: &mut Vec<lsp_types::Range>) {
+    let callee = match node.kind() {
+        "method_invocation" => node.child_by_field_name("name"),
+        "object_creation_expression" => node.child_by_field_name("type").map(type_name_node),
+        _ => None,
+    };
+    if let Some(callee) = callee {
+        sites.push(node_range(callee, rope));
+    }
+
+    let mut cursor = node.walk();
+    let children: Vec<_> = node.named_children(&mut cursor).collect();
+    for child in children {
+        if child.kind() != "class_body" {
+            collect_call_sites(child, rope, sites);
+        }
+    }
+}
+
+/// The simple name inside a type such as `Map.Entry<K, V>`.
+fn type_name_node(ty: Node) -> Node {
+    match ty.kind() {
+        "generic_type" => ty.named_child(0).map_or(ty, type_name_node),
+        "scoped_type_identifier" => ty
+            .named_child(ty.named_child_count().saturating_sub(1))
+            .map_or(ty, type_name_node),
+        _ => ty,
+    }
+}
+
+/// The `argument_list` the cursor sits in, innermost call first.
+fn enclosing_argument_list(tree: &Tree, byte_idx: usize) -> Option<Node<'_>> {
+    let mut curr = tree
+        .root_node()
+        .descendant_for_byte_range(byte_idx, byte_idx);
+    while let Some(node) = curr {
+        if node.kind() == "argument_list" && node.start_byte() < byte_idx {
+            let closed = node
+                .child(node.child_count().saturating_sub(1))
+                .is_some_and(|c| c.kind() == ")" && !c.is_missing());
+            if !closed || byte_idx < node.end_byte() {
+                return Some(node);
+            }
+        }
+        curr = node.parent();
+    }
+    None
+}
+
+/// Name and candidate overloads for a method or constructor invocation.
+fn invoked_overloads(
+    call: Node,
+    rope: &Rope,
+    index: &GlobalIndex,
+    file_info: &state::FileInfo,
+    current_uri: &str,
+) -> Option<(String, Vec<state::MemberLocation>)> {
+    let mut overloads = match call.kind() {
+        "method_invocation" => {
+            let name_node = call.child_by_field_name("name")?;
+            let name = get_node_text(name_node, rope);
+            let by_name: Vec<_> = index
+                .members_by_name(&name)
+                .into_iter()
+                .filter(|m| !m.is_field && !m.is_constructor)
+                .collect();
+
+            let owner = match call.child_by_field_name("object") {
+                Some(object) => {
+                    let qualifier = get_node_text(object, rope);
+                    resolve_qualifier_type(name_node, rope, &qualifier, index, file_info)
+                }
+                None => None,
+            };
+            let narrowed: Vec<_> = match owner {
+                Some(fqcn) => by_name
+                    .iter()
+                    .filter(|m| m.fqmn.starts_with(&format!("{}.", fqcn)))
+                    .cloned()
+                    .collect(),
+                // Unqualified calls usually target the enclosing file's class.
+                None => by_name
+                    .iter()
+                    .filter(|m| m.uri.as_str() == current_uri)
+                    .cloned()
+                    .collect(),
+            };
+            let overloads = if narrowed.is_empty() {
+                by_name
+            } else {
+                narrowed
+            };
+            (name, overloads)
+        }
+        "object_creation_expression" => {
+            let type_node = call.child_by_field_name("type")?;
+            let type_text = get_node_text(type_node, rope);
+            let short_name = type_text.split('<').next()?.trim().to_string();
+            let fqcn = resolve_class_from_name(&short_name, index, Some(file_info))?;
+            let constructors = index
+                .members_of_class(&fqcn)
+                .into_iter()
+                .filter(|m| m.is_constructor)
+                .collect();
+            (short_name, constructors)
+        }
+        _ => return None,
+    };
+
+    let mut seen = HashSet::new();
+    overloads.1.retain(|m| {
+        seen.insert((
+            m.uri.to_string(),
+            m.range.start.line,
+            m.range.start.character,
+        ))
+    });
+    overloads
+        .1
+        .sort_by_key(|m| (priority_for_uri(&m.uri, &m.fqmn), m.param_count));
+    Some(overloads)
+}
+
+fn signature_information(
+    name: &str,
+    member: &state::MemberLocation,
+    active_param: usize,
+) -> SignatureInformation {
+    let mut label = format!("{}(", name);
+    let mut parameters = Vec::new();
+    for i in 0..member.param_count {
+        if i > 0 {
+            label.push_str(", ");
+        }
+        let ty = member
+            .param_types
+            .get(i)
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| "?".to_string());
+        let dots = if member.is_varargs && i + 1 == member.param_count {
+            "..."
+        } else {
+            ""
+        };
+        let part = match member.param_names.get(i).filter(|n| !n.is_empty()) {
+            Some(param_name) => format!("{}{} {}", ty, dots, param_name),
+            None => format!("{}{}", ty, dots),
+        };
+
+        let start = label.encode_utf16().count() as u32;
+        label.push_str(&part);
+        let end = label.encode_utf16().count() as u32;
+        parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, end]),
+            documentation: None,
+        });
+    }
+    label.push(')');
+
+    // Extra arguments to a varargs method all land on its last parameter.
+    let active_parameter = if member.is_varargs && member.param_count > 0 {
+        active_param.min(member.param_count - 1)
+    } else {
+        active_param
+    };
+
+    SignatureInformation {
+        label,
+        documentation: member.doc.clone().map(|value| {
+            Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            })
+        }),
+        parameters: Some(parameters),
+        active_parameter: Some(active_parameter as u32),
+    }
+}
+
+/// Cap on the index-wide class names looked up, and on the completions
+/// with import edits they yield.
+const MAX_IMPORT_COMPLETIONS: usize = 100;
+
+/// The part of the identifier left of the cursor, if the cursor ends one.
+fn typed_prefix(tree: &Tree, rope: &Rope, position: Position, byte_idx: usize) -> String {
+    if byte_idx == 0 {
+        return String::new();
+    }
+    tree.root_node()
+        .descendant_for_byte_range(byte_idx - 1, byte_idx)
+        .filter(|n| matches!(n.kind(), "identifier" | "type_identifier"))
+        .map(|n| slice_prefix(n, rope, position))
+        .unwrap_or_default()
+}
+
+/// Classes named `short_name` that `current_uri` can import, workspace
+/// first, then the JDK.
+fn import_candidates(
+    short_name: &str,
+    index: &GlobalIndex,
+    current_uri: &str,
+) -> Vec<state::ClassLocation> {
+    let mut candidates: Vec<_> = index
+        .classes_by_short_name(short_name)
+        .into_iter()
+        // Classes in the default package cannot be imported.
+        .filter(|c| c.fqcn.contains('.') && index.is_visible_from(&c.uri, current_uri))
+        .collect();
+    candidates.sort_by(|a, b| {
+        (priority_for_uri(&a.uri, &a.fqcn), &a.fqcn)
+            .cmp(&(priority_for_uri(&b.uri, &b.fqcn), &b.fqcn))
+    });
+    candidates.dedup_by(|a, b| a.fqcn == b.fqcn);
+    candidates
+}
+
+fn needs_import(
+    class: &state::ClassLocation,
+    file_info: &state::FileInfo,
+    current_uri: &str,
+) -> bool {
+    let package = class.fqcn.rsplit_once('.').map(|(p, _)| p);
+    class.uri.as_str() != current_uri
+        && package != Some("java.lang")
+        && package != file_info.package_name.as_deref()
+        && !file_info.imports.iter().any(|i| i == &class.fqcn)
+}
+
+/// Whether a different class with the same short name is already imported.
+fn import_conflicts(short_name: &str, fqcn: &str, file_info: &state::FileInfo) -> bool {
+    file_info
+        .imports
+        .iter()
+        .any(|import| import != fqcn && import.rsplit('.').next() == Some(short_name))
+}
+
+/// Methods every class inherits from `java.lang.Object`.
+const OBJECT_METHODS: &[&str] = &[
+    "equals",
+    "hashCode",
+    "toString",
+    "getClass",
+    "notify",
+    "notifyAll",
+    "wait",
+    "clone",
+    "finalize",
+];
+
+/// Contextual keywords the grammar parses as `type_identifier`, such as
+/// `var` in `var list = new ArrayList<String>();`. `record`, `yield`,
+/// `sealed` and `permits` are keywords in the grammar and never show up here.
+const CONTEXTUAL_TYPE_NAMES: &[&str] = &["var"];
+
+pub const UNRESOLVED_TYPE: &str = "unresolved-type";
+pub const UNRESOLVED_IMPORT: &str = "unresolved-import";
+pub const UNRESOLVED_MEMBER: &str = "unresolved-member";
+
+fn semantic_warning(node: Node, rope: &Rope, code: &str, message: String) -> Diagnostic {
+    Diagnostic {
+        range: node_range(node, rope),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some("jrsls".to_string()),
+        message,
+        ..Diagnostic::default()
+    }
+}
+
+fn collect_type_parameters(root: Node, rope: &Rope) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.kind() == "type_parameter"
+            && let Some(name) = node.named_child(0)
         {
-            return Some(loc);
+            names.insert(get_node_text(name, rope));
         }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+    names
+}
+
+fn check_import(
+    node: Node,
+    rope: &Rope,
+    index: &GlobalIndex,
+    current_uri: &str,
+    jdk_indexed: bool,
+) -> Option<Diagnostic> {
+    let mut cursor = node.walk();
+    let children: Vec<_> = node.children(&mut cursor).collect();
+    // Static and on-demand imports name members and packages, not classes.
+    if children
+        .iter()
+        .any(|c| c.kind() == "static" || c.kind() == "asterisk")
+    {
+        return None;
+    }
+
+    let path = children.iter().find(|c| c.kind() == "scoped_identifier")?;
+    let fqcn = get_node_text(*path, rope);
+    if !jdk_indexed && (fqcn.starts_with("java.") || fqcn.starts_with("javax.")) {
+        return None;
+    }
+
+    let short_name = fqcn.rsplit('.').next()?;
+    if index
+        .classes_by_short_name(short_name)
+        .iter()
+        .any(|c| c.fqcn == fqcn && index.is_visible_from(&c.uri, current_uri))
+    {
+        return None;
+    }
+
+    Some(semantic_warning(
+        *path,
+        rope,
+        UNRESOLVED_IMPORT,
+        format!("cannot resolve import `{}`", fqcn),
+    ))
+}
+
+fn check_type(
+    node: Node,
+    rope: &Rope,
+    index: &GlobalIndex,
+    file_info: &state::FileInfo,
+    current_uri: &str,
+    type_params: &HashSet<String>,
+) -> Option<Diagnostic> {
+    // Qualified names (`java.util.List`, `Map.Entry`) are not looked up by short name.
+    if node
+        .parent()
+        .is_some_and(|p| p.kind() == "scoped_type_identifier")
+    {
+        return None;
+    }
+
+    let name = get_node_text(node, rope);
+    if CONTEXTUAL_TYPE_NAMES.contains(&name.as_str())
+        || type_params.contains(&name)
+        || file_info.defined_classes.contains(&name)
+    {
+        return None;
+    }
+
+    let candidates: Vec<_> = index
+        .classes_by_short_name(&name)
+        .into_iter()
+        .filter(|c| index.is_visible_from(&c.uri, current_uri))
+        .collect();
+    let resolved = match_imported_symbol(&candidates, &file_info.imports, &name).is_some()
+        || file_info
+            .package_name
+            .as_deref()
+            .is_some_and(|pkg| match_same_package(&candidates, pkg, &name).is_some())
+        || match_same_file(&candidates, current_uri).is_some()
+        || match_java_lang(&candidates).is_some()
+        || candidates.iter().any(|c| {
+            file_info
+                .imports
+                .iter()
+                .any(|import| c.fqcn == format!("{}.{}", import, name))
+        });
+    if resolved {
+        return None;
+    }
+
+    Some(semantic_warning(
+        node,
+        rope,
+        UNRESOLVED_TYPE,
+        format!("cannot resolve type `{}`", name),
+    ))
+}
+
+fn check_member_access(
+    node: Node,
+    name_field: &str,
+    rope: &Rope,
+    index: &GlobalIndex,
+    file_info: &state::FileInfo,
+) -> Option<Diagnostic> {
+    let object = node.child_by_field_name("object")?;
+    if !matches!(object.kind(), "identifier" | "field_access") {
+        return None;
+    }
+    let name_node = node.child_by_field_name(name_field)?;
+    let name = get_node_text(name_node, rope);
+    if OBJECT_METHODS.contains(&name.as_str()) {
+        return None;
+    }
+    // `Outer.Inner` names a nested type, not a member.
+    if !index.classes_by_short_name(&name).is_empty() {
+        return None;
+    }
+
+    let qualifier = get_node_text(object, rope);
+    let fqcn = resolve_qualifier_type(name_node, rope, &qualifier, index, file_info)?;
+    let short_name = fqcn.rsplit('.').next()?;
+    let class = index
+        .classes_by_short_name(short_name)
+        .into_iter()
+        .find(|c| c.fqcn == fqcn)?;
 
-        if let Some(loc) = match_java_lang(&global_candidates) {
-            return Some(Location::new(loc.uri, loc.range));
+    // Members may come from supertypes the index cannot see yet.
+    let supertypes = index.supertype_closure(&class.fqcn);
+    if supertypes
+        .iter()
+        .any(|s| s != "java.lang.Object" && index.class_by_fqcn(s).is_none())
+    {
+        return None;
+    }
+
+    let suffix = format!(".{}", name);
+    if index
+        .members_with_inherited(&fqcn)
+        .iter()
+        .any(|m| m.fqmn.ends_with(&suffix))
+    {
+        return None;
+    }
+
+    Some(semantic_warning(
+        name_node,
+        rope,
+        UNRESOLVED_MEMBER,
+        format!("`{}` is not a member of `{}`", name, fqcn),
+    ))
+}
+
+const TYPE_DECLARATION_KINDS: &[&str] = &[
+    "class_declaration",
+    "interface_declaration",
+    "enum_declaration",
+    "record_declaration",
+    "annotation_type_declaration",
+];
+
+const DECLARATION_KINDS: &[&str] = &[
+    "method_declaration",
+    "constructor_declaration",
+    "class_declaration",
+    "interface_declaration",
+    "enum_declaration",
+    "record_declaration",
+    "annotation_type_declaration",
+    "variable_declarator",
+    "enum_constant",
+    "formal_parameter",
+    "resource",
+    "catch_formal_parameter",
+];
+
+/// Identifiers naming a member of some other object (`obj.name`,
+/// `obj.name()`) or a method declaration never refer to a local.
+fn is_member_name(node: Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    let field = match parent.kind() {
+        "field_access" => "field",
+        "method_invocation" | "method_declaration" => "name",
+        _ => return false,
+    };
+    parent
+        .child_by_field_name(field)
+        .is_some_and(|n| n.id() == node.id())
+}
+
+/// The innermost node a local declaration is visible in.
+fn local_scope(decl: Node) -> Option<Node> {
+    let mut curr = if decl.kind() == "enhanced_for_statement" {
+        Some(decl)
+    } else {
+        decl.parent()
+    };
+    while let Some(n) = curr {
+        if matches!(
+            n.kind(),
+            "block"
+                | "method_declaration"
+                | "constructor_declaration"
+                | "lambda_expression"
+                | "for_statement"
+                | "enhanced_for_statement"
+                | "try_with_resources_statement"
+                | "catch_clause"
+        ) {
+            return Some(n);
         }
+        curr = n.parent();
+    }
+    None
+}
 
-        // Respect Java import rules: if nothing matched, do not jump.
-        None
+/// Returns the declaration node when `node` is the name it declares.
+fn declaration_for_name(node: Node) -> Option<Node> {
+    let parent = node.parent()?;
+    if !DECLARATION_KINDS.contains(&parent.kind()) {
+        return None;
+    }
+    let name = parent.child_by_field_name("name")?;
+    (name.id() == node.id()).then_some(parent)
+}
+
+/// Finds the declaration whose name (or whole body, for classes) spans `range`.
+fn declaration_at_range(tree: &Tree, range: lsp_types::Range) -> Option<Node<'_>> {
+    let start = tree_sitter::Point::new(range.start.line as usize, range.start.character as usize);
+    let end = tree_sitter::Point::new(range.end.line as usize, range.end.character as usize);
+    let node = tree.root_node().descendant_for_point_range(start, end)?;
+    if DECLARATION_KINDS.contains(&node.kind()) {
+        return Some(node);
     }
+    declaration_for_name(node)
+}
+
+fn describe_declaration(decl: Node, rope: &Rope) -> Option<state::DeclarationInfo> {
+    Some(state::DeclarationInfo {
+        signature: declaration_signature(decl, rope)?,
+        doc: doc_comment(decl, rope).map(|raw| javadoc_to_markdown(&raw)),
+    })
 }
 
 fn match_imported_symbol(
@@ -260,10 +1258,10 @@ fn match_imported_symbol(
     target_name: &str,
 ) -> Option<state::ClassLocation> {
     for import in imports {
-        if import.ends_with(&format!(".{}", target_name)) {
-            if let Some(loc) = candidates.iter().find(|loc| &loc.fqcn == import) {
-                return Some(loc.clone());
-            }
+        if import.ends_with(&format!(".{}", target_name))
+            && let Some(loc) = candidates.iter().find(|loc| &loc.fqcn == import)
+        {
+            return Some(loc.clone());
         }
     }
     None
@@ -317,6 +1315,7 @@ fn select_fallback(candidates: Vec<state::ClassLocation>) -> Option<Location> {
     Some(Location::new(loc.uri.clone(), loc.range))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn match_member(
     node: Node,
     rope: &Rope,
@@ -340,18 +1339,27 @@ fn match_member(
         );
         return None;
     }
-    let qualifier_fqcn =
-        resolve_qualifier_type(node, rope, &qualifier, index, file_info);
+    let qualifier_fqcn = resolve_qualifier_type(node, rope, &qualifier, index, file_info);
     let fqcn = qualifier_fqcn.clone().unwrap_or_default();
     let arg_count = count_args(node);
     let prefer_method_usage = has_ancestor_kind(node, "method_invocation")
         || prefer_method_usage_hint
         || is_followed_by_paren(node, rope);
     let prefer_field_usage = !prefer_method_usage && call_args.is_empty();
+    // Members may be inherited; the nearest declaring type wins.
+    let owners = if fqcn.is_empty() {
+        Vec::new()
+    } else {
+        index.supertype_closure(&fqcn)
+    };
+    let owner_depth = |m: &state::MemberLocation| {
+        let owner = m.fqmn.rsplit_once('.').map_or("", |(owner, _)| owner);
+        owners.iter().position(|o| o == owner)
+    };
 
     let candidates: Vec<_> = members
         .iter()
-        .filter(|m| fqcn.is_empty() || m.fqmn.starts_with(&format!("{}.", fqcn)))
+        .filter(|m| fqcn.is_empty() || owner_depth(m).is_some())
         .filter(|m| !prefer_method_usage || !m.is_field)
         .filter(|m| match_member_arity(m, arg_count))
         .collect();
@@ -406,11 +1414,20 @@ fn match_member(
 
     scored.sort_by_key(|(m, score)| {
         (
-            prefer_field_usage.then(|| !m.is_field).unwrap_or(false),
-            prefer_method_usage.then(|| m.is_field).unwrap_or(false),
+            if prefer_field_usage {
+                !m.is_field
+            } else {
+                false
+            },
+            if prefer_method_usage {
+                m.is_field
+            } else {
+                false
+            },
             m.is_varargs,
             -score,
             (m.param_count as isize - arg_count as isize).abs(),
+            owner_depth(m),
             priority_for_uri(&m.uri, &m.fqmn),
         )
     });
@@ -419,6 +1436,60 @@ fn match_member(
     Some(Location::new(member.uri.clone(), member.range))
 }
 
+/// The `new` expression whose type `node` names, if any.
+fn constructed_by(node: Node) -> Option<Node> {
+    let mut current = node;
+    while let Some(parent) = current.parent() {
+        match parent.kind() {
+            "object_creation_expression" => {
+                return (parent.child_by_field_name("type")? == current).then_some(parent);
+            }
+            "generic_type" | "scoped_type_identifier" => current = parent,
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// The constructor of `class_name` that best fits the arguments of
+/// `creation`; `None` when the class declares no constructors.
+fn match_constructor(
+    creation: Node,
+    rope: &Rope,
+    class_name: &str,
+    index: &GlobalIndex,
+    file_info: &state::FileInfo,
+    current_uri: &str,
+) -> Option<Location> {
+    let fqcn = resolve_class_from_name(class_name, index, Some(file_info))?;
+    let args: Vec<_> = match creation.child_by_field_name("arguments") {
+        Some(list) => {
+            let mut cursor = list.walk();
+            list.named_children(&mut cursor)
+                .filter(|n| n.kind() != "comment")
+                .collect()
+        }
+        None => Vec::new(),
+    };
+    let constructors: Vec<_> = index
+        .members_of_class(&fqcn)
+        .into_iter()
+        .filter(|m| m.is_constructor && is_accessible_from(m, file_info, current_uri))
+        .collect();
+
+    let mut scored: Vec<_> = constructors
+        .iter()
+        .filter(|m| match_member_arity(m, args.len()))
+        .filter_map(|m| score_member(m, &args, rope, index, current_uri).map(|score| (m, score)))
+        .collect();
+    scored.sort_by_key(|(m, score)| (m.is_varargs, -score));
+    scored
+        .first()
+        .map(|(m, _)| *m)
+        .or(constructors.first())
+        .map(|m| Location::new(m.uri.clone(), m.range))
+}
+
 fn score_member(
     member: &state::MemberLocation,
     call_args: &[Node],
@@ -444,7 +1515,7 @@ fn score_member(
         }
         let arg_type = solver.infer(*arg);
         let param_type = &member.param_types[param_idx];
-        let score = calculate_score(&arg_type, param_type);
+        let score = calculate_score(&arg_type, param_type, index);
         if score < 0 {
             tracing::debug!(
                 "reject member {} due to type mismatch: arg={:?} param={:?} score={}",
@@ -478,21 +1549,21 @@ fn priority_for_uri(uri: &lsp_types::Url, fqmn: &str) -> i32 {
 }
 
 fn count_args(node: Node) -> usize {
-    if let Some(parent) = node.parent() {
-        if parent.kind() == "method_invocation" {
-            let mut cursor = parent.walk();
-            let args: Vec<_> = parent
-                .children_by_field_name("arguments", &mut cursor)
-                .flat_map(|arglist| {
-                    let mut inner = arglist.walk();
-                    arglist
-                        .children(&mut inner)
-                        .filter(|n| n.kind() != "," && n.is_named())
-                        .collect::<Vec<_>>()
-                })
-                .collect();
-            return args.len();
-        }
+    if let Some(parent) = node.parent()
+        && parent.kind() == "method_invocation"
+    {
+        let mut cursor = parent.walk();
+        let args: Vec<_> = parent
+            .children_by_field_name("arguments", &mut cursor)
+            .flat_map(|arglist| {
+                let mut inner = arglist.walk();
+                arglist
+                    .children(&mut inner)
+                    .filter(|n| n.kind() != "," && n.is_named())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        return args.len();
     }
     0
 }
@@ -510,8 +1581,8 @@ fn has_ancestor_kind(node: Node, kind: &str) -> bool {
 
 fn is_followed_by_paren(node: Node, rope: &Rope) -> bool {
     let end_char = rope.byte_to_char(node.end_byte());
-    let mut iter = rope.chars_at(end_char);
-    while let Some(ch) = iter.next() {
+    let iter = rope.chars_at(end_char);
+    for ch in iter {
         if ch.is_whitespace() {
             continue;
         }
@@ -533,16 +1604,16 @@ fn find_local_variable(node: Node, rope: &Rope, name: &str) -> Option<lsp_types:
     let mut curr = Some(node);
 
     while let Some(n) = curr {
-        if n.kind() == "method_declaration" {
-            if let Some(params) = n.child_by_field_name("parameters") {
-                let mut cursor = params.walk();
-                for p in params.children(&mut cursor) {
-                    if (p.kind() == "formal_parameter" || p.kind() == "spread_parameter")
-                        && let Some(name_node) = p.child_by_field_name("name")
-                        && get_node_text(name_node, rope) == name
-                    {
-                        return Some(node_range(name_node, rope));
-                    }
+        if n.kind() == "method_declaration"
+            && let Some(params) = n.child_by_field_name("parameters")
+        {
+            let mut cursor = params.walk();
+            for p in params.children(&mut cursor) {
+                if (p.kind() == "formal_parameter" || p.kind() == "spread_parameter")
+                    && let Some(name_node) = p.child_by_field_name("name")
+                    && get_node_text(name_node, rope) == name
+                {
+                    return Some(node_range(name_node, rope));
                 }
             }
         }
@@ -602,47 +1673,90 @@ fn byte_before(rope: &Rope, byte_idx: usize) -> Option<char> {
     Some(rope.char(char_idx))
 }
 
+/// The expression the `.` before `position` is applied to, e.g.
+/// `repo.find(1)` in `repo.find(1).`.
 fn qualifier_at_dot(tree: &Tree, rope: &Rope, position: Position) -> Option<String> {
-    let lookup = position_before(rope, position)?;
-    let byte = offset_for_position(rope, lookup)?;
-    let node = tree.root_node().descendant_for_byte_range(byte, byte)?;
-    let target = if node.kind() == "identifier" || node.kind() == "type_identifier" {
-        node
-    } else {
-        node.parent()?
-    };
-    Some(get_node_text(target, rope))
+    let dot = offset_for_position(rope, position_before(rope, position)?)?;
+    let last = dot.checked_sub(1)?;
+    let mut node = tree.root_node().descendant_for_byte_range(last, last)?;
+    while let Some(parent) = node.parent()
+        && parent.end_byte() == dot
+        && matches!(
+            parent.kind(),
+            "argument_list"
+                | "method_invocation"
+                | "field_access"
+                | "array_access"
+                | "parenthesized_expression"
+                | "scoped_identifier"
+                | "scoped_type_identifier"
+        )
+    {
+        node = parent;
+    }
+    Some(get_node_text(node, rope))
 }
 
+/// The class whose members complete after `qualifier`, and whether the
+/// qualifier names the class itself, so only static members apply.
 fn resolve_qualifier_for_completion(
     qualifier: &str,
     index: &GlobalIndex,
     file_info: &state::FileInfo,
     tree: &Tree,
     rope: &Rope,
-) -> Option<String> {
-    if let Some(fqcn) = resolve_qualifier_chain(qualifier, index, file_info) {
-        return Some(fqcn);
+) -> Option<(String, bool)> {
+    let names_variable = find_identifier_type(tree.root_node(), rope, qualifier).is_some()
+        || find_type_by_text_scan(rope, qualifier).is_some();
+    if !names_variable
+        && !qualifier.contains(['.', '('])
+        && let Some(fqcn) = resolve_class_from_name(qualifier, index, Some(file_info))
+    {
+        return Some((fqcn, true));
+    }
+
+    if let Some(fqcn) = resolve_qualifier_chain(qualifier, index, file_info, tree.root_node(), rope)
+    {
+        return Some((fqcn, false));
     }
 
     // Try to infer variable type from local declarations
     if let Some(type_name) = find_identifier_type(tree.root_node(), rope, qualifier) {
         if let Some(fqcn) = resolve_class_from_name(&type_name, index, Some(file_info)) {
-            return Some(fqcn);
+            return Some((fqcn, false));
         }
-        return Some(type_name);
+        return Some((type_name, false));
     }
 
     if let Some(type_name) = find_type_by_text_scan(rope, qualifier) {
         if let Some(fqcn) = resolve_class_from_name(&type_name, index, Some(file_info)) {
-            return Some(fqcn);
+            return Some((fqcn, false));
         }
-        return Some(type_name);
+        return Some((type_name, false));
     }
 
     None
 }
 
+/// Whether code in `current_uri` may use `member`: private members stay in
+/// their file and package-private ones in their package.
+fn is_accessible_from(
+    member: &state::MemberLocation,
+    file_info: &state::FileInfo,
+    current_uri: &str,
+) -> bool {
+    match member.modifiers.visibility {
+        state::Visibility::Private => member.uri.as_str() == current_uri,
+        state::Visibility::Package => {
+            let owner = member.fqmn.rsplit_once('.').map_or("", |(owner, _)| owner);
+            let package = owner.rsplit_once('.').map_or("", |(package, _)| package);
+            member.uri.as_str() == current_uri
+                || package == file_info.package_name.as_deref().unwrap_or("")
+        }
+        _ => true,
+    }
+}
+
 struct MemberContext {
     qualifier: String,
     prefix: String,
@@ -669,27 +1783,24 @@ fn member_completion_context(
         .root_node()
         .descendant_for_byte_range(byte_idx.saturating_sub(1), byte_idx.saturating_sub(1))?;
 
-    if node.kind() == "identifier" || node.kind() == "field_identifier" {
-        if let Some(parent) = node.parent() {
-            if parent.kind() == "field_access" {
-                let object = parent.child_by_field_name("object")?;
-                let qualifier = get_node_text(object, rope);
-                let prefix = slice_prefix(node, rope, position);
-                return Some(MemberContext { qualifier, prefix });
-            }
-        }
+    if (node.kind() == "identifier" || node.kind() == "field_identifier")
+        && let Some(parent) = node.parent()
+        && parent.kind() == "field_access"
+    {
+        let object = parent.child_by_field_name("object")?;
+        let qualifier = get_node_text(object, rope);
+        let prefix = slice_prefix(node, rope, position);
+        return Some(MemberContext { qualifier, prefix });
     }
 
-    if node.kind() == "identifier" {
-        if let Some(parent) = node.parent() {
-            if parent.kind() == "method_invocation" {
-                if let Some(object) = parent.child_by_field_name("object") {
-                    let qualifier = get_node_text(object, rope);
-                    let prefix = slice_prefix(node, rope, position);
-                    return Some(MemberContext { qualifier, prefix });
-                }
-            }
-        }
+    if node.kind() == "identifier"
+        && let Some(parent) = node.parent()
+        && parent.kind() == "method_invocation"
+        && let Some(object) = parent.child_by_field_name("object")
+    {
+        let qualifier = get_node_text(object, rope);
+        let prefix = slice_prefix(node, rope, position);
+        return Some(MemberContext { qualifier, prefix });
     }
 
     // Fallback to textual split: find nearest '.' before cursor
@@ -734,10 +1845,10 @@ fn resolve_class_from_name(
         if let Some(loc) = match_imported_symbol(&candidates, &info.imports, name) {
             return Some(loc.fqcn);
         }
-        if let Some(pkg) = &info.package_name {
-            if let Some(loc) = match_same_package(&candidates, pkg, name) {
-                return Some(loc.fqcn);
-            }
+        if let Some(pkg) = &info.package_name
+            && let Some(loc) = match_same_package(&candidates, pkg, name)
+        {
+            return Some(loc.fqcn);
         }
     }
 
@@ -748,29 +1859,68 @@ fn resolve_class_from_name(
     candidates.first().map(|c| c.fqcn.clone())
 }
 
+/// Follows a qualifier such as `System.out` or `repo.find(1).customer()`
+/// through field types and method return types to the class it denotes.
+/// The head is a class name or, failing that, a variable declared in the
+/// file under `root`.
 fn resolve_qualifier_chain(
     qualifier: &str,
     index: &GlobalIndex,
     file_info: &state::FileInfo,
+    root: Node,
+    rope: &Rope,
 ) -> Option<String> {
-    let parts = qualifier.split('.').collect::<Vec<_>>();
-    if parts.is_empty() {
+    let parts = split_qualifier(qualifier);
+    let (head, rest) = parts.split_first()?;
+    if head.contains('(') {
         return None;
     }
 
-    let mut current_fqcn = resolve_class_from_name(parts[0], index, Some(file_info))?;
+    let mut current_fqcn = match resolve_class_from_name(head, index, Some(file_info)) {
+        Some(fqcn) => fqcn,
+        None => {
+            let type_name = find_identifier_type(root, rope, head)?;
+            let type_name = type_name.split('<').next().unwrap_or(&type_name).trim();
+            resolve_class_from_name(type_name, index, Some(file_info))?
+        }
+    };
 
-    for part in parts.iter().skip(1) {
-        let members = index.members_of_class(&current_fqcn);
-        let field = members
-            .iter()
-            .find(|m| m.is_field && m.fqmn.ends_with(&format!(".{}", part)));
-        let field_type = field.and_then(|f| f.field_type.clone());
+    for part in rest {
+        let members = index.members_with_inherited(&current_fqcn);
+        let member_type = match part.split_once('(') {
+            Some((name, args)) => {
+                let arg_count = count_top_level_args(args.strip_suffix(')').unwrap_or(args));
+                let methods: Vec<_> = members
+                    .iter()
+                    .filter(|m| {
+                        !m.is_field
+                            && !m.is_constructor
+                            && m.fqmn.ends_with(&format!(".{}", name.trim()))
+                    })
+                    .collect();
+                methods
+                    .iter()
+                    .find(|m| match_member_arity(m, arg_count))
+                    .or(methods.first())
+                    .and_then(|m| m.return_type.clone())
+            }
+            None => members
+                .iter()
+                .find(|m| m.is_field && m.fqmn.ends_with(&format!(".{}", part)))
+                .and_then(|f| f.field_type.clone()),
+        };
 
-        let type_name = match field_type {
+        let type_name = match member_type {
             Some(crate::ast::InferredType::Class(name)) => name,
+            Some(crate::ast::InferredType::String) => "String".to_string(),
             _ => return None,
         };
+        let type_name = type_name
+            .split('<')
+            .next()
+            .unwrap_or(&type_name)
+            .trim()
+            .to_string();
 
         current_fqcn =
             resolve_class_from_name(&type_name, index, Some(file_info)).unwrap_or(type_name);
@@ -779,23 +1929,69 @@ fn resolve_qualifier_chain(
     Some(current_fqcn)
 }
 
+/// Splits a qualifier at the dots outside of argument lists and type
+/// arguments: `a.b(c.d).e` becomes `a`, `b(c.d)` and `e`.
+fn split_qualifier(qualifier: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in qualifier.char_indices() {
+        match c {
+            '(' | '<' | '[' => depth += 1,
+            ')' | '>' | ']' => depth = depth.saturating_sub(1),
+            '.' if depth == 0 => {
+                parts.push(qualifier[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(qualifier[start..].trim());
+    parts
+}
+
+fn count_top_level_args(args: &str) -> usize {
+    if args.trim().is_empty() {
+        return 0;
+    }
+    let mut depth = 0usize;
+    let mut count = 1;
+    for c in args.chars() {
+        match c {
+            '(' | '<' | '[' | '{' => depth += 1,
+            ')' | '>' | ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => count += 1,
+            _ => {}
+        }
+    }
+    count
+}
+
 fn find_identifier_type(root: Node, rope: &Rope, name: &str) -> Option<String> {
     let mut stack = vec![root];
     while let Some(node) = stack.pop() {
-        if node.kind() == "local_variable_declaration" || node.kind() == "field_declaration" {
-            if let Some(t) = node.child_by_field_name("type") {
-                let mut sub_cursor = node.walk();
-                for child in node.children(&mut sub_cursor) {
-                    if child.kind() == "variable_declarator"
-                        && let Some(n) = child.child_by_field_name("name")
-                        && get_node_text(n, rope) == name
-                    {
-                        return Some(get_node_text(t, rope));
-                    }
+        if (node.kind() == "local_variable_declaration" || node.kind() == "field_declaration")
+            && let Some(t) = node.child_by_field_name("type")
+        {
+            let mut sub_cursor = node.walk();
+            for child in node.children(&mut sub_cursor) {
+                if child.kind() == "variable_declarator"
+                    && let Some(n) = child.child_by_field_name("name")
+                    && get_node_text(n, rope) == name
+                {
+                    return Some(get_node_text(t, rope));
                 }
             }
         }
 
+        if node.kind() == "formal_parameter"
+            && let Some(t) = node.child_by_field_name("type")
+            && let Some(n) = node.child_by_field_name("name")
+            && get_node_text(n, rope) == name
+        {
+            return Some(get_node_text(t, rope));
+        }
+
         let mut child_cursor = node.walk();
         for child in node.children(&mut child_cursor) {
             stack.push(child);
@@ -827,15 +2023,15 @@ fn find_type_by_text_scan(rope: &Rope, name: &str) -> Option<String> {
 fn resolve_qualifier(node: Node, rope: &Rope) -> Option<String> {
     // Handles both field_access (System.out) and method_invocation (obj.method())
     if let Some(parent) = node.parent() {
-        if parent.kind() == "field_access" {
-            if let Some(object) = parent.child_by_field_name("object") {
-                return Some(get_node_text(object, rope));
-            }
+        if parent.kind() == "field_access"
+            && let Some(object) = parent.child_by_field_name("object")
+        {
+            return Some(get_node_text(object, rope));
         }
-        if parent.kind() == "method_invocation" {
-            if let Some(object) = parent.child_by_field_name("object") {
-                return Some(get_node_text(object, rope));
-            }
+        if parent.kind() == "method_invocation"
+            && let Some(object) = parent.child_by_field_name("object")
+        {
+            return Some(get_node_text(object, rope));
         }
     }
     None
@@ -847,10 +2043,10 @@ fn resolve_qualifier_fqcn(
     file_info: &state::FileInfo,
 ) -> Option<String> {
     // If qualifier looks qualified (chained access), try the first segment as the type name.
-    if qualifier.contains('.') {
-        if let Some(first) = qualifier.split('.').next() {
-            return resolve_qualifier_fqcn(first, class_candidates, file_info);
-        }
+    if qualifier.contains('.')
+        && let Some(first) = qualifier.split('.').next()
+    {
+        return resolve_qualifier_fqcn(first, class_candidates, file_info);
     }
 
     // Try imports first
@@ -891,14 +2087,16 @@ fn resolve_qualifier_type(
     file_info: &state::FileInfo,
 ) -> Option<String> {
     // Try direct class resolution first
-    if let Some(fqcn) =
-        resolve_qualifier_fqcn(qualifier, &index.classes_by_short_name(qualifier), file_info)
-    {
+    if let Some(fqcn) = resolve_qualifier_fqcn(
+        qualifier,
+        &index.classes_by_short_name(qualifier),
+        file_info,
+    ) {
         return Some(fqcn);
     }
 
-    // Try field chain resolution (e.g., System.out -> PrintStream)
-    if let Some(fqcn) = resolve_qualifier_chain(qualifier, index, file_info) {
+    // Try chain resolution (e.g., System.out -> PrintStream)
+    if let Some(fqcn) = resolve_qualifier_chain(qualifier, index, file_info, root_of(node), rope) {
         return Some(fqcn);
     }
 
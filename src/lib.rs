@@ -1,9 +1,23 @@
 pub mod ast;
 pub mod backend;
+pub mod cache;
+pub mod classfile;
+pub mod diagnostics;
 pub mod filesystem;
+pub mod hierarchy;
+pub mod implementation;
+pub mod imports;
 pub mod indexer;
 pub mod inference;
+pub mod javadoc;
 pub mod lang;
 pub mod library;
+pub mod pool;
+pub mod progress;
+pub mod project;
+pub mod references;
+pub mod rename;
 pub mod state;
+pub mod stubs;
 pub mod utils;
+pub mod workspace_symbols;
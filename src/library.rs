@@ -1,11 +1,16 @@
-use std::io::Read;
-use std::path::PathBuf;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use dashmap::DashMap;
 use tower_lsp::lsp_types::{Location, Url};
 use zip::ZipArchive;
 
+use crate::classfile::ClassFile;
+use crate::stubs::{Stub, render_stub, stub_path};
+
 pub trait SourceProvider: Send + Sync {
     fn fetch(&self, entry_path: &str) -> anyhow::Result<String>;
 }
@@ -32,20 +37,331 @@ impl SourceProvider for ZipSourceProvider {
     }
 }
 
-/// Keeps track of source providers keyed by URI scheme so we can
-/// materialize virtual URIs (e.g. jrsls-std:///) into temp files
-/// and hand them back to editors. Future providers (e.g. jar+decompiler)
-/// can be registered without changing LSP flow.
+/// Where a JDK without `src.zip` keeps its class files.
+#[derive(Debug, Clone)]
+pub enum JdkClassArchives {
+    /// `$JAVA_HOME/jmods`, one `<module>.jmod` per module (Java 9+).
+    Jmods(PathBuf),
+    /// `rt.jar` (Java 8 and older).
+    RtJar(PathBuf),
+}
+
+impl JdkClassArchives {
+    pub fn locate(java_home: &Path) -> Option<Self> {
+        let jmods = java_home.join("jmods");
+        if jmods.is_dir() {
+            return Some(Self::Jmods(jmods));
+        }
+        [
+            java_home.join("jre").join("lib").join("rt.jar"),
+            java_home.join("lib").join("rt.jar"),
+        ]
+        .into_iter()
+        .find(|p| p.is_file())
+        .map(Self::RtJar)
+    }
+
+    /// Archives to index, each with the stub path prefix its entries get
+    /// (the module name for jmods, mirroring the layout of `src.zip`).
+    pub fn archives(&self) -> Vec<(PathBuf, String)> {
+        match self {
+            Self::Jmods(dir) => {
+                let mut jmods: Vec<_> = std::fs::read_dir(dir)
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().is_some_and(|ext| ext == "jmod"))
+                    .filter_map(|p| {
+                        let module = p.file_stem()?.to_str()?.to_string();
+                        Some((p, format!("{}/", module)))
+                    })
+                    .collect();
+                jmods.sort();
+                jmods
+            }
+            Self::RtJar(jar) => vec![(jar.clone(), String::new())],
+        }
+    }
+
+    fn class_root(&self) -> &'static str {
+        match self {
+            Self::Jmods(_) => JMOD_CLASS_ROOT,
+            Self::RtJar(_) => "",
+        }
+    }
+}
+
+/// Class files inside a `.jmod` live under this directory.
+const JMOD_CLASS_ROOT: &str = "classes/";
+
+/// Renders stubs for every accessible top-level class in a jar or jmod and
+/// hands them to `f` with their relative `.java` path.
+///
+/// A jmod is a zip with a 4-byte header, which the zip reader skips like
+/// any other prepended data.
+pub fn for_each_class_stub(
+    archive_path: &Path,
+    mut f: impl FnMut(String, Stub),
+) -> anyhow::Result<()> {
+    let file = std::fs::File::open(archive_path)?;
+    let mut archive = ZipArchive::new(file)?;
+    let class_root = if archive_path.extension().is_some_and(|ext| ext == "jmod") {
+        JMOD_CLASS_ROOT
+    } else {
+        ""
+    };
+
+    let mut classes = HashMap::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let Some(name) = entry
+            .name()
+            .strip_prefix(class_root)
+            .and_then(|n| n.strip_suffix(".class"))
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut bytes)?;
+        match ClassFile::parse(&bytes) {
+            Ok(class) => {
+                classes.insert(name, class);
+            }
+            Err(err) => tracing::debug!("Skipping class {}: {}", name, err),
+        }
+    }
+
+    let lookup = |internal: &str| classes.get(internal).cloned();
+    for (name, class) in &classes {
+        let Some(path) = stub_path(&format!("{}.class", name)) else {
+            continue;
+        };
+        if let Some(stub) = render_stub(class, &lookup) {
+            f(path, stub);
+        }
+    }
+    Ok(())
+}
+
+fn read_class<R: Read + Seek>(archive: &mut ZipArchive<R>, entry: &str) -> Option<ClassFile> {
+    let mut file = archive.by_name(entry).ok()?;
+    let mut bytes = Vec::with_capacity(file.size() as usize);
+    file.read_to_end(&mut bytes).ok()?;
+    ClassFile::parse(&bytes).ok()
+}
+
+/// Serves JDK stubs rendered from class files, for URIs produced while
+/// indexing `JdkClassArchives`.
+pub struct JdkStubProvider {
+    archives: JdkClassArchives,
+}
+
+impl JdkStubProvider {
+    pub fn new(archives: JdkClassArchives) -> Self {
+        Self { archives }
+    }
+}
+
+impl SourceProvider for JdkStubProvider {
+    fn fetch(&self, entry_path: &str) -> anyhow::Result<String> {
+        let (archive_path, class_path) = match &self.archives {
+            JdkClassArchives::Jmods(dir) => {
+                let (module, rest) = entry_path
+                    .split_once('/')
+                    .ok_or_else(|| anyhow::anyhow!("No module in {}", entry_path))?;
+                (dir.join(format!("{}.jmod", module)), rest)
+            }
+            JdkClassArchives::RtJar(jar) => (jar.clone(), entry_path),
+        };
+        render_archive_stub(&archive_path, self.archives.class_root(), class_path)
+    }
+}
+
+/// Renders the stub at `stub_path` (e.g. `java/util/List.java`) from the
+/// class files under `class_root` in a jar or jmod.
+fn render_archive_stub(
+    archive_path: &Path,
+    class_root: &str,
+    stub_path: &str,
+) -> anyhow::Result<String> {
+    let internal = stub_path
+        .strip_suffix(".java")
+        .ok_or_else(|| anyhow::anyhow!("Not a stub path: {}", stub_path))?;
+
+    let archive = RefCell::new(ZipArchive::new(std::fs::File::open(archive_path)?)?);
+    let class = read_class(
+        &mut archive.borrow_mut(),
+        &format!("{}{}.class", class_root, internal),
+    )
+    .ok_or_else(|| anyhow::anyhow!("No class file for {}", stub_path))?;
+    let lookup = |name: &str| {
+        read_class(
+            &mut archive.borrow_mut(),
+            &format!("{}{}.class", class_root, name),
+        )
+    };
+    render_stub(&class, &lookup)
+        .map(|stub| stub.source)
+        .ok_or_else(|| anyhow::anyhow!("{} is not an accessible class", stub_path))
+}
+
+/// URI scheme for classes of classpath jars, served from an attached
+/// sources jar or rendered as stubs.
+pub const JAR_SCHEME: &str = "jrsls-jar";
+
+/// `jrsls-jar:///<jar path>!/<source path>` for a class in `jar`.
+pub fn jar_entry_uri(jar: &Path, source_path: &str) -> Option<Url> {
+    let jar_url = Url::from_file_path(jar).ok()?;
+    Url::parse(&format!(
+        "{}://{}!/{}",
+        JAR_SCHEME,
+        jar_url.path(),
+        source_path
+    ))
+    .ok()
+}
+
+/// Splits the path of a `jrsls-jar` URI into the jar and the entry in it.
+fn split_jar_entry(uri_path: &str) -> Option<(PathBuf, &str)> {
+    let (jar, entry) = uri_path.split_once("!/")?;
+    // The jar part is percent-encoded like a file URL path.
+    let jar = Url::parse(&format!("file:///{}", jar.trim_start_matches('/')))
+        .ok()?
+        .to_file_path()
+        .ok()?;
+    Some((jar, entry))
+}
+
+/// The `-sources.jar` Maven keeps next to a binary jar, or Gradle's cache
+/// keeps in a sibling `<sha1>` directory, if any.
+pub fn attached_sources(jar: &Path) -> Option<PathBuf> {
+    let stem = jar.file_stem()?.to_str()?;
+    let name = format!("{}-sources.jar", stem);
+    let sources = jar.with_file_name(&name);
+    if sources.is_file() {
+        return Some(sources);
+    }
+    let hash_dir = jar.parent()?;
+    let is_hash = hash_dir
+        .file_name()?
+        .to_str()
+        .is_some_and(|n| n.len() == 40 && n.bytes().all(|b| b.is_ascii_hexdigit()));
+    if !is_hash {
+        return None;
+    }
+    std::fs::read_dir(hash_dir.parent()?)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path().join(&name))
+        .find(|sources| sources.is_file())
+}
+
+/// Number of top-level `.java` or `.class` files in a zip, i.e. roughly
+/// how many entries indexing it produces.
+pub fn count_entries(zip_path: &Path, extension: &str) -> anyhow::Result<usize> {
+    let archive = ZipArchive::new(std::fs::File::open(zip_path)?)?;
+    let suffix = format!(".{}", extension);
+    Ok(archive
+        .file_names()
+        .filter(|name| name.ends_with(&suffix) && !name.contains('$'))
+        .count())
+}
+
+/// Calls `f` with the path and contents of every `.java` file in a zip.
+pub fn for_each_source_file(
+    zip_path: &Path,
+    mut f: impl FnMut(&str, String),
+) -> anyhow::Result<()> {
+    let mut archive = ZipArchive::new(std::fs::File::open(zip_path)?)?;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if entry.is_dir() || !entry.name().ends_with(".java") {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents)?;
+        f(&name, contents);
+    }
+    Ok(())
+}
+
+/// A file indexed for a classpath jar.
+pub enum LibraryFile {
+    /// Source from the attached sources jar.
+    Source(String),
+    /// A stub for a class the sources jar lacks.
+    Stub(Stub),
+}
+
+/// Calls `f` for every `.java` file of the `sources` attached to `jar`, then
+/// for stubs of the classes of `jar` they leave out, such as generated or
+/// shaded ones. Paths are relative to the archive root.
+pub fn for_each_attached_source(
+    jar: &Path,
+    sources: &Path,
+    mut f: impl FnMut(&str, LibraryFile),
+) -> anyhow::Result<()> {
+    let mut covered = HashSet::new();
+    for_each_source_file(sources, |name, text| {
+        covered.insert(name.to_string());
+        f(name, LibraryFile::Source(text));
+    })?;
+    for_each_class_stub(jar, |path, stub| {
+        if !covered.contains(&path) {
+            f(&path, LibraryFile::Stub(stub));
+        }
+    })
+}
+
+/// Serves stubs rendered from the class files of a jar without sources.
+pub struct JarStubProvider {
+    jar: PathBuf,
+}
+
+impl JarStubProvider {
+    pub fn new(jar: PathBuf) -> Self {
+        Self { jar }
+    }
+}
+
+impl SourceProvider for JarStubProvider {
+    fn fetch(&self, entry_path: &str) -> anyhow::Result<String> {
+        render_archive_stub(&self.jar, "", entry_path)
+    }
+}
+
+/// Serves a jar's attached sources, and stubs for the classes they lack.
+struct AttachedSourceProvider {
+    sources: ZipSourceProvider,
+    stubs: JarStubProvider,
+}
+
+impl SourceProvider for AttachedSourceProvider {
+    fn fetch(&self, entry_path: &str) -> anyhow::Result<String> {
+        self.sources
+            .fetch(entry_path)
+            .or_else(|_| self.stubs.fetch(entry_path))
+    }
+}
+
+/// Keeps track of source providers so we can materialize virtual URIs into
+/// temp files and hand them back to editors.
+///
+/// Whole schemes such as `jrsls-std:///` map to one provider. `jrsls-jar`
+/// URIs name their jar, so each jar gets its own provider: its sources jar
+/// when one is attached, class-file stubs otherwise.
 #[derive(Default)]
 pub struct SourceArchiveRegistry {
     providers: DashMap<String, Arc<dyn SourceProvider>>,
+    jars: DashMap<PathBuf, Arc<dyn SourceProvider>>,
 }
 
 impl SourceArchiveRegistry {
     pub fn new() -> Self {
-        Self {
-            providers: DashMap::new(),
-        }
+        Self::default()
     }
 
     pub fn register_zip(&self, scheme: &str, zip_path: PathBuf) {
@@ -55,23 +371,45 @@ impl SourceArchiveRegistry {
         );
     }
 
+    pub fn register(&self, scheme: &str, provider: Arc<dyn SourceProvider>) {
+        self.providers.insert(scheme.to_string(), provider);
+    }
+
+    /// Registers a classpath jar, backed by `sources` when given and by
+    /// class-file stubs for anything they do not cover.
+    pub fn register_jar(&self, jar: PathBuf, sources: Option<PathBuf>) {
+        let stubs = JarStubProvider::new(jar.clone());
+        let provider: Arc<dyn SourceProvider> = match sources {
+            Some(sources) => Arc::new(AttachedSourceProvider {
+                sources: ZipSourceProvider::new(sources),
+                stubs,
+            }),
+            None => Arc::new(stubs),
+        };
+        self.jars.insert(jar, provider);
+    }
+
     pub fn materialize(&self, location: &Location) -> Option<Location> {
         let scheme = location.uri.scheme();
-        let provider = self.providers.get(scheme)?;
-
         // Strip leading slash to avoid absolute path duplication in the temp dir
         let entry_path = location.uri.path().trim_start_matches('/');
-        let contents = provider.fetch(entry_path).ok()?;
+
+        let contents = if scheme == JAR_SCHEME {
+            let (jar, entry) = split_jar_entry(entry_path)?;
+            self.jars.get(&jar)?.fetch(entry).ok()?
+        } else {
+            self.providers.get(scheme)?.fetch(entry_path).ok()?
+        };
 
         let target_path = std::env::temp_dir()
             .join("jrsls")
             .join(scheme)
             .join(entry_path);
 
-        if let Some(parent) = target_path.parent() {
-            if std::fs::create_dir_all(parent).is_err() {
-                return None;
-            }
+        if let Some(parent) = target_path.parent()
+            && std::fs::create_dir_all(parent).is_err()
+        {
+            return None;
         }
         if std::fs::write(&target_path, contents).is_err() {
             return None;
@@ -1,5 +1,7 @@
 use clap::{Parser, ValueEnum};
 use jrsls::backend::LspBackend;
+use jrsls::cache::IndexCache;
+use jrsls::imports::ImportLayout;
 use tower_lsp::{LspService, Server};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
@@ -31,6 +33,29 @@ struct Cli {
     /// Comma-separated keyword list override
     #[arg(long)]
     java_keywords: Option<String>,
+
+    /// Import group order for organize imports: package prefixes, `*` for
+    /// third-party, `project` and `static`
+    #[arg(long, default_value = "java,javax,*,project,static")]
+    import_order: ImportLayout,
+
+    /// Library jars to index, separated like the platform's PATH
+    #[arg(long)]
+    classpath: Option<std::ffi::OsString>,
+
+    /// Glob of workspace paths to skip when indexing, relative to the root;
+    /// may be repeated
+    #[arg(long)]
+    exclude: Vec<String>,
+
+    /// Directory for the persistent index cache (defaults to
+    /// `$XDG_CACHE_HOME/jrsls` or `~/.cache/jrsls`)
+    #[arg(long)]
+    cache_dir: Option<std::path::PathBuf>,
+
+    /// Re-index everything on startup instead of using the index cache
+    #[arg(long)]
+    no_cache: bool,
 }
 
 #[tokio::main]
@@ -41,7 +66,21 @@ async fn main() -> anyhow::Result<()> {
     setup_java_home(&cli);
     let keywords = collect_keywords(&cli);
 
-    let config = jrsls::backend::ServerConfig { keywords };
+    let config = jrsls::backend::ServerConfig {
+        keywords,
+        import_layout: cli.import_order.clone(),
+        classpath: cli
+            .classpath
+            .as_deref()
+            .map(|cp| std::env::split_paths(cp).collect())
+            .unwrap_or_default(),
+        exclude: cli.exclude.clone(),
+        cache_dir: if cli.no_cache {
+            None
+        } else {
+            cli.cache_dir.clone().or_else(IndexCache::default_dir)
+        },
+    };
     let (service, socket) = LspService::new(move |client| LspBackend::new(client, config.clone()));
 
     match cli.mode {
@@ -0,0 +1,165 @@
+//! Parallel bulk indexing. A fixed set of worker threads, each owning its
+//! own tree-sitter parser, turns jobs into index entries and hands them to
+//! `GlobalIndex` in batches, so the index locks are taken once per batch
+//! rather than once per file.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, sync_channel};
+
+use ropey::Rope;
+
+use crate::indexer::Indexer;
+use crate::state::{FileEntry, GlobalIndex};
+
+/// Entries a worker collects before inserting them.
+const BATCH_SIZE: usize = 256;
+
+/// One unit of indexing work.
+pub enum Job {
+    /// A workspace file, read and parsed by the worker.
+    File { uri: String, path: PathBuf },
+    /// Source text already in memory, e.g. from an archive.
+    Source { uri: String, text: String },
+    /// An entry built by the producer itself, e.g. from a class file stub.
+    Entry(FileEntry),
+}
+
+impl Job {
+    pub fn uri(&self) -> &str {
+        match self {
+            Self::File { uri, .. } | Self::Source { uri, .. } => uri,
+            Self::Entry(entry) => &entry.uri,
+        }
+    }
+}
+
+/// Jobs submitted and jobs finished so far, for progress reporting.
+/// Producers add to `total` as they learn how much work there is.
+#[derive(Debug, Default)]
+pub struct JobCounter {
+    pub total: AtomicUsize,
+    pub done: AtomicUsize,
+}
+
+impl JobCounter {
+    pub fn add_total(&self, jobs: usize) {
+        self.total.fetch_add(jobs, Ordering::Relaxed);
+    }
+
+    /// `(done, total)`; `done` never exceeds `total`.
+    pub fn snapshot(&self) -> (usize, usize) {
+        let done = self.done.load(Ordering::Relaxed);
+        let total = self.total.load(Ordering::Relaxed);
+        (done.min(total), total)
+    }
+}
+
+pub fn worker_count() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+pub fn java_parser() -> anyhow::Result<tree_sitter::Parser> {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&tree_sitter_java::LANGUAGE.into())
+        .map_err(|e| anyhow::anyhow!("Failed to load Java grammar: {}", e))?;
+    Ok(parser)
+}
+
+/// Runs `produce` on the calling thread while `worker_count()` workers index
+/// the jobs it submits. Blocks until every job is indexed, and returns what
+/// `produce` returned along with the URIs that made it into the index.
+/// Every finished job, indexed or not, counts towards `counter.done`.
+pub fn index_parallel<R>(
+    index: &GlobalIndex,
+    counter: &JobCounter,
+    produce: impl FnOnce(&mut dyn FnMut(Job)) -> R,
+) -> (R, Vec<String>) {
+    let workers = worker_count();
+    let (sender, receiver) = sync_channel::<Job>(workers * 4);
+    let receiver = Mutex::new(receiver);
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| scope.spawn(|| work(index, counter, &receiver)))
+            .collect();
+
+        let result = produce(&mut |job| {
+            // Only fails once every worker is gone, i.e. they panicked.
+            let _ = sender.send(job);
+        });
+        drop(sender);
+
+        let mut uris = Vec::new();
+        for handle in handles {
+            match handle.join() {
+                Ok(indexed) => uris.extend(indexed),
+                Err(_) => tracing::error!("Indexing worker panicked"),
+            }
+        }
+        (result, uris)
+    })
+}
+
+fn work(index: &GlobalIndex, counter: &JobCounter, receiver: &Mutex<Receiver<Job>>) -> Vec<String> {
+    let mut parser = match java_parser() {
+        Ok(parser) => parser,
+        Err(err) => {
+            tracing::error!("{}", err);
+            return Vec::new();
+        }
+    };
+
+    let mut uris = Vec::new();
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    loop {
+        let job = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => break,
+        };
+        let Ok(job) = job else {
+            break;
+        };
+        let entry = match job {
+            Job::File { uri, path } => match std::fs::read_to_string(&path) {
+                Ok(text) => parse_entry(&mut parser, &uri, &text),
+                Err(err) => {
+                    tracing::warn!("Indexing failed for {:?}: {}", path, err);
+                    None
+                }
+            },
+            Job::Source { uri, text } => parse_entry(&mut parser, &uri, &text),
+            Job::Entry(entry) => Some(entry),
+        };
+        counter.done.fetch_add(1, Ordering::Relaxed);
+        if let Some(entry) = entry {
+            uris.push(entry.uri.clone());
+            batch.push(entry);
+        }
+        if batch.len() >= BATCH_SIZE {
+            index.insert_entries(std::mem::take(&mut batch));
+        }
+    }
+    index.insert_entries(batch);
+    uris
+}
+
+fn parse_entry(parser: &mut tree_sitter::Parser, uri: &str, text: &str) -> Option<FileEntry> {
+    let rope = Rope::from_str(text);
+    let tree = parser.parse_with_options(
+        &mut |offset, _| rope.byte_slice(offset..).chunks().next().unwrap_or(""),
+        None,
+        None,
+    );
+    match tree {
+        Some(tree) => Indexer::file_entry(uri, &tree, &rope),
+        None => {
+            tracing::warn!("Failed to parse {}", uri);
+            None
+        }
+    }
+}
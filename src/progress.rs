@@ -0,0 +1,118 @@
+//! Work-done progress (`window/workDoneProgress/create` and `$/progress`)
+//! for long-running server work such as startup indexing.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tower_lsp::Client;
+use tower_lsp::lsp_types::notification::Progress as ProgressNotification;
+use tower_lsp::lsp_types::request::WorkDoneProgressCreate;
+use tower_lsp::lsp_types::{
+    NumberOrString, ProgressParams, ProgressParamsValue, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressCreateParams, WorkDoneProgressEnd, WorkDoneProgressReport,
+};
+
+use crate::pool::JobCounter;
+
+/// How often a running task's counter is turned into a report.
+const REPORT_INTERVAL: Duration = Duration::from_millis(200);
+
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(0);
+
+/// One progress bar in the client. Does nothing when the client does not
+/// support work-done progress or refused the token.
+pub struct Progress {
+    client: Client,
+    token: Option<NumberOrString>,
+}
+
+impl Progress {
+    /// Creates a token and sends the `begin` notification.
+    pub async fn begin(client: &Client, supported: bool, title: &str) -> Self {
+        let mut progress = Self {
+            client: client.clone(),
+            token: None,
+        };
+        if !supported {
+            return progress;
+        }
+
+        let token = NumberOrString::String(format!(
+            "jrsls/{}",
+            NEXT_TOKEN.fetch_add(1, Ordering::Relaxed)
+        ));
+        let created = client
+            .send_request::<WorkDoneProgressCreate>(WorkDoneProgressCreateParams {
+                token: token.clone(),
+            })
+            .await;
+        if let Err(err) = created {
+            tracing::debug!("Client refused progress token: {}", err);
+            return progress;
+        }
+
+        progress.token = Some(token);
+        progress
+            .send(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                title: title.to_string(),
+                cancellable: Some(false),
+                message: None,
+                percentage: Some(0),
+            }))
+            .await;
+        progress
+    }
+
+    /// Reports `done` out of `total` files.
+    pub async fn report(&self, done: usize, total: usize) {
+        let percentage = (done * 100).checked_div(total).unwrap_or(0).min(100) as u32;
+        self.send(WorkDoneProgress::Report(WorkDoneProgressReport {
+            cancellable: Some(false),
+            message: Some(format!("{}/{} files", done, total)),
+            percentage: Some(percentage),
+        }))
+        .await;
+    }
+
+    /// Reports `counter` every `REPORT_INTERVAL` until `task` finishes.
+    pub async fn track<T>(
+        &self,
+        counter: &JobCounter,
+        task: tokio::task::JoinHandle<T>,
+    ) -> Result<T, tokio::task::JoinError> {
+        tokio::pin!(task);
+        let mut ticker = tokio::time::interval(REPORT_INTERVAL);
+        let mut last = None;
+        loop {
+            tokio::select! {
+                result = &mut task => return result,
+                _ = ticker.tick() => {
+                    let snapshot = counter.snapshot();
+                    if last != Some(snapshot) {
+                        last = Some(snapshot);
+                        self.report(snapshot.0, snapshot.1).await;
+                    }
+                }
+            }
+        }
+    }
+
+    pub async fn end(self, message: impl Into<String>) {
+        self.send(WorkDoneProgress::End(WorkDoneProgressEnd {
+            message: Some(message.into()),
+        }))
+        .await;
+    }
+
+    async fn send(&self, value: WorkDoneProgress) {
+        let Some(token) = self.token.clone() else {
+            return;
+        };
+        self.client
+            .send_notification::<ProgressNotification>(ProgressParams {
+                token,
+                value: ProgressParamsValue::WorkDone(value),
+            })
+            .await;
+    }
+}
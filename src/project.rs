@@ -0,0 +1,229 @@
+//! Build tool project models. They tell the server which library jars the
+//! workspace compiles against and where its sources live; nothing is ever
+//! downloaded.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::filesystem::SourceFilter;
+
+pub mod gradle;
+pub mod maven;
+
+/// A directory whose subdirectories mirror Java packages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceRoot {
+    pub dir: PathBuf,
+    /// Test sources may use main sources, but not the other way round.
+    pub is_test: bool,
+}
+
+/// What the build at a workspace root declares.
+#[derive(Debug, Clone, Default)]
+pub struct BuildModel {
+    /// Library jars main sources compile against.
+    pub classpath: Vec<PathBuf>,
+    /// Library jars only test sources compile against, such as JUnit.
+    pub test_classpath: Vec<PathBuf>,
+    /// Directories of the build's projects or modules.
+    pub module_dirs: Vec<PathBuf>,
+    pub source_roots: Vec<SourceRoot>,
+}
+
+/// Reads the Maven or Gradle build at `root`; empty when no supported build
+/// file is present.
+pub fn load_build(root: &Path) -> BuildModel {
+    if root.join("pom.xml").is_file() {
+        let Some(repository) = maven::local_repository() else {
+            tracing::info!("No home directory; skip Maven dependency resolution");
+            return BuildModel::default();
+        };
+        match maven::MavenProject::load(root, &maven::Repository::Maven(repository)) {
+            Ok(project) => {
+                return BuildModel {
+                    classpath: project.classpath(),
+                    test_classpath: project.test_classpath(),
+                    module_dirs: project.modules.iter().map(|m| m.dir.clone()).collect(),
+                    source_roots: project
+                        .modules
+                        .iter()
+                        .flat_map(|m| {
+                            [
+                                SourceRoot {
+                                    dir: m.source_dir.clone(),
+                                    is_test: false,
+                                },
+                                SourceRoot {
+                                    dir: m.test_source_dir.clone(),
+                                    is_test: true,
+                                },
+                            ]
+                        })
+                        .collect(),
+                };
+            }
+            Err(err) => tracing::warn!("Failed to load Maven project at {:?}: {}", root, err),
+        }
+    }
+    if gradle::is_gradle_build(root) {
+        let Some(cache) = gradle::dependency_cache() else {
+            tracing::info!("No Gradle user home; skip Gradle dependency resolution");
+            return BuildModel::default();
+        };
+        match gradle::GradleProject::load(root, &cache) {
+            Ok(project) => {
+                return BuildModel {
+                    classpath: project.classpath(),
+                    test_classpath: project.test_classpath(),
+                    module_dirs: project.modules.iter().map(|m| m.dir.clone()).collect(),
+                    source_roots: project
+                        .modules
+                        .iter()
+                        .flat_map(|m| &m.source_sets)
+                        .flat_map(|set| {
+                            set.java_dirs.iter().map(|dir| SourceRoot {
+                                dir: dir.clone(),
+                                is_test: set.name != "main",
+                            })
+                        })
+                        .collect(),
+                };
+            }
+            Err(err) => tracing::warn!("Failed to load Gradle project at {:?}: {}", root, err),
+        }
+    }
+    BuildModel::default()
+}
+
+/// Java sources of a workspace and the roots they belong to.
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceSources {
+    pub roots: Vec<SourceRoot>,
+    pub files: Vec<PathBuf>,
+}
+
+/// Finds the source roots under `root` and the `.java` files in them.
+///
+/// Roots come from the build, from the `src/<set>/java` convention, and,
+/// for files outside any known project, from their `package` declaration.
+/// Other files inside a project (resources, fixtures) are left out.
+pub fn collect_sources(root: &Path, build: &BuildModel, filter: &SourceFilter) -> WorkspaceSources {
+    let mut roots: Vec<SourceRoot> = build
+        .source_roots
+        .iter()
+        .filter(|r| r.dir.is_dir())
+        .cloned()
+        .collect();
+    let mut module_dirs = build.module_dirs.clone();
+
+    let mut seen = HashSet::new();
+    let mut candidates = Vec::new();
+    for dir in roots
+        .iter()
+        .map(|r| r.dir.clone())
+        .chain([root.to_path_buf()])
+    {
+        for file in filter.collect_files_with_ext(&dir, "java") {
+            if seen.insert(file.clone()) {
+                candidates.push(file);
+            }
+        }
+    }
+
+    // The build's own modules keep exactly the roots it declares.
+    for file in &candidates {
+        if let Some((module, root)) = conventional_root(file)
+            && !build.module_dirs.contains(&module)
+            && !roots.iter().any(|r| r.dir == root.dir)
+        {
+            module_dirs.push(module);
+            roots.push(root);
+        }
+    }
+
+    let mut files = Vec::new();
+    for file in candidates {
+        if roots.iter().any(|r| file.starts_with(&r.dir)) {
+            files.push(file);
+            continue;
+        }
+        if module_dirs.iter().any(|m| file.starts_with(m)) {
+            tracing::debug!("Skipping {:?}: not in a source root", file);
+            continue;
+        }
+        let Some(derived) = package_root(&file) else {
+            continue;
+        };
+        let is_test = derived
+            .strip_prefix(root)
+            .unwrap_or(&derived)
+            .components()
+            .any(|c| matches!(c.as_os_str().to_str(), Some("test" | "tests")));
+        let derived = SourceRoot {
+            dir: derived,
+            is_test,
+        };
+        if !roots.contains(&derived) {
+            roots.push(derived);
+        }
+        files.push(file);
+    }
+
+    WorkspaceSources { roots, files }
+}
+
+/// Whether `path` belongs to the sources `collect_sources` found: it lies in
+/// one of their roots and passes `filter`.
+pub fn is_workspace_source(roots: &[SourceRoot], filter: &SourceFilter, path: &Path) -> bool {
+    roots.iter().any(|root| filter.accepts_in(&root.dir, path))
+}
+
+/// `src/<set>/java` above `file`, with the project directory holding `src`.
+/// Every set but `main` is a test set.
+fn conventional_root(file: &Path) -> Option<(PathBuf, SourceRoot)> {
+    file.ancestors().find_map(|dir| {
+        let set = dir.parent()?;
+        let src = set.parent()?;
+        let is_convention = dir.file_name()? == "java" && src.file_name()? == "src";
+        is_convention.then(|| {
+            (
+                src.parent().unwrap_or(src).to_path_buf(),
+                SourceRoot {
+                    dir: dir.to_path_buf(),
+                    is_test: set.file_name().is_some_and(|name| name != "main"),
+                },
+            )
+        })
+    })
+}
+
+/// The directory `file`'s package declaration is relative to, or its own
+/// directory when the layout does not match the package.
+fn package_root(file: &Path) -> Option<PathBuf> {
+    let dir = file.parent()?;
+    let source = std::fs::read_to_string(file).ok()?;
+    let package = source.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix("package")?;
+        rest.starts_with(char::is_whitespace)
+            .then(|| rest.split(';').next().unwrap_or(rest).trim().to_string())
+    });
+    let Some(package) = package else {
+        return Some(dir.to_path_buf());
+    };
+
+    let mut root = dir;
+    for segment in package.rsplit('.') {
+        match (root.file_name(), root.parent()) {
+            (Some(name), Some(parent)) if name == segment => root = parent,
+            _ => return Some(dir.to_path_buf()),
+        }
+    }
+    Some(root.to_path_buf())
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
@@ -0,0 +1,879 @@
+//! Gradle project model from a lightweight reading of the build scripts.
+//!
+//! Nothing is evaluated: settings and build files (Groovy or Kotlin DSL) are
+//! split into statements, and the common forms of `include`, `sourceSets`,
+//! `dependencies` and simple string variables are recognized. Declared
+//! artifacts are resolved, with the dependencies their POMs declare, from
+//! Gradle's download cache.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::maven::{self, Dependency, Repository};
+
+const SETTINGS_FILES: [&str; 2] = ["settings.gradle", "settings.gradle.kts"];
+const BUILD_FILES: [&str; 2] = ["build.gradle", "build.gradle.kts"];
+
+/// Whether `root` holds a Gradle build.
+pub fn is_gradle_build(root: &Path) -> bool {
+    SETTINGS_FILES
+        .iter()
+        .chain(&BUILD_FILES)
+        .any(|file| root.join(file).is_file())
+}
+
+/// Gradle's artifact cache: `$GRADLE_USER_HOME/caches/modules-2/files-2.1`,
+/// with `~/.gradle` as the default user home.
+pub fn dependency_cache() -> Option<PathBuf> {
+    let user_home = std::env::var_os("GRADLE_USER_HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| super::home_dir().map(|home| home.join(".gradle")))?;
+    Some(user_home.join("caches").join("modules-2").join("files-2.1"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSet {
+    pub name: String,
+    pub java_dirs: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+pub enum DependencyTarget {
+    /// An external artifact.
+    Module(Dependency),
+    /// Another project of the build, by path such as `:core`.
+    Project(String),
+    /// Local jars from `files(...)` or `fileTree(...)`.
+    Files(Vec<PathBuf>),
+}
+
+#[derive(Debug, Clone)]
+pub struct DeclaredDependency {
+    /// Configuration it was declared in, e.g. `implementation`.
+    pub configuration: String,
+    pub target: DependencyTarget,
+}
+
+#[derive(Debug, Clone)]
+pub struct GradleModule {
+    /// Project path: `:` for the root, `:app:core` for nested projects.
+    pub path: String,
+    pub dir: PathBuf,
+    pub source_sets: Vec<SourceSet>,
+    pub dependencies: Vec<DeclaredDependency>,
+    /// Jars this project's main sources compile against, including those
+    /// exported by the projects it depends on.
+    pub classpath: Vec<PathBuf>,
+    /// Jars only its test source sets compile against.
+    pub test_classpath: Vec<PathBuf>,
+}
+
+/// A Gradle build: the root project and every included subproject.
+#[derive(Debug, Clone)]
+pub struct GradleProject {
+    /// The root project first, then subprojects in `include` order.
+    pub modules: Vec<GradleModule>,
+}
+
+impl GradleProject {
+    /// Reads the build at `root` and resolves its dependencies from `cache`
+    /// (see `dependency_cache`).
+    pub fn load(root: &Path, cache: &Path) -> anyhow::Result<Self> {
+        anyhow::ensure!(is_gradle_build(root), "no Gradle build in {:?}", root);
+
+        let mut modules = vec![GradleModule {
+            path: ":".to_string(),
+            dir: root.to_path_buf(),
+            source_sets: Vec::new(),
+            dependencies: Vec::new(),
+            classpath: Vec::new(),
+            test_classpath: Vec::new(),
+        }];
+        let settings = read_script(root, &SETTINGS_FILES);
+        for (path, dir) in included_projects(&settings, root) {
+            if !modules.iter().any(|m| m.path == path) {
+                modules.push(GradleModule {
+                    path,
+                    dir,
+                    source_sets: Vec::new(),
+                    dependencies: Vec::new(),
+                    classpath: Vec::new(),
+                    test_classpath: Vec::new(),
+                });
+            }
+        }
+
+        let catalog = VersionCatalog::load(&root.join("gradle").join("libs.versions.toml"));
+        let root_script = read_script(root, &BUILD_FILES);
+        let mut root_variables = read_properties(&root.join("gradle.properties"));
+        collect_variables(&root_script, &mut root_variables);
+
+        let all_paths: Vec<String> = modules.iter().map(|m| m.path.clone()).collect();
+        let mut declared: HashMap<String, Vec<DeclaredDependency>> = HashMap::new();
+        for module in &mut modules {
+            let script = if module.path == ":" {
+                root_script.clone()
+            } else {
+                read_script(&module.dir, &BUILD_FILES)
+            };
+            let mut variables = root_variables.clone();
+            variables.extend(read_properties(&module.dir.join("gradle.properties")));
+            collect_variables(&script, &mut variables);
+            let context = ScriptContext {
+                dir: &module.dir,
+                variables: &variables,
+                catalog: &catalog,
+            };
+
+            module.source_sets = source_sets(&script, &module.dir);
+            for statement in &script {
+                let Some(targets) = statement.dependency_targets(&module.path, &all_paths) else {
+                    continue;
+                };
+                for dependency in context.parse_dependency(&statement.text) {
+                    for target in &targets {
+                        declared
+                            .entry(target.clone())
+                            .or_default()
+                            .push(dependency.clone());
+                    }
+                }
+            }
+        }
+        for module in &mut modules {
+            module.dependencies = declared.remove(&module.path).unwrap_or_default();
+        }
+
+        let repository = Repository::GradleCache(cache.to_path_buf());
+        let classpaths: Vec<_> = (0..modules.len())
+            .map(|i| {
+                let (dependencies, files) = classpath_roots(&modules, i);
+                let resolved = maven::resolve_dependencies(&repository, &dependencies);
+                maven::split_classpath(
+                    files
+                        .iter()
+                        .map(|(jar, scope)| (jar.clone(), scope.as_str()))
+                        .chain(resolved.iter().map(|r| (r.jar.clone(), r.scope.as_str()))),
+                )
+            })
+            .collect();
+        for (module, (classpath, test_classpath)) in modules.iter_mut().zip(classpaths) {
+            module.classpath = classpath;
+            module.test_classpath = test_classpath;
+        }
+        Ok(Self { modules })
+    }
+
+    /// Every jar the main sources of any project compile against.
+    pub fn classpath(&self) -> Vec<PathBuf> {
+        self.split_classpath().0
+    }
+
+    /// Jars only test sources compile against, in every project.
+    pub fn test_classpath(&self) -> Vec<PathBuf> {
+        self.split_classpath().1
+    }
+
+    fn split_classpath(&self) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let main = self.modules.iter().flat_map(|m| &m.classpath);
+        let test = self.modules.iter().flat_map(|m| &m.test_classpath);
+        maven::split_classpath(
+            main.map(|jar| (jar.clone(), "compile"))
+                .chain(test.map(|jar| (jar.clone(), "test"))),
+        )
+    }
+}
+
+/// Artifacts and local jars, with their scope, visible to `modules[index]`:
+/// its own, plus what the projects it depends on export through `api`-like
+/// configurations.
+fn classpath_roots(
+    modules: &[GradleModule],
+    index: usize,
+) -> (Vec<Dependency>, Vec<(PathBuf, String)>) {
+    let mut dependencies = Vec::new();
+    let mut jars = Vec::new();
+    let mut visited = HashSet::new();
+    // Scope is `None` for the module itself; dependents see the scope of
+    // the project dependency instead.
+    let mut stack: Vec<(usize, Option<String>)> = vec![(index, None)];
+    while let Some((i, inherited)) = stack.pop() {
+        if !visited.insert(i) {
+            continue;
+        }
+        for declared in &modules[i].dependencies {
+            if inherited.is_some() && !is_exported(&declared.configuration) {
+                continue;
+            }
+            let Some(scope) = inherited
+                .clone()
+                .or_else(|| configuration_scope(&declared.configuration))
+            else {
+                continue;
+            };
+            match &declared.target {
+                DependencyTarget::Module(dependency) => {
+                    let mut dependency = dependency.clone();
+                    dependency.scope = Some(scope);
+                    dependencies.push(dependency);
+                }
+                DependencyTarget::Files(files) => {
+                    jars.extend(files.iter().map(|file| (file.clone(), scope.clone())))
+                }
+                DependencyTarget::Project(path) => {
+                    if let Some(j) = modules.iter().position(|m| m.path == *path) {
+                        stack.push((j, Some(scope)));
+                    }
+                }
+            }
+        }
+    }
+    (dependencies, jars)
+}
+
+/// Maven scope matching a dependency configuration, or `None` for
+/// configurations that are not part of compilation (annotation processors,
+/// plugins and the like).
+fn configuration_scope(configuration: &str) -> Option<String> {
+    let scope = match configuration {
+        "api" | "implementation" | "compile" | "compileOnlyApi" => "compile",
+        "compileOnly" => "provided",
+        "runtimeOnly" | "runtime" | "testRuntime" => "runtime",
+        "testCompile" => "test",
+        // Any other source set, such as `test` or `integrationTest`.
+        other if other.len() > "RuntimeOnly".len() && other.ends_with("RuntimeOnly") => "runtime",
+        other
+            if ["Implementation", "CompileOnly", "Api"]
+                .iter()
+                .any(|suffix| other.len() > suffix.len() && other.ends_with(suffix)) =>
+        {
+            "test"
+        }
+        _ => return None,
+    };
+    Some(scope.to_string())
+}
+
+/// Configurations whose dependencies dependent projects compile against.
+fn is_exported(configuration: &str) -> bool {
+    matches!(configuration, "api" | "compile" | "compileOnlyApi")
+}
+
+/// A statement of a build script, with the names of the blocks around it.
+#[derive(Debug, Clone)]
+struct Statement {
+    /// Enclosing blocks, outermost first; dotted names are split, so
+    /// `sourceSets.main {` contributes `sourceSets` and `main`.
+    blocks: Vec<String>,
+    text: String,
+}
+
+impl Statement {
+    /// Projects a statement inside a `dependencies` block applies to, or
+    /// `None` for anything else.
+    fn dependency_targets(&self, own: &str, all: &[String]) -> Option<Vec<String>> {
+        if self.blocks.last().map(String::as_str) != Some("dependencies")
+            || self.blocks.iter().any(|b| b == "buildscript")
+        {
+            return None;
+        }
+        if self.blocks.iter().any(|b| b == "allprojects") {
+            return Some(all.to_vec());
+        }
+        if self.blocks.iter().any(|b| b == "subprojects") {
+            return Some(all.iter().filter(|p| *p != own).cloned().collect());
+        }
+        if let Some(path) = self
+            .blocks
+            .iter()
+            .find_map(|b| b.strip_prefix("project(")?.strip_suffix(')'))
+        {
+            return Some(vec![path.to_string()]);
+        }
+        Some(vec![own.to_string()])
+    }
+}
+
+fn read_script(dir: &Path, candidates: &[&str]) -> Vec<Statement> {
+    candidates
+        .iter()
+        .find_map(|file| std::fs::read_to_string(dir.join(file)).ok())
+        .map(|source| statements(&source))
+        .unwrap_or_default()
+}
+
+/// Splits a script into statements at line ends and semicolons outside
+/// brackets, dropping comments and tracking `name { ... }` blocks.
+fn statements(source: &str) -> Vec<Statement> {
+    let mut out = Vec::new();
+    let mut blocks: Vec<Vec<String>> = Vec::new();
+    let mut current = String::new();
+    let mut brackets = 0usize;
+
+    let flush = |current: &mut String, blocks: &[Vec<String>], out: &mut Vec<Statement>| {
+        let text = current.trim();
+        if !text.is_empty() {
+            out.push(Statement {
+                blocks: blocks.iter().flatten().cloned().collect(),
+                text: text.to_string(),
+            });
+        }
+        current.clear();
+    };
+
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = '\0';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '\'' | '"' => {
+                current.push(c);
+                while let Some(next) = chars.next() {
+                    current.push(next);
+                    if next == '\\' {
+                        if let Some(escaped) = chars.next() {
+                            current.push(escaped);
+                        }
+                    } else if next == c {
+                        break;
+                    }
+                }
+            }
+            '(' | '[' => {
+                brackets += 1;
+                current.push(c);
+            }
+            ')' | ']' => {
+                brackets = brackets.saturating_sub(1);
+                current.push(c);
+            }
+            '{' => {
+                let name = block_name(&current);
+                flush(&mut current, &blocks, &mut out);
+                blocks.push(name);
+                brackets = 0;
+            }
+            '}' => {
+                flush(&mut current, &blocks, &mut out);
+                blocks.pop();
+            }
+            '\n' | ';' if brackets == 0 => flush(&mut current, &blocks, &mut out),
+            _ => current.push(c),
+        }
+    }
+    flush(&mut current, &blocks, &mut out);
+    out
+}
+
+/// Names of the block a `{` opens, from the text before it: `main` for
+/// `main`, `named("main")`, `getByName("main")` or `val main by getting`,
+/// `project(:core)` for `project(':core')`, and the called function for
+/// anything else.
+fn block_name(header: &str) -> Vec<String> {
+    let header = header.trim();
+    if let Some(rest) = header.strip_prefix("val ") {
+        return rest
+            .split_whitespace()
+            .next()
+            .map(|name| vec![name.to_string()])
+            .unwrap_or_default();
+    }
+    let Some(open) = header.find('(') else {
+        return header
+            .split_whitespace()
+            .last()
+            .map(|name| name.split('.').map(str::to_string).collect())
+            .unwrap_or_default();
+    };
+    let function = header[..open].trim();
+    let function = function.rsplit('.').next().unwrap_or(function);
+    let argument = quoted_strings(&header[open..]).into_iter().next();
+    let name = match (function, argument) {
+        ("named" | "getByName" | "create" | "register" | "maybeCreate", Some(name)) => name,
+        ("project", Some(path)) => format!("project({})", project_path(&path)),
+        _ => function.to_string(),
+    };
+    vec![name]
+}
+
+/// Contents of every quoted string in `text`, in order.
+fn quoted_strings(text: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\'' && c != '"' {
+            continue;
+        }
+        let mut value = String::new();
+        while let Some(next) = chars.next() {
+            match next {
+                '\\' => value.extend(chars.next()),
+                _ if next == c => break,
+                _ => value.push(next),
+            }
+        }
+        strings.push(value);
+    }
+    strings
+}
+
+/// String value of a named argument, `name: 'x'` (Groovy) or `name = "x"`
+/// (Kotlin).
+fn named_argument(text: &str, name: &str) -> Option<String> {
+    let mut search = 0;
+    while let Some(found) = text[search..].find(name) {
+        let start = search + found;
+        let end = start + name.len();
+        search = end;
+        let preceded = text[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.');
+        let rest = text[end..].trim_start();
+        let Some(rest) = rest.strip_prefix(':').or_else(|| rest.strip_prefix('=')) else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        if preceded || !(rest.starts_with('\'') || rest.starts_with('"')) {
+            continue;
+        }
+        return quoted_strings(rest).into_iter().next();
+    }
+    None
+}
+
+/// `:a:b` for `a:b`, `:a:b` or `a:b/`.
+fn project_path(path: &str) -> String {
+    let path = path.trim().trim_end_matches('/');
+    if path.starts_with(':') {
+        path.to_string()
+    } else {
+        format!(":{}", path)
+    }
+}
+
+/// Subprojects declared in the settings script, with their directories.
+fn included_projects(settings: &[Statement], root: &Path) -> Vec<(String, PathBuf)> {
+    let mut projects: Vec<(String, PathBuf)> = Vec::new();
+    let mut dirs: HashMap<String, PathBuf> = HashMap::new();
+    for statement in settings {
+        let text = statement.text.as_str();
+        if text.starts_with("include") && !text.starts_with("includeBuild") {
+            for path in quoted_strings(text) {
+                let path = project_path(&path);
+                let dir = path
+                    .trim_start_matches(':')
+                    .split(':')
+                    .fold(root.to_path_buf(), |dir, segment| dir.join(segment));
+                projects.push((path, dir));
+            }
+        } else if text.starts_with("project(") && text.contains(".projectDir") {
+            let strings = quoted_strings(text);
+            if let (Some(path), Some(dir)) = (strings.first(), strings.last())
+                && strings.len() > 1
+            {
+                dirs.insert(project_path(path), root.join(dir));
+            }
+        }
+    }
+    for (path, dir) in &mut projects {
+        if let Some(custom) = dirs.remove(path) {
+            *dir = custom;
+        }
+    }
+    projects
+}
+
+fn read_properties(path: &Path) -> HashMap<String, String> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#') && !line.starts_with('!'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=').or_else(|| line.split_once(':'))?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Records simple string variables: `def x = '1'`, `val x = "1"`,
+/// `ext.x = '1'`, `x = '1'` inside `ext { }`, `extra["x"] = "1"`,
+/// `set("x", "1")` and `val x by extra("1")`.
+fn collect_variables(script: &[Statement], variables: &mut HashMap<String, String>) {
+    for statement in script {
+        let text = statement.text.as_str();
+        if let Some(args) = text.strip_prefix("set(") {
+            if let [name, value] = quoted_strings(args).as_slice() {
+                variables.insert(name.clone(), value.clone());
+            }
+            continue;
+        }
+        if let Some((lhs, rhs)) = text.split_once(" by extra(") {
+            if let (Some(name), Some(value)) = (
+                lhs.strip_prefix("val ").map(str::trim),
+                quoted_strings(rhs).first(),
+            ) {
+                variables.insert(name.to_string(), value.clone());
+            }
+            continue;
+        }
+
+        let Some((lhs, rhs)) = text.split_once('=') else {
+            continue;
+        };
+        let rhs = rhs.trim();
+        let quoted = (rhs.starts_with('\'') && rhs.ends_with('\''))
+            || (rhs.starts_with('"') && rhs.ends_with('"'));
+        if rhs.starts_with('=') || rhs.len() < 2 || !quoted {
+            continue;
+        }
+        let lhs = lhs
+            .trim()
+            .trim_start_matches("const ")
+            .trim_start_matches("def ")
+            .trim_start_matches("val ")
+            .trim_start_matches("var ")
+            .trim_start_matches("String ");
+        // Drop a Kotlin type annotation.
+        let lhs = lhs.split(':').next().unwrap_or(lhs).trim();
+        let name = match lhs.strip_prefix("extra[") {
+            Some(key) => quoted_strings(key).into_iter().next(),
+            None => lhs.rsplit('.').next().map(str::to_string),
+        };
+        if let Some(name) =
+            name.filter(|n| !n.is_empty() && n.chars().all(|c| c.is_alphanumeric() || c == '_'))
+        {
+            variables.insert(name, rhs[1..rhs.len() - 1].to_string());
+        }
+    }
+}
+
+/// Replaces `$name` and `${name}` (or `${project.name}`) with known
+/// variables; unknown references stay.
+fn interpolate(value: &str, variables: &HashMap<String, String>) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(dollar) = rest.find('$') {
+        out.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+        let (expression, consumed) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+        let name = expression.rsplit('.').next().unwrap_or(expression);
+        match variables.get(name) {
+            Some(value) if consumed > 0 => {
+                out.push_str(value);
+                rest = &after[consumed..];
+            }
+            _ => {
+                out.push('$');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Source sets with their Java directories: `main` and `test` by
+/// convention, adjusted by `srcDir`/`srcDirs` statements.
+fn source_sets(script: &[Statement], dir: &Path) -> Vec<SourceSet> {
+    let mut sets = vec![
+        SourceSet {
+            name: "main".to_string(),
+            java_dirs: vec![dir.join("src").join("main").join("java")],
+        },
+        SourceSet {
+            name: "test".to_string(),
+            java_dirs: vec![dir.join("src").join("test").join("java")],
+        },
+    ];
+
+    for statement in script {
+        let text = statement.text.as_str();
+        let head_end = text
+            .find(|c: char| c.is_whitespace() || c == '(' || c == '=' || c == '+')
+            .unwrap_or(text.len());
+        let mut path: Vec<&str> = statement.blocks.iter().map(String::as_str).collect();
+        path.extend(text[..head_end].split('.'));
+        let Some(method) = path.pop() else {
+            continue;
+        };
+        if !matches!(method, "srcDir" | "srcDirs" | "setSrcDirs") {
+            continue;
+        }
+        let Some(at) = path.iter().position(|b| *b == "sourceSets") else {
+            continue;
+        };
+        let (Some(name), true) = (path.get(at + 1), path[at + 1..].contains(&"java")) else {
+            continue;
+        };
+
+        let rest = text[head_end..].trim_start();
+        let replace = method == "setSrcDirs" || (rest.starts_with('=') && !rest.starts_with("=="));
+        let dirs: Vec<PathBuf> = quoted_strings(rest).iter().map(|d| dir.join(d)).collect();
+        let set = match sets.iter().position(|s| s.name == *name) {
+            Some(i) => &mut sets[i],
+            None => {
+                sets.push(SourceSet {
+                    name: name.to_string(),
+                    java_dirs: vec![dir.join("src").join(name).join("java")],
+                });
+                sets.last_mut().expect("just pushed")
+            }
+        };
+        if replace {
+            set.java_dirs = dirs;
+        } else {
+            for dir in dirs {
+                if !set.java_dirs.contains(&dir) {
+                    set.java_dirs.push(dir);
+                }
+            }
+        }
+    }
+    sets
+}
+
+/// Libraries and bundles from `gradle/libs.versions.toml`, by accessor
+/// (`libs.foo.bar` for alias `foo-bar`).
+#[derive(Debug, Default)]
+struct VersionCatalog {
+    libraries: HashMap<String, Dependency>,
+    bundles: HashMap<String, Vec<String>>,
+}
+
+impl VersionCatalog {
+    fn load(path: &Path) -> Self {
+        let Ok(source) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        let table: toml::Table = match source.parse() {
+            Ok(table) => table,
+            Err(err) => {
+                tracing::warn!("Failed to parse {:?}: {}", path, err);
+                return Self::default();
+            }
+        };
+        let section = |name: &str| table.get(name).and_then(toml::Value::as_table);
+        let versions = section("versions");
+        let version_of = |value: &toml::Value| -> Option<String> {
+            match value {
+                toml::Value::String(version) => Some(version.clone()),
+                toml::Value::Table(spec) => {
+                    if let Some(key) = spec.get("ref").and_then(toml::Value::as_str) {
+                        return versions?.get(key).and_then(|v| match v {
+                            toml::Value::String(version) => Some(version.clone()),
+                            toml::Value::Table(rich) => ["strictly", "require", "prefer"]
+                                .iter()
+                                .find_map(|k| rich.get(*k)?.as_str().map(str::to_string)),
+                            _ => None,
+                        });
+                    }
+                    ["strictly", "require", "prefer"]
+                        .iter()
+                        .find_map(|k| spec.get(*k)?.as_str().map(str::to_string))
+                }
+                _ => None,
+            }
+        };
+
+        let mut catalog = Self::default();
+        for (alias, library) in section("libraries").into_iter().flatten() {
+            let dependency = match library {
+                toml::Value::String(notation) => parse_notation(notation),
+                toml::Value::Table(spec) => {
+                    let (group_id, artifact_id) = match spec.get("module").and_then(|m| m.as_str())
+                    {
+                        Some(module) => match module.split_once(':') {
+                            Some((g, a)) => (g.to_string(), a.to_string()),
+                            None => continue,
+                        },
+                        None => (
+                            spec.get("group")
+                                .and_then(|g| g.as_str())
+                                .unwrap_or_default()
+                                .to_string(),
+                            spec.get("name")
+                                .and_then(|n| n.as_str())
+                                .unwrap_or_default()
+                                .to_string(),
+                        ),
+                    };
+                    Some(Dependency {
+                        group_id,
+                        artifact_id,
+                        version: spec.get("version").and_then(version_of),
+                        ..Dependency::default()
+                    })
+                }
+                _ => None,
+            };
+            if let Some(dependency) = dependency {
+                catalog.libraries.insert(accessor(alias), dependency);
+            }
+        }
+        for (alias, bundle) in section("bundles").into_iter().flatten() {
+            let members = bundle
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|m| m.as_str().map(accessor))
+                .collect();
+            catalog.bundles.insert(accessor(alias), members);
+        }
+        catalog
+    }
+}
+
+/// Gradle's accessor path for a catalog alias: `-` and `_` become `.`.
+fn accessor(alias: &str) -> String {
+    alias.replace(['-', '_'], ".")
+}
+
+/// `group:name[:version[:classifier]][@extension]`.
+fn parse_notation(notation: &str) -> Option<Dependency> {
+    let (coordinates, extension) = match notation.split_once('@') {
+        Some((coordinates, extension)) => (coordinates, Some(extension)),
+        None => (notation, None),
+    };
+    let mut parts = coordinates.split(':');
+    let group_id = parts.next()?.trim().to_string();
+    let artifact_id = parts.next()?.trim().to_string();
+    if group_id.is_empty() || artifact_id.is_empty() {
+        return None;
+    }
+    Some(Dependency {
+        group_id,
+        artifact_id,
+        version: parts
+            .next()
+            .map(|v| v.trim_end_matches("!!").to_string())
+            .filter(|v| !v.is_empty()),
+        classifier: parts.next().map(str::to_string),
+        kind: extension.filter(|e| *e != "jar").map(str::to_string),
+        ..Dependency::default()
+    })
+}
+
+struct ScriptContext<'a> {
+    dir: &'a Path,
+    variables: &'a HashMap<String, String>,
+    catalog: &'a VersionCatalog,
+}
+
+impl ScriptContext<'_> {
+    /// Dependencies declared by one statement of a `dependencies` block.
+    fn parse_dependency(&self, text: &str) -> Vec<DeclaredDependency> {
+        let configuration: String = text
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        if configuration.is_empty() || configuration_scope(&configuration).is_none() {
+            return Vec::new();
+        }
+        let mut rest = text[configuration.len()..].trim();
+        if rest.starts_with('(') && rest.ends_with(')') {
+            rest = rest[1..rest.len() - 1].trim();
+        }
+        let declared = |target| DeclaredDependency {
+            configuration: configuration.clone(),
+            target,
+        };
+
+        if rest.starts_with("platform(")
+            || rest.starts_with("enforcedPlatform(")
+            || rest.starts_with("testFixtures(")
+        {
+            return Vec::new();
+        }
+        if rest.starts_with("project(") {
+            let path =
+                named_argument(rest, "path").or_else(|| quoted_strings(rest).into_iter().next());
+            return path
+                .map(|path| vec![declared(DependencyTarget::Project(project_path(&path)))])
+                .unwrap_or_default();
+        }
+        if rest.starts_with("files(") {
+            let files = quoted_strings(rest)
+                .iter()
+                .map(|f| self.dir.join(interpolate(f, self.variables)))
+                .collect();
+            return vec![declared(DependencyTarget::Files(files))];
+        }
+        if rest.starts_with("fileTree(") {
+            let Some(tree) =
+                named_argument(rest, "dir").or_else(|| quoted_strings(rest).into_iter().next())
+            else {
+                return Vec::new();
+            };
+            let jars = crate::filesystem::collect_files_with_ext(self.dir.join(tree), "jar");
+            return vec![declared(DependencyTarget::Files(jars))];
+        }
+        if let Some(reference) = rest.strip_prefix("libs.") {
+            let reference = reference.trim_end_matches(".get()");
+            let aliases = match reference.strip_prefix("bundles.") {
+                Some(bundle) => self
+                    .catalog
+                    .bundles
+                    .get(bundle)
+                    .cloned()
+                    .unwrap_or_default(),
+                None => vec![reference.to_string()],
+            };
+            return aliases
+                .iter()
+                .filter_map(|alias| self.catalog.libraries.get(alias))
+                .map(|dependency| declared(DependencyTarget::Module(dependency.clone())))
+                .collect();
+        }
+
+        let dependency = match named_argument(rest, "name") {
+            Some(name) => Some(Dependency {
+                group_id: named_argument(rest, "group").unwrap_or_default(),
+                artifact_id: name,
+                version: named_argument(rest, "version"),
+                classifier: named_argument(rest, "classifier"),
+                ..Dependency::default()
+            }),
+            None => quoted_strings(rest)
+                .first()
+                .and_then(|notation| parse_notation(notation)),
+        };
+        dependency
+            .map(|dependency| {
+                let dependency = Dependency {
+                    group_id: interpolate(&dependency.group_id, self.variables),
+                    artifact_id: interpolate(&dependency.artifact_id, self.variables),
+                    version: dependency.version.map(|v| interpolate(&v, self.variables)),
+                    ..dependency
+                };
+                vec![declared(DependencyTarget::Module(dependency))]
+            })
+            .unwrap_or_default()
+    }
+}
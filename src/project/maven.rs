@@ -0,0 +1,891 @@
+//! Offline Maven model: reads `pom.xml` files with their parents, modules,
+//! properties and dependency management, and resolves the dependency graph
+//! from artifacts already on disk (`~/.m2/repository`, or Gradle's cache).
+//!
+//! Profiles, plugins and remote repositories are ignored. Artifacts missing
+//! from the repository are skipped along with their dependencies.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Context;
+use roxmltree::Node;
+
+/// Parent and BOM chains longer than this are treated as cycles.
+const MAX_POM_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Coordinates {
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: String,
+}
+
+impl std::fmt::Display for Coordinates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.group_id, self.artifact_id, self.version)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Parent {
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: String,
+    pub relative_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Dependency {
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: Option<String>,
+    pub scope: Option<String>,
+    /// The `<type>`, `jar` when absent.
+    pub kind: Option<String>,
+    pub classifier: Option<String>,
+    pub optional: bool,
+    pub system_path: Option<String>,
+    /// `(groupId, artifactId)` pairs; either may be `*`.
+    pub exclusions: Vec<(String, String)>,
+}
+
+impl Dependency {
+    /// Identity used by dependency management and conflict resolution.
+    fn key(&self) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            self.group_id,
+            self.artifact_id,
+            self.kind.as_deref().unwrap_or("jar"),
+            self.classifier.as_deref().unwrap_or("")
+        )
+    }
+
+    fn is_excluded_by(&self, exclusions: &[(String, String)]) -> bool {
+        exclusions.iter().any(|(group, artifact)| {
+            (group == "*" || *group == self.group_id)
+                && (artifact == "*" || *artifact == self.artifact_id)
+        })
+    }
+
+    fn interpolated(&self, context: &HashMap<String, String>) -> Self {
+        let text = |s: &String| interpolate(s, context);
+        Self {
+            group_id: text(&self.group_id),
+            artifact_id: text(&self.artifact_id),
+            version: self.version.as_ref().map(text),
+            scope: self.scope.as_ref().map(text),
+            kind: self.kind.as_ref().map(text),
+            classifier: self.classifier.as_ref().map(text),
+            optional: self.optional,
+            system_path: self.system_path.as_ref().map(text),
+            exclusions: self
+                .exclusions
+                .iter()
+                .map(|(g, a)| (text(g), text(a)))
+                .collect(),
+        }
+    }
+}
+
+/// A `pom.xml` as written, before inheritance and interpolation.
+#[derive(Debug, Clone, Default)]
+pub struct Pom {
+    pub group_id: Option<String>,
+    pub artifact_id: String,
+    pub version: Option<String>,
+    pub packaging: Option<String>,
+    pub parent: Option<Parent>,
+    pub properties: HashMap<String, String>,
+    pub dependency_management: Vec<Dependency>,
+    pub dependencies: Vec<Dependency>,
+    pub modules: Vec<String>,
+    /// `<build><sourceDirectory>`, relative to the module unless absolute.
+    pub source_directory: Option<String>,
+    pub test_source_directory: Option<String>,
+}
+
+impl Pom {
+    pub fn parse(xml: &str) -> anyhow::Result<Self> {
+        let doc = roxmltree::Document::parse(xml)?;
+        let project = doc.root_element();
+        anyhow::ensure!(
+            project.tag_name().name() == "project",
+            "root element is <{}>, not <project>",
+            project.tag_name().name()
+        );
+
+        let parent = child(project, "parent").map(|p| Parent {
+            group_id: child_text(p, "groupId").unwrap_or_default(),
+            artifact_id: child_text(p, "artifactId").unwrap_or_default(),
+            version: child_text(p, "version").unwrap_or_default(),
+            relative_path: child_text(p, "relativePath"),
+        });
+        let properties = child(project, "properties")
+            .map(|props| {
+                props
+                    .children()
+                    .filter(Node::is_element)
+                    .map(|p| {
+                        let value = p.text().unwrap_or_default().trim().to_string();
+                        (p.tag_name().name().to_string(), value)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            group_id: child_text(project, "groupId"),
+            artifact_id: child_text(project, "artifactId")
+                .context("<project> without <artifactId>")?,
+            version: child_text(project, "version"),
+            packaging: child_text(project, "packaging"),
+            parent,
+            properties,
+            dependency_management: child(project, "dependencyManagement")
+                .and_then(|dm| child(dm, "dependencies"))
+                .map(parse_dependencies)
+                .unwrap_or_default(),
+            dependencies: child(project, "dependencies")
+                .map(parse_dependencies)
+                .unwrap_or_default(),
+            modules: child(project, "modules")
+                .map(|modules| {
+                    children(modules, "module")
+                        .filter_map(|m| m.text())
+                        .map(|m| m.trim().to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            source_directory: child(project, "build")
+                .and_then(|build| child_text(build, "sourceDirectory")),
+            test_source_directory: child(project, "build")
+                .and_then(|build| child_text(build, "testSourceDirectory")),
+        })
+    }
+
+    fn read(path: &Path) -> anyhow::Result<Self> {
+        let xml = std::fs::read_to_string(path).with_context(|| format!("reading {:?}", path))?;
+        Self::parse(&xml).with_context(|| format!("parsing {:?}", path))
+    }
+}
+
+fn child<'a, 'i>(node: Node<'a, 'i>, name: &str) -> Option<Node<'a, 'i>> {
+    node.children()
+        .find(|c| c.is_element() && c.tag_name().name() == name)
+}
+
+fn children<'a, 'i>(node: Node<'a, 'i>, name: &'a str) -> impl Iterator<Item = Node<'a, 'i>> {
+    node.children()
+        .filter(move |c| c.is_element() && c.tag_name().name() == name)
+}
+
+fn child_text(node: Node, name: &str) -> Option<String> {
+    child(node, name)
+        .and_then(|c| c.text())
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+fn parse_dependencies(node: Node) -> Vec<Dependency> {
+    children(node, "dependency")
+        .map(|d| Dependency {
+            group_id: child_text(d, "groupId").unwrap_or_default(),
+            artifact_id: child_text(d, "artifactId").unwrap_or_default(),
+            version: child_text(d, "version"),
+            scope: child_text(d, "scope"),
+            kind: child_text(d, "type"),
+            classifier: child_text(d, "classifier"),
+            optional: child_text(d, "optional").is_some_and(|o| o == "true"),
+            system_path: child_text(d, "systemPath"),
+            exclusions: child(d, "exclusions")
+                .map(|e| {
+                    children(e, "exclusion")
+                        .map(|x| {
+                            (
+                                child_text(x, "groupId").unwrap_or_default(),
+                                child_text(x, "artifactId").unwrap_or_default(),
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Replaces `${name}` references from `context`, plus `env.*` from the
+/// environment. Unknown references are left as they are.
+fn interpolate(value: &str, context: &HashMap<String, String>) -> String {
+    let mut value = value.to_string();
+    // Values may reference further properties; bounded in case they cycle.
+    for _ in 0..MAX_POM_DEPTH {
+        let mut out = String::new();
+        let mut rest = value.as_str();
+        let mut changed = false;
+        while let Some(start) = rest.find("${") {
+            let Some(len) = rest[start..].find('}') else {
+                break;
+            };
+            let name = &rest[start + 2..start + len];
+            out.push_str(&rest[..start]);
+            let replacement = context.get(name).cloned().or_else(|| {
+                name.strip_prefix("env.")
+                    .and_then(|var| std::env::var(var).ok())
+            });
+            match replacement {
+                Some(replacement) => {
+                    out.push_str(&replacement);
+                    changed = true;
+                }
+                None => out.push_str(&rest[start..=start + len]),
+            }
+            rest = &rest[start + len + 1..];
+        }
+        out.push_str(rest);
+        value = out;
+        if !changed {
+            break;
+        }
+    }
+    value
+}
+
+/// A POM after inheritance, interpolation and dependency management.
+#[derive(Debug, Clone)]
+struct EffectivePom {
+    coordinates: Coordinates,
+    /// Managed dependencies by `Dependency::key`.
+    managed: HashMap<String, Dependency>,
+    /// Direct dependencies, with managed versions and scopes filled in.
+    dependencies: Vec<Dependency>,
+    modules: Vec<String>,
+    source_directory: Option<String>,
+    test_source_directory: Option<String>,
+}
+
+/// A dependency found in the repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedDependency {
+    pub coordinates: Coordinates,
+    /// Scope after mediation, e.g. a compile dependency of a test
+    /// dependency is `test`.
+    pub scope: String,
+    pub jar: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct MavenModule {
+    pub dir: PathBuf,
+    pub coordinates: Coordinates,
+    /// Main Java sources, `src/main/java` unless the POM says otherwise.
+    pub source_dir: PathBuf,
+    pub test_source_dir: PathBuf,
+    pom: Arc<EffectivePom>,
+}
+
+/// A Maven build: the root project, its modules and their resolved
+/// dependencies.
+#[derive(Debug, Clone)]
+pub struct MavenProject {
+    /// The root project first, then its modules.
+    pub modules: Vec<MavenModule>,
+    pub dependencies: Vec<ResolvedDependency>,
+}
+
+impl MavenProject {
+    /// Reads `root/pom.xml` and its modules, and resolves their dependencies
+    /// from `repository`.
+    pub fn load(root: &Path, repository: &Repository) -> anyhow::Result<Self> {
+        let mut resolver = Resolver {
+            repository,
+            cache: HashMap::new(),
+        };
+
+        let mut modules = Vec::new();
+        let mut seen = HashSet::new();
+        let mut pending = vec![root.join("pom.xml")];
+        while let Some(pom_path) = pending.pop() {
+            let canonical = pom_path.canonicalize().unwrap_or_else(|_| pom_path.clone());
+            if !seen.insert(canonical) {
+                continue;
+            }
+            let pom = match Pom::read(&pom_path) {
+                Ok(pom) => pom,
+                Err(err) if modules.is_empty() => return Err(err),
+                Err(err) => {
+                    tracing::warn!("Skipping Maven module: {:#}", err);
+                    continue;
+                }
+            };
+            let dir = pom_path.parent().unwrap_or(root).to_path_buf();
+            let inherited = resolver.inherit(pom, Some(&dir), 0);
+            let effective = resolver.effective(inherited, 0);
+
+            // Modules pop in declaration order.
+            for module in effective.modules.iter().rev() {
+                let path = dir.join(module);
+                pending.push(if path.extension().is_some_and(|ext| ext == "xml") {
+                    path
+                } else {
+                    path.join("pom.xml")
+                });
+            }
+            let source_dir = |declared: &Option<String>, default: &str| {
+                let declared = declared.as_deref().map(|d| {
+                    d.trim_start_matches("${project.basedir}/")
+                        .trim_start_matches("${basedir}/")
+                });
+                dir.join(declared.unwrap_or(default))
+            };
+            modules.push(MavenModule {
+                source_dir: source_dir(&effective.source_directory, "src/main/java"),
+                test_source_dir: source_dir(&effective.test_source_directory, "src/test/java"),
+                dir,
+                coordinates: effective.coordinates.clone(),
+                pom: Arc::new(effective),
+            });
+        }
+
+        let mut dependencies: Vec<ResolvedDependency> = Vec::new();
+        for module in &modules {
+            let resolved =
+                resolver.resolve(&module.pom.dependencies, &module.pom.managed, &modules);
+            for dep in resolved {
+                if !dependencies
+                    .iter()
+                    .any(|d| d.jar == dep.jar && d.scope == dep.scope)
+                {
+                    dependencies.push(dep);
+                }
+            }
+        }
+        Ok(Self {
+            modules,
+            dependencies,
+        })
+    }
+
+    /// Jars main code compiles against, from any module.
+    pub fn classpath(&self) -> Vec<PathBuf> {
+        self.split_classpath().0
+    }
+
+    /// Jars only test code compiles against, such as JUnit.
+    pub fn test_classpath(&self) -> Vec<PathBuf> {
+        self.split_classpath().1
+    }
+
+    fn split_classpath(&self) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        split_classpath(
+            self.dependencies
+                .iter()
+                .map(|d| (d.jar.clone(), d.scope.as_str())),
+        )
+    }
+}
+
+/// Splits jars by scope into the main and the test compile classpath.
+/// `runtime` jars are on neither, and a jar in both scopes stays on the main
+/// one.
+pub fn split_classpath<'a>(
+    jars: impl IntoIterator<Item = (PathBuf, &'a str)>,
+) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut main = Vec::new();
+    let mut test = Vec::new();
+    for (jar, scope) in jars {
+        let classpath = match scope {
+            "test" => &mut test,
+            "runtime" => continue,
+            _ => &mut main,
+        };
+        if !classpath.contains(&jar) {
+            classpath.push(jar);
+        }
+    }
+    test.retain(|jar| !main.contains(jar));
+    (main, test)
+}
+
+/// Where Maven keeps downloaded artifacts: `<localRepository>` from
+/// `~/.m2/settings.xml`, or `~/.m2/repository`.
+pub fn local_repository() -> Option<PathBuf> {
+    let home = super::home_dir()?;
+    let m2 = home.join(".m2");
+    let configured = std::fs::read_to_string(m2.join("settings.xml"))
+        .ok()
+        .and_then(|xml| {
+            let doc = roxmltree::Document::parse(&xml).ok()?;
+            child_text(doc.root_element(), "localRepository")
+        })
+        .map(|path| path.replace("${user.home}", &home.to_string_lossy()));
+    Some(configured.map_or_else(|| m2.join("repository"), PathBuf::from))
+}
+
+/// Resolves `dependencies` and everything they pull in through the POMs in
+/// `repository`.
+pub fn resolve_dependencies(
+    repository: &Repository,
+    dependencies: &[Dependency],
+) -> Vec<ResolvedDependency> {
+    let mut resolver = Resolver {
+        repository,
+        cache: HashMap::new(),
+    };
+    resolver.resolve(dependencies, &HashMap::new(), &[])
+}
+
+/// Where downloaded artifacts live on disk.
+#[derive(Debug, Clone)]
+pub enum Repository {
+    /// A Maven local repository such as `~/.m2/repository`.
+    Maven(PathBuf),
+    /// Gradle's `caches/modules-2/files-2.1`, which keeps each file of
+    /// `<group>/<artifact>/<version>` in a directory named by its SHA-1.
+    GradleCache(PathBuf),
+}
+
+impl Repository {
+    /// The artifact file, if it has been downloaded.
+    pub fn find(
+        &self,
+        coordinates: &Coordinates,
+        classifier: Option<&str>,
+        extension: &str,
+    ) -> Option<PathBuf> {
+        match self {
+            Self::Maven(root) => {
+                let path = artifact_path(root, coordinates, classifier, extension);
+                path.is_file().then_some(path)
+            }
+            Self::GradleCache(root) => {
+                let file = artifact_file_name(coordinates, classifier, extension);
+                let dir = root
+                    .join(&coordinates.group_id)
+                    .join(&coordinates.artifact_id)
+                    .join(&coordinates.version);
+                std::fs::read_dir(dir)
+                    .ok()?
+                    .flatten()
+                    .map(|hash| hash.path().join(&file))
+                    .find(|path| path.is_file())
+            }
+        }
+    }
+
+    /// Versions of `group:artifact` present on disk.
+    fn versions(&self, group_id: &str, artifact_id: &str) -> Vec<String> {
+        let dir = match self {
+            Self::Maven(root) => group_id
+                .split('.')
+                .fold(root.clone(), |path, segment| path.join(segment)),
+            Self::GradleCache(root) => root.join(group_id),
+        };
+        std::fs::read_dir(dir.join(artifact_id))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+            .collect()
+    }
+}
+
+fn artifact_file_name(
+    coordinates: &Coordinates,
+    classifier: Option<&str>,
+    extension: &str,
+) -> String {
+    match classifier {
+        Some(classifier) => format!(
+            "{}-{}-{}.{}",
+            coordinates.artifact_id, coordinates.version, classifier, extension
+        ),
+        None => format!(
+            "{}-{}.{}",
+            coordinates.artifact_id, coordinates.version, extension
+        ),
+    }
+}
+
+/// `<repository>/<group path>/<artifact>/<version>/<artifact>-<version>[-<classifier>].<ext>`
+pub fn artifact_path(
+    repository: &Path,
+    coordinates: &Coordinates,
+    classifier: Option<&str>,
+    extension: &str,
+) -> PathBuf {
+    coordinates
+        .group_id
+        .split('.')
+        .fold(repository.to_path_buf(), |path, segment| path.join(segment))
+        .join(&coordinates.artifact_id)
+        .join(&coordinates.version)
+        .join(artifact_file_name(coordinates, classifier, extension))
+}
+
+struct Resolver<'a> {
+    repository: &'a Repository,
+    /// Effective repository POMs by coordinates; `None` when missing or
+    /// broken, and while being loaded so cycles stop.
+    cache: HashMap<Coordinates, Option<Arc<EffectivePom>>>,
+}
+
+impl Resolver<'_> {
+    /// Merges the parent chain into `pom`. `dir` is the directory of a
+    /// workspace POM, whose parent is looked up by `relativePath` first.
+    fn inherit(&mut self, mut pom: Pom, dir: Option<&Path>, depth: usize) -> Pom {
+        let Some(parent_ref) = pom.parent.clone() else {
+            return pom;
+        };
+        if depth > MAX_POM_DEPTH {
+            return pom;
+        }
+
+        let local = dir.and_then(|dir| {
+            let path = dir.join(parent_ref.relative_path.as_deref().unwrap_or("../pom.xml"));
+            let path = if path.is_dir() {
+                path.join("pom.xml")
+            } else {
+                path
+            };
+            let parent = Pom::read(&path).ok()?;
+            (parent.artifact_id == parent_ref.artifact_id)
+                .then(|| (parent, path.parent().map(Path::to_path_buf)))
+        });
+        let parent = match local {
+            Some((parent, parent_dir)) => {
+                Some(self.inherit(parent, parent_dir.as_deref(), depth + 1))
+            }
+            None => {
+                let coordinates = Coordinates {
+                    group_id: parent_ref.group_id.clone(),
+                    artifact_id: parent_ref.artifact_id.clone(),
+                    version: parent_ref.version.clone(),
+                };
+                let pom = self
+                    .repository
+                    .find(&coordinates, None, "pom")
+                    .context("not in the repository")
+                    .and_then(|path| Pom::read(&path));
+                match pom {
+                    Ok(parent) => Some(self.inherit(parent, None, depth + 1)),
+                    Err(err) => {
+                        tracing::debug!("Missing parent {}: {:#}", coordinates, err);
+                        None
+                    }
+                }
+            }
+        };
+
+        pom.group_id = pom.group_id.or(Some(parent_ref.group_id));
+        pom.version = pom.version.or(Some(parent_ref.version));
+        let Some(parent) = parent else {
+            return pom;
+        };
+
+        pom.source_directory = pom.source_directory.or(parent.source_directory);
+        pom.test_source_directory = pom.test_source_directory.or(parent.test_source_directory);
+
+        let mut properties = parent.properties;
+        properties.extend(pom.properties);
+        pom.properties = properties;
+        for managed in parent.dependency_management {
+            if !pom
+                .dependency_management
+                .iter()
+                .any(|d| d.key() == managed.key())
+            {
+                pom.dependency_management.push(managed);
+            }
+        }
+        let mut dependencies: Vec<_> = parent
+            .dependencies
+            .into_iter()
+            .filter(|d| !pom.dependencies.iter().any(|own| own.key() == d.key()))
+            .collect();
+        dependencies.append(&mut pom.dependencies);
+        pom.dependencies = dependencies;
+        pom
+    }
+
+    /// Interpolates an inherited POM and applies its dependency management,
+    /// including imported BOMs.
+    fn effective(&mut self, pom: Pom, depth: usize) -> EffectivePom {
+        let mut context = pom.properties.clone();
+        let mut project = vec![
+            ("groupId", pom.group_id.clone()),
+            ("artifactId", Some(pom.artifact_id.clone())),
+            ("version", pom.version.clone()),
+            ("packaging", pom.packaging.clone()),
+        ];
+        if let Some(parent) = &pom.parent {
+            project.extend([
+                ("parent.groupId", Some(parent.group_id.clone())),
+                ("parent.artifactId", Some(parent.artifact_id.clone())),
+                ("parent.version", Some(parent.version.clone())),
+            ]);
+        }
+        for (name, value) in project {
+            if let Some(value) = value {
+                context.insert(format!("project.{}", name), value.clone());
+                context.insert(format!("pom.{}", name), value);
+            }
+        }
+
+        let coordinates = Coordinates {
+            group_id: interpolate(pom.group_id.as_deref().unwrap_or_default(), &context),
+            artifact_id: interpolate(&pom.artifact_id, &context),
+            version: interpolate(pom.version.as_deref().unwrap_or_default(), &context),
+        };
+
+        // Declared entries win over anything imported from a BOM.
+        let mut managed = HashMap::new();
+        let mut imports = Vec::new();
+        for dep in &pom.dependency_management {
+            let dep = dep.interpolated(&context);
+            if dep.scope.as_deref() == Some("import") && dep.kind.as_deref() == Some("pom") {
+                imports.push(dep);
+            } else {
+                managed.entry(dep.key()).or_insert(dep);
+            }
+        }
+        for bom in imports {
+            let Some(version) = bom.version.clone() else {
+                continue;
+            };
+            let coordinates = Coordinates {
+                group_id: bom.group_id,
+                artifact_id: bom.artifact_id,
+                version,
+            };
+            if let Some(bom) = self.load(&coordinates, depth + 1) {
+                for (key, dep) in &bom.managed {
+                    managed.entry(key.clone()).or_insert_with(|| dep.clone());
+                }
+            }
+        }
+
+        let dependencies = pom
+            .dependencies
+            .iter()
+            .map(|dep| {
+                let mut dep = dep.interpolated(&context);
+                if let Some(managed) = managed.get(&dep.key()) {
+                    dep.version = dep.version.or_else(|| managed.version.clone());
+                    dep.scope = dep.scope.or_else(|| managed.scope.clone());
+                    if dep.exclusions.is_empty() {
+                        dep.exclusions = managed.exclusions.clone();
+                    }
+                }
+                dep
+            })
+            .collect();
+
+        EffectivePom {
+            coordinates,
+            managed,
+            dependencies,
+            modules: pom.modules,
+            source_directory: pom.source_directory.map(|dir| interpolate(&dir, &context)),
+            test_source_directory: pom
+                .test_source_directory
+                .map(|dir| interpolate(&dir, &context)),
+        }
+    }
+
+    fn load(&mut self, coordinates: &Coordinates, depth: usize) -> Option<Arc<EffectivePom>> {
+        if let Some(cached) = self.cache.get(coordinates) {
+            return cached.clone();
+        }
+        if depth > MAX_POM_DEPTH {
+            return None;
+        }
+        self.cache.insert(coordinates.clone(), None);
+
+        let pom = self
+            .repository
+            .find(coordinates, None, "pom")
+            .context("not in the repository")
+            .and_then(|path| Pom::read(&path));
+        let loaded = match pom {
+            Ok(pom) => {
+                let inherited = self.inherit(pom, None, depth);
+                Some(Arc::new(self.effective(inherited, depth)))
+            }
+            Err(err) => {
+                tracing::debug!("Missing POM for {}: {:#}", coordinates, err);
+                None
+            }
+        };
+        self.cache.insert(coordinates.clone(), loaded.clone());
+        loaded
+    }
+
+    /// Breadth-first walk of the dependency graph, so the nearest
+    /// declaration of an artifact wins as in Maven. `managed` overrides
+    /// versions chosen further down the graph.
+    fn resolve(
+        &mut self,
+        dependencies: &[Dependency],
+        managed: &HashMap<String, Dependency>,
+        workspace: &[MavenModule],
+    ) -> Vec<ResolvedDependency> {
+        let mut resolved = Vec::new();
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<_> = dependencies
+            .iter()
+            .map(|dep| {
+                let scope = dep.scope.clone().unwrap_or_else(|| "compile".to_string());
+                (dep.clone(), scope, dep.exclusions.clone())
+            })
+            .collect();
+
+        while let Some((dep, scope, exclusions)) = queue.pop_front() {
+            if scope == "import" || !seen.insert(dep.key()) {
+                continue;
+            }
+            let Some(version) = self.resolve_version(&dep) else {
+                tracing::debug!("No version for {}:{}", dep.group_id, dep.artifact_id);
+                continue;
+            };
+            let coordinates = Coordinates {
+                group_id: dep.group_id.clone(),
+                artifact_id: dep.artifact_id.clone(),
+                version,
+            };
+
+            // Sibling modules are indexed from source; only their own
+            // dependencies matter.
+            let sibling = workspace.iter().find(|m| {
+                m.coordinates.group_id == coordinates.group_id
+                    && m.coordinates.artifact_id == coordinates.artifact_id
+            });
+            let pom = match sibling {
+                Some(sibling) => Some(sibling.pom.clone()),
+                None => {
+                    match self.artifact_file(&dep, &coordinates) {
+                        Some(jar) => resolved.push(ResolvedDependency {
+                            coordinates: coordinates.clone(),
+                            scope: scope.clone(),
+                            jar,
+                        }),
+                        None => tracing::debug!("{} is not in the repository", coordinates),
+                    }
+                    self.load(&coordinates, 0)
+                }
+            };
+            let Some(pom) = pom else {
+                continue;
+            };
+
+            for transitive in &pom.dependencies {
+                if transitive.optional || transitive.is_excluded_by(&exclusions) {
+                    continue;
+                }
+                let Some(transitive_scope) =
+                    mediate_scope(&scope, transitive.scope.as_deref().unwrap_or("compile"))
+                else {
+                    continue;
+                };
+                let mut transitive = transitive.clone();
+                if let Some(managed) = managed.get(&transitive.key())
+                    && managed.version.is_some()
+                {
+                    transitive.version = managed.version.clone();
+                }
+                let mut transitive_exclusions = exclusions.clone();
+                transitive_exclusions.extend(transitive.exclusions.iter().cloned());
+                queue.push_back((transitive, transitive_scope, transitive_exclusions));
+            }
+        }
+        resolved
+    }
+
+    /// The version to use for `dep`. Ranges pick the highest version inside
+    /// them that the repository has.
+    fn resolve_version(&self, dep: &Dependency) -> Option<String> {
+        let spec = dep.version.as_deref()?;
+        if !spec.starts_with('[') && !spec.starts_with('(') {
+            return Some(spec.to_string());
+        }
+        self.repository
+            .versions(&dep.group_id, &dep.artifact_id)
+            .into_iter()
+            .filter(|version| version_in_range(version, spec))
+            .max_by(|a, b| compare_versions(a, b))
+    }
+
+    fn artifact_file(&self, dep: &Dependency, coordinates: &Coordinates) -> Option<PathBuf> {
+        if dep.scope.as_deref() == Some("system") {
+            return dep
+                .system_path
+                .as_ref()
+                .map(PathBuf::from)
+                .filter(|p| p.is_file());
+        }
+        let classifier = match dep.kind.as_deref().unwrap_or("jar") {
+            "jar" | "bundle" | "maven-plugin" | "ejb" => dep.classifier.as_deref(),
+            "test-jar" => Some(dep.classifier.as_deref().unwrap_or("tests")),
+            _ => return None,
+        };
+        self.repository.find(coordinates, classifier, "jar")
+    }
+}
+
+/// Scope of a dependency reached through one in `parent` scope, or `None`
+/// when it does not carry over (`provided`, `test` and the like).
+fn mediate_scope(parent: &str, scope: &str) -> Option<String> {
+    match (parent, scope) {
+        ("compile", "compile" | "runtime") => Some(scope.to_string()),
+        ("provided" | "runtime" | "test", "compile" | "runtime") => Some(parent.to_string()),
+        _ => None,
+    }
+}
+
+/// Compares versions segment by segment, numerically where both are
+/// numbers.
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let segments =
+        |v: &str| -> Vec<String> { v.split(['.', '-']).map(str::to_string).collect::<Vec<_>>() };
+    let (a, b) = (segments(a), segments(b));
+    for (x, y) in a.iter().zip(&b) {
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => x.cmp(y),
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// Whether `version` satisfies a range such as `[1.0,2.0)` or `[1.5]`.
+/// Unions of ranges are not supported.
+fn version_in_range(version: &str, range: &str) -> bool {
+    if range.len() < 2 {
+        return false;
+    }
+    let inclusive_start = range.starts_with('[');
+    let inclusive_end = range.ends_with(']');
+    let inner = &range[1..range.len() - 1];
+    let (lower, upper) = match inner.split_once(',') {
+        Some((lower, upper)) => (lower.trim(), upper.trim()),
+        None => (inner.trim(), inner.trim()),
+    };
+    let above = lower.is_empty()
+        || match compare_versions(version, lower) {
+            std::cmp::Ordering::Greater => true,
+            std::cmp::Ordering::Equal => inclusive_start,
+            std::cmp::Ordering::Less => false,
+        };
+    let below = upper.is_empty()
+        || match compare_versions(version, upper) {
+            std::cmp::Ordering::Less => true,
+            std::cmp::Ordering::Equal => inclusive_end,
+            std::cmp::Ordering::Greater => false,
+        };
+    above && below
+}
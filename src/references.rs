@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+use tower_lsp::lsp_types::{Location, Range, Url};
+
+use crate::lang::LanguageService;
+use crate::state::{Document, GlobalIndex};
+
+/// Finds every identifier named `name` that resolves back to `target`.
+///
+/// Candidate files come from the index's occurrence table, so only files that
+/// mention the name are loaded through `load` (open buffer or disk) and
+/// checked with the language service's resolution chain.
+pub fn find_references(
+    service: &dyn LanguageService,
+    index: &GlobalIndex,
+    target: &Location,
+    name: &str,
+    include_declaration: bool,
+    load: &dyn Fn(&Url) -> Option<Document>,
+) -> Vec<Location> {
+    // Locals and parameters are not indexed and cannot escape their file.
+    let file_local = !index.is_indexed_declaration(target);
+
+    let mut by_file: BTreeMap<Url, Vec<Range>> = BTreeMap::new();
+    for occurrence in index.occurrences_of(name) {
+        if file_local && occurrence.uri != target.uri {
+            continue;
+        }
+        by_file
+            .entry(occurrence.uri)
+            .or_default()
+            .push(occurrence.range);
+    }
+
+    let mut results = Vec::new();
+    for (uri, mut ranges) in by_file {
+        let Some(doc) = load(&uri) else {
+            tracing::debug!("references: could not load {}", uri);
+            continue;
+        };
+        ranges.sort_by_key(|r| (r.start.line, r.start.character));
+
+        for range in ranges {
+            let resolved = service.declaration_location(
+                &doc.tree,
+                &doc.text,
+                range.start,
+                index,
+                uri.as_str(),
+            );
+            if resolved.as_ref() != Some(target) {
+                continue;
+            }
+            if !include_declaration
+                && service.is_declaration_name(&doc.tree, &doc.text, range.start)
+            {
+                continue;
+            }
+            results.push(Location::new(uri.clone(), range));
+        }
+    }
+
+    results
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail};
+use tower_lsp::lsp_types::{
+    DocumentChangeOperation, DocumentChanges, Location, OneOf,
+    OptionalVersionedTextDocumentIdentifier, Position, Range, RenameFile, ResourceOp,
+    TextDocumentEdit, TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::lang::LanguageService;
+use crate::references::find_references;
+use crate::state::{Document, GlobalIndex};
+use crate::utils::{get_node_at_pos, get_node_text, node_range};
+
+/// Checks that the symbol at `position` can be renamed and returns the range
+/// of the identifier the editor should offer for editing.
+pub fn prepare_rename(
+    service: &dyn LanguageService,
+    index: &GlobalIndex,
+    doc: &Document,
+    uri: &str,
+    position: Position,
+) -> anyhow::Result<Range> {
+    let (node, _) = get_node_at_pos(&doc.tree, &doc.text, position)
+        .ok_or_else(|| anyhow!("No symbol at cursor"))?;
+    let range = node_range(node, &doc.text);
+
+    if service
+        .local_rename_edits(&doc.tree, &doc.text, position, "_")
+        .is_some()
+    {
+        return Ok(range);
+    }
+
+    let target = service
+        .declaration_location(&doc.tree, &doc.text, position, index, uri)
+        .ok_or_else(|| anyhow!("Cannot resolve the symbol to rename"))?;
+    ensure_workspace_symbol(&target)?;
+    Ok(range)
+}
+
+/// Computes the workspace edit renaming the symbol at `position`.
+///
+/// Locals and parameters are renamed within their scope only. Members and
+/// types are renamed at every reference in indexed files, including imports;
+/// renaming a public top-level class also renames its `.java` file.
+#[allow(clippy::too_many_arguments)]
+pub fn rename(
+    service: &dyn LanguageService,
+    index: &GlobalIndex,
+    doc: &Document,
+    uri: &str,
+    position: Position,
+    new_name: &str,
+    keywords: &[String],
+    load: &dyn Fn(&Url) -> Option<Document>,
+) -> anyhow::Result<WorkspaceEdit> {
+    if !is_valid_identifier(new_name) || keywords.iter().any(|k| k == new_name) {
+        bail!("'{}' is not a valid Java identifier", new_name);
+    }
+
+    let current_url = Url::parse(uri)?;
+    if let Some(edits) = service.local_rename_edits(&doc.tree, &doc.text, position, new_name) {
+        return Ok(WorkspaceEdit {
+            changes: Some(HashMap::from([(current_url, edits)])),
+            ..WorkspaceEdit::default()
+        });
+    }
+
+    let (_, old_name) = get_node_at_pos(&doc.tree, &doc.text, position)
+        .ok_or_else(|| anyhow!("No symbol at cursor"))?;
+    let target = service
+        .declaration_location(&doc.tree, &doc.text, position, index, uri)
+        .ok_or_else(|| anyhow!("Cannot resolve the symbol to rename"))?;
+    ensure_workspace_symbol(&target)?;
+
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for location in find_references(service, index, &target, &old_name, true, load) {
+        changes
+            .entry(location.uri)
+            .or_default()
+            .push(TextEdit::new(location.range, new_name.to_string()));
+    }
+
+    // Constructors share the class name but resolve to themselves.
+    let target_doc = load(&target.uri);
+    if let Some(target_doc) = &target_doc {
+        for range in constructor_names(target_doc, &target, &old_name) {
+            let edits = changes.entry(target.uri.clone()).or_default();
+            if !edits.iter().any(|e| e.range == range) {
+                edits.push(TextEdit::new(range, new_name.to_string()));
+            }
+        }
+    }
+
+    for edits in changes.values_mut() {
+        edits.sort_by_key(|e| (e.range.start.line, e.range.start.character));
+    }
+
+    let file_rename = target_doc
+        .as_ref()
+        .and_then(|d| renamed_file(d, &target, &old_name, new_name));
+    let Some((old_uri, new_uri)) = file_rename else {
+        return Ok(WorkspaceEdit {
+            changes: Some(changes),
+            ..WorkspaceEdit::default()
+        });
+    };
+
+    // Text edits address the old URI, so they must precede the file rename.
+    let mut operations: Vec<_> = changes
+        .into_iter()
+        .map(|(uri, edits)| {
+            DocumentChangeOperation::Edit(TextDocumentEdit {
+                text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+                edits: edits.into_iter().map(OneOf::Left).collect(),
+            })
+        })
+        .collect();
+    operations.push(DocumentChangeOperation::Op(ResourceOp::Rename(
+        RenameFile {
+            old_uri,
+            new_uri,
+            options: None,
+            annotation_id: None,
+        },
+    )));
+
+    Ok(WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        ..WorkspaceEdit::default()
+    })
+}
+
+fn ensure_workspace_symbol(target: &Location) -> anyhow::Result<()> {
+    match target.uri.scheme() {
+        "file" | "untitled" => Ok(()),
+        "jrsls-std" => bail!("Cannot rename symbols declared in the JDK"),
+        other => bail!("Cannot rename symbols from '{}' sources", other),
+    }
+}
+
+fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// The type declaration `target` points at, if it points at one.
+fn type_declaration<'a>(doc: &'a Document, target: &Location) -> Option<tree_sitter::Node<'a>> {
+    let start = tree_sitter::Point::new(
+        target.range.start.line as usize,
+        target.range.start.character as usize,
+    );
+    let end = tree_sitter::Point::new(
+        target.range.end.line as usize,
+        target.range.end.character as usize,
+    );
+    let node = doc
+        .tree
+        .root_node()
+        .descendant_for_point_range(start, end)?;
+    node.kind().ends_with("_declaration").then_some(node)
+}
+
+fn constructor_names(doc: &Document, target: &Location, old_name: &str) -> Vec<Range> {
+    let Some(body) =
+        type_declaration(doc, target).and_then(|decl| decl.child_by_field_name("body"))
+    else {
+        return Vec::new();
+    };
+
+    let mut cursor = body.walk();
+    body.children(&mut cursor)
+        .filter(|child| child.kind() == "constructor_declaration")
+        .filter_map(|child| child.child_by_field_name("name"))
+        .filter(|name| get_node_text(*name, &doc.text) == old_name)
+        .map(|name| node_range(name, &doc.text))
+        .collect()
+}
+
+/// Old and new URI when `target` is a public top-level class living in a
+/// file named after it.
+fn renamed_file(
+    doc: &Document,
+    target: &Location,
+    old_name: &str,
+    new_name: &str,
+) -> Option<(Url, Url)> {
+    let decl = type_declaration(doc, target)?;
+    if decl.parent()?.kind() != "program" {
+        return None;
+    }
+
+    let mut cursor = decl.walk();
+    let is_public = decl
+        .children(&mut cursor)
+        .find(|c| c.kind() == "modifiers")
+        .is_some_and(|m| {
+            get_node_text(m, &doc.text)
+                .split_whitespace()
+                .any(|w| w == "public")
+        });
+    if !is_public {
+        return None;
+    }
+
+    let path = target.uri.to_file_path().ok()?;
+    if path.file_stem()?.to_str()? != old_name {
+        return None;
+    }
+    let new_path = path.with_file_name(format!("{}.java", new_name));
+    Some((target.uri.clone(), Url::from_file_path(new_path).ok()?))
+}
@@ -1,8 +1,10 @@
-use std::sync::Mutex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Mutex, RwLock};
 
 use dashmap::{DashMap, mapref::entry::Entry};
 use ropey::Rope;
 use salsa::Setter;
+use serde::{Deserialize, Serialize};
 use tower_lsp::lsp_types;
 use tree_sitter::Tree;
 
@@ -18,6 +20,9 @@ pub struct ClassLocation {
     pub fqcn: String,
     pub uri: lsp_types::Url,
     pub range: lsp_types::Range,
+    pub kind: TypeKind,
+    pub signature: String,
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -28,19 +33,74 @@ pub struct MemberLocation {
     pub param_count: usize,
     pub is_varargs: bool,
     pub param_types: Vec<crate::ast::InferredType>,
+    pub param_names: Vec<String>,
     pub is_field: bool,
+    pub is_constructor: bool,
     pub field_type: Option<crate::ast::InferredType>,
+    pub return_type: Option<crate::ast::InferredType>,
+    pub modifiers: Modifiers,
+    pub declaring_kind: TypeKind,
+    pub signature: String,
+    pub doc: Option<String>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+impl From<IndexedClass> for ClassLocation {
+    fn from(class: IndexedClass) -> Self {
+        Self {
+            fqcn: class.fqcn,
+            uri: class.uri,
+            range: class.range,
+            kind: class.kind,
+            signature: class.signature,
+            doc: class.doc,
+        }
+    }
+}
+
+impl From<IndexedMember> for MemberLocation {
+    fn from(member: IndexedMember) -> Self {
+        Self {
+            fqmn: member.fqmn,
+            uri: member.uri,
+            range: member.range,
+            param_count: member.param_count,
+            is_varargs: member.is_varargs,
+            param_types: member.param_types,
+            param_names: member.param_names,
+            is_field: member.is_field,
+            is_constructor: member.is_constructor,
+            field_type: member.field_type,
+            return_type: member.return_type,
+            modifiers: member.modifiers,
+            declaring_kind: member.declaring_kind,
+            signature: member.signature,
+            doc: member.doc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct IndexedClass {
     pub short_name: String,
     pub fqcn: String,
     pub uri: lsp_types::Url,
     pub range: lsp_types::Range,
+    /// Declaration header, e.g. `public final class String`.
+    pub signature: String,
+    /// Javadoc rendered as Markdown.
+    pub doc: Option<String>,
+    pub kind: TypeKind,
+    /// The extended class as written, without type arguments. Implicit
+    /// ones (`Object`, `Enum`, `Record`) are recorded by their FQCN; `None`
+    /// for interfaces and `java.lang.Object`. `GlobalIndex::supertypes`
+    /// resolves names against the file's package and imports.
+    pub superclass: Option<String>,
+    /// Implemented interfaces, or the extended ones for an interface,
+    /// spelled like `superclass`.
+    pub interfaces: Vec<String>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct IndexedMember {
     pub name: String,
     pub fqmn: String,
@@ -49,8 +109,74 @@ pub struct IndexedMember {
     pub param_count: usize,
     pub is_varargs: bool,
     pub param_types: Vec<crate::ast::InferredType>,
+    pub param_names: Vec<String>,
     pub is_field: bool,
+    pub is_constructor: bool,
     pub field_type: Option<crate::ast::InferredType>,
+    /// Declared return type of a method; `None` for `void`, fields and
+    /// constructors.
+    pub return_type: Option<crate::ast::InferredType>,
+    pub modifiers: Modifiers,
+    /// Kind of the type declaring the member.
+    pub declaring_kind: TypeKind,
+    pub signature: String,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TypeKind {
+    #[default]
+    Class,
+    Interface,
+    Enum,
+    Record,
+    Annotation,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Visibility {
+    Public,
+    Protected,
+    #[default]
+    Package,
+    Private,
+}
+
+/// Member modifiers, implicit ones included: interface methods are public
+/// and, without a body, abstract; interface fields are public static.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Modifiers {
+    pub visibility: Visibility,
+    pub is_static: bool,
+    pub is_abstract: bool,
+    /// A `default` interface method.
+    pub is_default: bool,
+}
+
+/// An identifier or type name seen in a workspace file. Used to narrow
+/// reference searches down to files that mention a name at all.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct IndexedOccurrence {
+    pub name: String,
+    pub range: lsp_types::Range,
+}
+
+/// Everything the index holds for one file, as passed to `upsert_file`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub uri: String,
+    pub package_name: Option<String>,
+    pub imports: Vec<String>,
+    pub classes: Vec<IndexedClass>,
+    pub members: Vec<IndexedMember>,
+    pub occurrences: Vec<IndexedOccurrence>,
+}
+
+/// Hover material for a declaration stored in the index.
+#[derive(Debug, Clone)]
+pub struct DeclarationInfo {
+    pub signature: String,
+    pub doc: Option<String>,
 }
 
 #[salsa::input]
@@ -60,6 +186,8 @@ struct FileIndex {
     imports: Vec<String>,
     classes: Vec<IndexedClass>,
     members: Vec<IndexedMember>,
+    #[returns(ref)]
+    occurrences: Vec<IndexedOccurrence>,
 }
 
 #[salsa::db]
@@ -71,9 +199,84 @@ struct IndexStorage {
 #[salsa::db]
 impl salsa::Database for IndexStorage {}
 
+/// Sorted map from short names to the URIs declaring (or, for occurrences,
+/// mentioning) them, so name lookups only touch files that can match.
+#[derive(Default)]
+struct NameTable {
+    names: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl NameTable {
+    fn insert(&mut self, name: &str, uri: &str) {
+        self.names
+            .entry(name.to_string())
+            .or_default()
+            .insert(uri.to_string());
+    }
+
+    fn remove(&mut self, name: &str, uri: &str) {
+        if let Some(uris) = self.names.get_mut(name) {
+            uris.remove(uri);
+            if uris.is_empty() {
+                self.names.remove(name);
+            }
+        }
+    }
+
+    /// Moves `uri` off the names in `old` it no longer has and onto the
+    /// ones in `new` it gained.
+    fn update(&mut self, uri: &str, old: &BTreeSet<&str>, new: &BTreeSet<&str>) {
+        for name in old.difference(new) {
+            self.remove(name, uri);
+        }
+        for name in new.difference(old) {
+            self.insert(name, uri);
+        }
+    }
+
+    fn uris(&self, name: &str) -> Vec<String> {
+        self.names
+            .get(name)
+            .map(|uris| uris.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+fn declared_names<'a>(
+    classes: &'a [IndexedClass],
+    members: &'a [IndexedMember],
+) -> BTreeSet<&'a str> {
+    classes
+        .iter()
+        .map(|c| c.short_name.as_str())
+        .chain(members.iter().map(|m| m.name.as_str()))
+        .collect()
+}
+
+fn class_names(classes: &[IndexedClass]) -> BTreeSet<&str> {
+    classes.iter().map(|c| c.short_name.as_str()).collect()
+}
+
+fn occurrence_names(occurrences: &[IndexedOccurrence]) -> BTreeSet<&str> {
+    occurrences.iter().map(|o| o.name.as_str()).collect()
+}
+
+const OBJECT: &str = "java.lang.Object";
+
+fn member_name(fqmn: &str) -> &str {
+    fqmn.rsplit('.').next().unwrap_or(fqmn)
+}
+
 pub struct GlobalIndex {
     storage: Mutex<IndexStorage>,
     handles: DashMap<String, FileIndex>,
+    names: RwLock<NameTable>,
+    /// Short names of declared classes only, for class completion.
+    class_names: RwLock<NameTable>,
+    /// Names of recorded occurrences, by the files they occur in.
+    occurrence_names: RwLock<NameTable>,
+    /// URI prefixes of test source roots.
+    test_roots: RwLock<Vec<String>>,
 }
 
 impl GlobalIndex {
@@ -81,9 +284,33 @@ impl GlobalIndex {
         Self {
             storage: Mutex::new(IndexStorage::default()),
             handles: DashMap::new(),
+            names: RwLock::new(NameTable::default()),
+            class_names: RwLock::new(NameTable::default()),
+            occurrence_names: RwLock::new(NameTable::default()),
+            test_roots: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Replaces the test source roots, given as URI prefixes: directories,
+    /// or `jrsls-jar` archives only tests compile against.
+    pub fn set_test_roots(&self, roots: Vec<lsp_types::Url>) {
+        if let Ok(mut test_roots) = self.test_roots.write() {
+            *test_roots = roots.into_iter().map(String::from).collect();
         }
     }
 
+    pub fn is_test_source(&self, uri: &str) -> bool {
+        self.test_roots
+            .read()
+            .is_ok_and(|roots| roots.iter().any(|root| uri.starts_with(root.as_str())))
+    }
+
+    /// Whether code in `from` can see a declaration in `declared_in`: test
+    /// sources are only visible to other test sources.
+    pub fn is_visible_from(&self, declared_in: &lsp_types::Url, from: &str) -> bool {
+        !self.is_test_source(declared_in.as_str()) || self.is_test_source(from)
+    }
+
     pub fn upsert_file(
         &self,
         uri: &str,
@@ -91,33 +318,157 @@ impl GlobalIndex {
         imports: Vec<String>,
         classes: Vec<IndexedClass>,
         members: Vec<IndexedMember>,
+        occurrences: Vec<IndexedOccurrence>,
     ) {
+        self.insert_entries(vec![FileEntry {
+            uri: uri.to_string(),
+            package_name,
+            imports,
+            classes,
+            members,
+            occurrences,
+        }]);
+    }
+
+    /// Stores many files under a single acquisition of the storage and
+    /// name table locks; bulk indexing goes through here.
+    pub fn insert_entries(&self, entries: Vec<FileEntry>) {
         let mut db = self
             .storage
             .lock()
             .expect("GlobalIndex storage poisoned unexpectedly");
 
-        match self.handles.entry(uri.to_string()) {
-            Entry::Occupied(entry) => {
-                let handle = entry.get();
-                handle.set_package_name(&mut *db).to(package_name);
-                handle.set_imports(&mut *db).to(imports);
-                handle.set_classes(&mut *db).to(classes);
-                handle.set_members(&mut *db).to(members);
-            }
-            Entry::Vacant(entry) => {
-                entry.insert(FileIndex::new(
-                    &mut *db,
-                    uri.to_string(),
-                    package_name,
-                    imports,
-                    classes,
-                    members,
-                ));
+        let mut names = self
+            .names
+            .write()
+            .expect("GlobalIndex name table poisoned unexpectedly");
+        let mut classes = self
+            .class_names
+            .write()
+            .expect("GlobalIndex name table poisoned unexpectedly");
+        let mut occurrences = self
+            .occurrence_names
+            .write()
+            .expect("GlobalIndex name table poisoned unexpectedly");
+
+        for entry in entries {
+            let uri = entry.uri.as_str();
+            match self.handles.entry(entry.uri.clone()) {
+                Entry::Occupied(handle) => {
+                    let handle = handle.get();
+                    let old_classes = handle.classes(&*db);
+                    let old_members = handle.members(&*db);
+                    names.update(
+                        uri,
+                        &declared_names(&old_classes, &old_members),
+                        &declared_names(&entry.classes, &entry.members),
+                    );
+                    classes.update(
+                        uri,
+                        &class_names(&old_classes),
+                        &class_names(&entry.classes),
+                    );
+                    occurrences.update(
+                        uri,
+                        &occurrence_names(handle.occurrences(&*db)),
+                        &occurrence_names(&entry.occurrences),
+                    );
+
+                    handle.set_package_name(&mut *db).to(entry.package_name);
+                    handle.set_imports(&mut *db).to(entry.imports);
+                    handle.set_classes(&mut *db).to(entry.classes);
+                    handle.set_members(&mut *db).to(entry.members);
+                    handle.set_occurrences(&mut *db).to(entry.occurrences);
+                }
+                Entry::Vacant(handle) => {
+                    let none = BTreeSet::new();
+                    names.update(uri, &none, &declared_names(&entry.classes, &entry.members));
+                    classes.update(uri, &none, &class_names(&entry.classes));
+                    occurrences.update(uri, &none, &occurrence_names(&entry.occurrences));
+                    handle.insert(FileIndex::new(
+                        &*db,
+                        entry.uri.clone(),
+                        entry.package_name,
+                        entry.imports,
+                        entry.classes,
+                        entry.members,
+                        entry.occurrences,
+                    ));
+                }
             }
         }
     }
 
+    pub fn insert_entry(&self, entry: FileEntry) {
+        self.insert_entries(vec![entry]);
+    }
+
+    /// Forgets everything indexed for `uri`, e.g. once the file is deleted.
+    /// Returns whether it was indexed.
+    pub fn remove_file(&self, uri: &str) -> bool {
+        self.remove_where(|indexed| indexed == uri) > 0
+    }
+
+    /// Forgets every file whose URI starts with `prefix`, e.g. a directory
+    /// or a jar dropped from the classpath. Returns how many were indexed.
+    pub fn remove_files_under(&self, prefix: &str) -> usize {
+        self.remove_where(|indexed| indexed.starts_with(prefix))
+    }
+
+    fn remove_where(&self, matches: impl Fn(&str) -> bool) -> usize {
+        let db = self
+            .storage
+            .lock()
+            .expect("GlobalIndex storage poisoned unexpectedly");
+        let mut names = self
+            .names
+            .write()
+            .expect("GlobalIndex name table poisoned unexpectedly");
+        let mut classes = self
+            .class_names
+            .write()
+            .expect("GlobalIndex name table poisoned unexpectedly");
+        let mut occurrences = self
+            .occurrence_names
+            .write()
+            .expect("GlobalIndex name table poisoned unexpectedly");
+
+        let uris: Vec<String> = self
+            .handles
+            .iter()
+            .filter(|handle| matches(handle.key()))
+            .map(|handle| handle.key().clone())
+            .collect();
+        for uri in &uris {
+            // Salsa inputs cannot be deleted; dropping the handle is enough
+            // for every lookup to stop seeing the file.
+            let Some((_, handle)) = self.handles.remove(uri) else {
+                continue;
+            };
+            let old_classes = handle.classes(&*db);
+            let old_members = handle.members(&*db);
+            let none = BTreeSet::new();
+            names.update(uri, &declared_names(&old_classes, &old_members), &none);
+            classes.update(uri, &class_names(&old_classes), &none);
+            occurrences.update(uri, &occurrence_names(handle.occurrences(&*db)), &none);
+        }
+        uris.len()
+    }
+
+    /// A copy of what is indexed for `uri`.
+    pub fn file_entry(&self, uri: &str) -> Option<FileEntry> {
+        let db = self.storage.lock().ok()?;
+        let handle = self.handles.get(uri)?;
+        Some(FileEntry {
+            uri: uri.to_string(),
+            package_name: handle.package_name(&*db),
+            imports: handle.imports(&*db),
+            classes: handle.classes(&*db),
+            members: handle.members(&*db),
+            occurrences: handle.occurrences(&*db).clone(),
+        })
+    }
+
     pub fn file_info(&self, uri: &str) -> Option<FileInfo> {
         let db = self.storage.lock().ok()?;
         let handle = self.handles.get(uri)?;
@@ -138,19 +489,15 @@ impl GlobalIndex {
             Err(_) => return Vec::new(),
         };
 
-        self.handles
+        self.declaring_uris(short_name)
             .iter()
-            .flat_map(|entry| {
-                entry
-                    .value()
+            .filter_map(|uri| self.handles.get(uri))
+            .flat_map(|handle| {
+                handle
                     .classes(&*db)
                     .into_iter()
                     .filter(move |class| class.short_name == short_name)
-                    .map(|class| ClassLocation {
-                        fqcn: class.fqcn.clone(),
-                        uri: class.uri.clone(),
-                        range: class.range,
-                    })
+                    .map(ClassLocation::from)
             })
             .collect()
     }
@@ -161,28 +508,59 @@ impl GlobalIndex {
             Err(_) => return Vec::new(),
         };
 
-        self.handles
+        self.declaring_uris(name)
             .iter()
-            .flat_map(|entry| {
-                entry
-                    .value()
+            .filter_map(|uri| self.handles.get(uri))
+            .flat_map(|handle| {
+                handle
                     .members(&*db)
                     .into_iter()
                     .filter(move |member| member.name == name)
-                    .map(|member| MemberLocation {
-                        fqmn: member.fqmn.clone(),
-                        uri: member.uri.clone(),
-                        range: member.range,
-                        param_count: member.param_count,
-                        is_varargs: member.is_varargs,
-                        param_types: member.param_types.clone(),
-                        is_field: member.is_field,
-                        field_type: member.field_type.clone(),
-                    })
+                    .map(MemberLocation::from)
             })
             .collect()
     }
 
+    /// Every distinct class or member name in the index, in sorted order.
+    pub fn declared_names(&self) -> Vec<String> {
+        match self.names.read() {
+            Ok(names) => names.names.keys().cloned().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Maps every distinct class or member name through `f` under the name
+    /// table's read lock, so only the names `f` keeps are copied.
+    pub fn filter_map_declared_names<T>(&self, mut f: impl FnMut(&str) -> Option<T>) -> Vec<T> {
+        match self.names.read() {
+            Ok(names) => names.names.keys().filter_map(|name| f(name)).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Up to `limit` declared class names starting with `prefix`, in sorted
+    /// order.
+    pub fn class_names_with_prefix(&self, prefix: &str, limit: usize) -> Vec<String> {
+        match self.class_names.read() {
+            Ok(names) => names
+                .names
+                .range(prefix.to_string()..)
+                .map(|(name, _)| name)
+                .take_while(|name| name.starts_with(prefix))
+                .take(limit)
+                .cloned()
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn declaring_uris(&self, name: &str) -> Vec<String> {
+        match self.names.read() {
+            Ok(names) => names.uris(name),
+            Err(_) => Vec::new(),
+        }
+    }
+
     pub fn members_of_class(&self, fqcn: &str) -> Vec<MemberLocation> {
         let db = match self.storage.lock() {
             Ok(db) => db,
@@ -197,19 +575,312 @@ impl GlobalIndex {
                     .members(&*db)
                     .into_iter()
                     .filter(move |m| m.fqmn.starts_with(&format!("{}.", fqcn)))
-                    .map(|member| MemberLocation {
-                        fqmn: member.fqmn.clone(),
-                        uri: member.uri.clone(),
-                        range: member.range,
-                        param_count: member.param_count,
-                        is_varargs: member.is_varargs,
-                        param_types: member.param_types.clone(),
-                        is_field: member.is_field,
-                        field_type: member.field_type.clone(),
+                    .map(MemberLocation::from)
+            })
+            .collect()
+    }
+
+    /// The indexed class named `fqcn`.
+    pub fn class_by_fqcn(&self, fqcn: &str) -> Option<ClassLocation> {
+        let db = self.storage.lock().ok()?;
+        self.class_entry(&db, fqcn)
+            .map(|(class, _)| ClassLocation::from(class))
+    }
+
+    /// Direct supertypes of `fqcn` as FQCNs, superclass first. Names that
+    /// resolve to no indexed class are kept as written. An interface that
+    /// extends nothing has `java.lang.Object` as its supertype.
+    pub fn supertypes(&self, fqcn: &str) -> Vec<String> {
+        let Ok(db) = self.storage.lock() else {
+            return Vec::new();
+        };
+        let Some((class, handle)) = self.class_entry(&db, fqcn) else {
+            return Vec::new();
+        };
+
+        let mut supertypes: Vec<String> = class
+            .superclass
+            .iter()
+            .chain(&class.interfaces)
+            .map(|name| self.resolve_type_name(&db, handle, name))
+            .collect();
+        if supertypes.is_empty() && class.kind == TypeKind::Interface {
+            supertypes.push(OBJECT.to_string());
+        }
+        supertypes
+    }
+
+    /// Indexed classes that directly extend or implement `fqcn`.
+    pub fn subtypes(&self, fqcn: &str) -> Vec<ClassLocation> {
+        let Ok(db) = self.storage.lock() else {
+            return Vec::new();
+        };
+        let short_name = fqcn.rsplit('.').next().unwrap_or(fqcn);
+
+        let handles: Vec<FileIndex> = self.handles.iter().map(|entry| *entry.value()).collect();
+        let mut subtypes = Vec::new();
+        for handle in handles {
+            for class in handle.classes(&*db) {
+                let extends = class
+                    .superclass
+                    .iter()
+                    .chain(&class.interfaces)
+                    .filter(|name| name.rsplit('.').next() == Some(short_name))
+                    .any(|name| self.resolve_type_name(&db, handle, name) == fqcn);
+                if extends {
+                    subtypes.push(ClassLocation::from(class));
+                }
+            }
+        }
+        subtypes
+    }
+
+    /// `fqcn` followed by all of its supertypes, nearest first, each once.
+    pub fn supertype_closure(&self, fqcn: &str) -> Vec<String> {
+        let mut seen = vec![fqcn.to_string()];
+        let mut next = 0;
+        while next < seen.len() {
+            for supertype in self.supertypes(&seen[next]) {
+                if !seen.contains(&supertype) {
+                    seen.push(supertype);
+                }
+            }
+            next += 1;
+        }
+        seen
+    }
+
+    /// Every indexed class extending or implementing `fqcn`, directly or
+    /// through other classes, nearest first.
+    pub fn subtype_closure(&self, fqcn: &str) -> Vec<ClassLocation> {
+        let mut seen: Vec<ClassLocation> = Vec::new();
+        let mut pending = vec![fqcn.to_string()];
+        let mut next = 0;
+        while next < pending.len() {
+            for subtype in self.subtypes(&pending[next]) {
+                if subtype.fqcn != fqcn && !seen.iter().any(|s| s.fqcn == subtype.fqcn) {
+                    pending.push(subtype.fqcn.clone());
+                    seen.push(subtype);
+                }
+            }
+            next += 1;
+        }
+        seen
+    }
+
+    /// Whether `sub` is `sup` or extends or implements it.
+    pub fn is_subtype(&self, sub: &str, sup: &str) -> bool {
+        sub == sup || sup == OBJECT || self.supertype_closure(sub).iter().any(|s| s == sup)
+    }
+
+    /// Members of `fqcn` plus those it inherits. Supertype constructors,
+    /// private members and overridden methods are left out.
+    pub fn members_with_inherited(&self, fqcn: &str) -> Vec<MemberLocation> {
+        let mut members = self.members_of_class(fqcn);
+        for supertype in self.supertype_closure(fqcn).iter().skip(1) {
+            let inherited: Vec<_> = self
+                .declared_members(supertype)
+                .into_iter()
+                .filter(|m| !m.is_constructor && m.modifiers.visibility != Visibility::Private)
+                .filter(|m| {
+                    let name = member_name(&m.fqmn);
+                    !members.iter().any(|own| {
+                        member_name(&own.fqmn) == name
+                            && own.is_field == m.is_field
+                            && (m.is_field || own.param_types == m.param_types)
                     })
+                })
+                .collect();
+            members.extend(inherited);
+        }
+        members
+    }
+
+    /// Members declared by `fqcn`, found through the file declaring it.
+    fn declared_members(&self, fqcn: &str) -> Vec<MemberLocation> {
+        let Ok(db) = self.storage.lock() else {
+            return Vec::new();
+        };
+        let Some((_, handle)) = self.class_entry(&db, fqcn) else {
+            return Vec::new();
+        };
+        handle
+            .members(&*db)
+            .into_iter()
+            .filter(|m| {
+                m.fqmn
+                    .rsplit_once('.')
+                    .is_some_and(|(owner, _)| owner == fqcn)
             })
+            .map(MemberLocation::from)
             .collect()
     }
+
+    fn class_entry(&self, db: &IndexStorage, fqcn: &str) -> Option<(IndexedClass, FileIndex)> {
+        let short_name = fqcn.rsplit('.').next().unwrap_or(fqcn);
+        self.declaring_uris(short_name).iter().find_map(|uri| {
+            let handle = *self.handles.get(uri)?;
+            handle
+                .classes(db)
+                .into_iter()
+                .find(|class| class.fqcn == fqcn)
+                .map(|class| (class, handle))
+        })
+    }
+
+    /// Resolves a type name used in the file behind `handle` the way javac
+    /// would: classes of the same file, single-type imports, on-demand
+    /// imports, the file's package, then `java.lang`.
+    fn resolve_type_name(&self, db: &IndexStorage, handle: FileIndex, name: &str) -> String {
+        let short_name = name.rsplit('.').next().unwrap_or(name);
+        // `Outer.Inner` is looked up by its last segment; anything starting
+        // with a package is already qualified.
+        if name.contains('.') && !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+            return name.to_string();
+        }
+
+        let uri = handle.uri(db);
+        let candidates: Vec<(String, String)> = self
+            .declaring_uris(short_name)
+            .into_iter()
+            .filter_map(|declaring| {
+                let declaring_handle = *self.handles.get(&declaring)?;
+                let fqcns: Vec<_> = declaring_handle
+                    .classes(db)
+                    .into_iter()
+                    .filter(|class| class.short_name == short_name)
+                    .map(|class| (declaring.clone(), class.fqcn))
+                    .collect();
+                Some(fqcns)
+            })
+            .flatten()
+            .collect();
+        let find = |matches: &dyn Fn(&str, &str) -> bool| {
+            candidates
+                .iter()
+                .find(|(declaring, fqcn)| matches(declaring, fqcn))
+                .map(|(_, fqcn)| fqcn.clone())
+        };
+
+        let imports = handle.imports(db);
+        let package = handle.package_name(db);
+        let in_package = |package: &str| format!("{}.{}", package, short_name);
+        find(&|declaring, _| declaring == uri)
+            .or_else(|| find(&|_, fqcn| imports.iter().any(|i| i == fqcn)))
+            .or_else(|| find(&|_, fqcn| imports.iter().any(|i| in_package(i) == fqcn)))
+            .or_else(|| match &package {
+                Some(package) => find(&|_, fqcn| in_package(package) == fqcn),
+                None => find(&|_, fqcn| fqcn == short_name),
+            })
+            .or_else(|| find(&|_, fqcn| in_package("java.lang") == fqcn))
+            .or_else(|| {
+                imports
+                    .iter()
+                    .find(|i| i.rsplit('.').next() == Some(short_name))
+                    .cloned()
+            })
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Every recorded occurrence of `name`, across all indexed files.
+    /// Only files the occurrence table lists for `name` are looked at.
+    pub fn occurrences_of(&self, name: &str) -> Vec<lsp_types::Location> {
+        let uris = match self.occurrence_names.read() {
+            Ok(occurrences) => occurrences.uris(name),
+            Err(_) => return Vec::new(),
+        };
+        let db = match self.storage.lock() {
+            Ok(db) => db,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut locations = Vec::new();
+        for uri in uris {
+            let (Some(handle), Ok(url)) = (self.handles.get(&uri), lsp_types::Url::parse(&uri))
+            else {
+                continue;
+            };
+            locations.extend(
+                handle
+                    .occurrences(&*db)
+                    .iter()
+                    .filter(|occ| occ.name == name)
+                    .map(|occ| lsp_types::Location::new(url.clone(), occ.range)),
+            );
+        }
+        locations
+    }
+
+    /// Whether `location` is exactly the declaration of an indexed class or member.
+    pub fn is_indexed_declaration(&self, location: &lsp_types::Location) -> bool {
+        let Ok(db) = self.storage.lock() else {
+            return false;
+        };
+        let Some(handle) = self.handles.get(location.uri.as_str()) else {
+            return false;
+        };
+
+        handle
+            .members(&*db)
+            .iter()
+            .any(|m| m.range == location.range)
+            || handle
+                .classes(&*db)
+                .iter()
+                .any(|c| c.range == location.range)
+    }
+
+    /// The indexed class whose declaration spans exactly `location`.
+    pub fn class_at(&self, location: &lsp_types::Location) -> Option<ClassLocation> {
+        let db = self.storage.lock().ok()?;
+        let handle = self.handles.get(location.uri.as_str())?;
+        handle
+            .classes(&*db)
+            .into_iter()
+            .find(|c| c.range == location.range)
+            .map(ClassLocation::from)
+    }
+
+    /// The indexed member whose name sits exactly at `location`.
+    pub fn member_at(&self, location: &lsp_types::Location) -> Option<MemberLocation> {
+        let db = self.storage.lock().ok()?;
+        let handle = self.handles.get(location.uri.as_str())?;
+        handle
+            .members(&*db)
+            .into_iter()
+            .find(|m| m.range == location.range)
+            .map(MemberLocation::from)
+    }
+
+    /// Looks up the indexed class or member whose declaration was reported
+    /// at `location` (as returned by goto-definition).
+    pub fn declaration_at(&self, location: &lsp_types::Location) -> Option<DeclarationInfo> {
+        let db = self.storage.lock().ok()?;
+        let handle = self.handles.get(location.uri.as_str())?;
+
+        let member = handle
+            .members(&*db)
+            .into_iter()
+            .find(|m| m.range == location.range)
+            .map(|m| DeclarationInfo {
+                signature: m.signature,
+                doc: m.doc,
+            });
+        if member.is_some() {
+            return member;
+        }
+
+        // Class ranges span the whole declaration, so match by containment.
+        handle
+            .classes(&*db)
+            .into_iter()
+            .filter(|c| c.range.start <= location.range.start && location.range.end <= c.range.end)
+            .min_by_key(|c| (c.range.end.line - c.range.start.line, c.range.start.line))
+            .map(|c| DeclarationInfo {
+                signature: c.signature,
+                doc: c.doc,
+            })
+    }
 }
 
 impl Default for GlobalIndex {
@@ -221,4 +892,6 @@ impl Default for GlobalIndex {
 pub struct Document {
     pub text: Rope,
     pub tree: Tree,
+    /// Version the client last sent; 0 for text read from disk.
+    pub version: i32,
 }
@@ -0,0 +1,579 @@
+//! Renders parsed class files as Java source stubs: declarations with empty
+//! bodies. Libraries without sources are indexed straight from the
+//! declarations recorded while rendering, whose ranges point into the stub
+//! that goto-definition opens.
+
+use std::collections::BTreeMap;
+
+use tower_lsp::lsp_types::{Position, Range};
+
+use crate::ast::InferredType;
+use crate::classfile::{
+    ACC_ABSTRACT, ACC_ANNOTATION, ACC_BRIDGE, ACC_ENUM, ACC_FINAL, ACC_INTERFACE, ACC_NATIVE,
+    ACC_PRIVATE, ACC_PROTECTED, ACC_PUBLIC, ACC_STATIC, ACC_SYNTHETIC, ACC_VARARGS, ClassFile,
+    MemberInfo, SignatureParser, split_method_descriptor,
+};
+use crate::state::{Modifiers, TypeKind, Visibility};
+
+/// A rendered stub and the declarations in it.
+#[derive(Debug, Clone)]
+pub struct Stub {
+    pub source: String,
+    pub package: Option<String>,
+    pub imports: Vec<String>,
+    pub classes: Vec<StubClass>,
+    pub members: Vec<StubMember>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StubClass {
+    pub simple_name: String,
+    /// The whole declaration, header to closing brace.
+    pub range: Range,
+    /// Declaration header, e.g. `public interface Map<K, V>`.
+    pub signature: String,
+    pub kind: TypeKind,
+    /// FQCN of the superclass; `None` for interfaces and `Object`.
+    pub superclass: Option<String>,
+    /// FQCNs of the direct superinterfaces.
+    pub interfaces: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StubMember {
+    /// Simple name of the declaring class.
+    pub class_name: String,
+    pub name: String,
+    /// Range of the member's name.
+    pub range: Range,
+    pub signature: String,
+    pub is_field: bool,
+    pub is_constructor: bool,
+    pub is_varargs: bool,
+    /// Erased parameter types, taken from the descriptor.
+    pub param_types: Vec<InferredType>,
+    pub param_names: Vec<String>,
+    pub field_type: Option<InferredType>,
+    /// Erased return type; `None` for `void`, fields and constructors.
+    pub return_type: Option<InferredType>,
+    pub modifiers: Modifiers,
+    pub declaring_kind: TypeKind,
+}
+
+/// Renders a top-level class and its accessible nested classes, which
+/// `lookup` fetches by internal name.
+///
+/// Returns `None` for classes that are not public or protected, and for
+/// `module-info` and `package-info`.
+pub fn render_stub(class: &ClassFile, lookup: &dyn Fn(&str) -> Option<ClassFile>) -> Option<Stub> {
+    let (package, simple) = class
+        .this_class
+        .rsplit_once('/')
+        .unwrap_or(("", class.this_class.as_str()));
+    if simple == "module-info" || simple == "package-info" || !is_accessible(class.access_flags) {
+        return None;
+    }
+
+    let package = package.replace('/', ".");
+    let mut writer = StubWriter {
+        names: TypeNames {
+            package: package.clone(),
+            imports: BTreeMap::new(),
+        },
+        body: String::new(),
+        line: 0,
+        classes: Vec::new(),
+        members: Vec::new(),
+    };
+    writer.write_class(class, class.access_flags, simple, 0, lookup);
+
+    let mut header = String::new();
+    if !package.is_empty() {
+        header.push_str(&format!("package {};\n\n", package));
+    }
+    let imports: Vec<String> = writer.names.imports.values().cloned().collect();
+    for fqcn in &imports {
+        header.push_str(&format!("import {};\n", fqcn));
+    }
+    if !imports.is_empty() {
+        header.push('\n');
+    }
+
+    // Declarations were positioned within the body; move them below the header.
+    let offset = header.matches('\n').count() as u32;
+    let shift = |range: &mut Range| {
+        range.start.line += offset;
+        range.end.line += offset;
+    };
+    writer.classes.iter_mut().for_each(|c| shift(&mut c.range));
+    writer.members.iter_mut().for_each(|m| shift(&mut m.range));
+
+    Some(Stub {
+        source: header + &writer.body,
+        package: (!package.is_empty()).then_some(package),
+        imports,
+        classes: writer.classes,
+        members: writer.members,
+    })
+}
+
+/// Relative `.java` path of the stub for a top-level class entry such as
+/// `java/util/List.class`, or `None` for nested classes and non-class files.
+pub fn stub_path(class_entry: &str) -> Option<String> {
+    let stem = class_entry.strip_suffix(".class")?;
+    let simple = stem.rsplit('/').next()?;
+    if simple.contains('$') || simple == "module-info" || simple == "package-info" {
+        return None;
+    }
+    Some(format!("{}.java", stem))
+}
+
+fn is_accessible(flags: u16) -> bool {
+    flags & (ACC_PUBLIC | ACC_PROTECTED) != 0 && flags & ACC_SYNTHETIC == 0
+}
+
+/// Spells internal class names as they would appear in source, collecting
+/// the imports that make the short spelling valid.
+struct TypeNames {
+    package: String,
+    /// Imported top-level classes by simple name.
+    imports: BTreeMap<String, String>,
+}
+
+/// The FQCN the index uses for an internal class name: nested classes
+/// are qualified by their package only, like source declarations.
+fn indexed_fqcn(internal: &str) -> String {
+    let (package, binary) = internal.rsplit_once('/').unwrap_or(("", internal));
+    let simple = binary.rsplit('$').next().unwrap_or(binary);
+    if package.is_empty() {
+        simple.to_string()
+    } else {
+        format!("{}.{}", package.replace('/', "."), simple)
+    }
+}
+
+impl TypeNames {
+    fn spell(&mut self, internal: &str) -> String {
+        let (package, binary) = internal.rsplit_once('/').unwrap_or(("", internal));
+        let package = package.replace('/', ".");
+        let nested = binary.replace('$', ".");
+        let top = nested.split('.').next().unwrap_or(&nested).to_string();
+
+        if package.is_empty() || package == self.package || package == "java.lang" {
+            return nested;
+        }
+        let top_fqcn = format!("{}.{}", package, top);
+        match self.imports.get(&top) {
+            Some(existing) if *existing == top_fqcn => nested,
+            Some(_) => format!("{}.{}", package, nested),
+            None => {
+                self.imports.insert(top, top_fqcn);
+                nested
+            }
+        }
+    }
+
+    fn field_type(&mut self, member: &MemberInfo) -> String {
+        member
+            .signature
+            .as_deref()
+            .and_then(|sig| SignatureParser::new(sig, &mut |n| self.spell(n)).field_type())
+            .or_else(|| {
+                SignatureParser::new(&member.descriptor, &mut |n| self.spell(n)).field_type()
+            })
+            .unwrap_or_else(|| "Object".to_string())
+    }
+}
+
+/// Writes the class body line by line, recording where each declaration
+/// lands.
+struct StubWriter {
+    names: TypeNames,
+    body: String,
+    /// Line the next `push_line` writes.
+    line: u32,
+    classes: Vec<StubClass>,
+    members: Vec<StubMember>,
+}
+
+impl StubWriter {
+    fn push_line(&mut self, text: &str) -> u32 {
+        self.body.push_str(text);
+        self.body.push('\n');
+        self.line += 1;
+        self.line - 1
+    }
+
+    fn name_range(line: u32, column: usize, name: &str) -> Range {
+        Range::new(
+            Position::new(line, column as u32),
+            Position::new(line, (column + name.len()) as u32),
+        )
+    }
+
+    fn write_class(
+        &mut self,
+        class: &ClassFile,
+        flags: u16,
+        simple: &str,
+        depth: usize,
+        lookup: &dyn Fn(&str) -> Option<ClassFile>,
+    ) {
+        let indent = "    ".repeat(depth);
+        let is_interface = flags & ACC_INTERFACE != 0;
+        let is_annotation = flags & ACC_ANNOTATION != 0;
+        let is_enum = flags & ACC_ENUM != 0;
+        let kind = if is_annotation {
+            TypeKind::Annotation
+        } else if is_interface {
+            TypeKind::Interface
+        } else if is_enum {
+            TypeKind::Enum
+        } else if class.super_class.as_deref() == Some("java/lang/Record") {
+            TypeKind::Record
+        } else {
+            TypeKind::Class
+        };
+
+        let mut header = access_modifiers(flags);
+        if depth > 0 && flags & ACC_STATIC != 0 && !is_interface && !is_enum {
+            header.push("static");
+        }
+        if flags & ACC_ABSTRACT != 0 && !is_interface {
+            header.push("abstract");
+        }
+        if flags & ACC_FINAL != 0 && !is_enum {
+            header.push("final");
+        }
+        header.push(if is_annotation {
+            "@interface"
+        } else if is_interface {
+            "interface"
+        } else if is_enum {
+            "enum"
+        } else {
+            "class"
+        });
+
+        let names = &mut self.names;
+        let supertypes = class
+            .signature
+            .as_deref()
+            .and_then(|sig| SignatureParser::new(sig, &mut |n| names.spell(n)).class_type());
+        let (type_params, super_class, interfaces) = match supertypes {
+            Some(sig) => (sig.type_params, sig.super_class, sig.interfaces),
+            None => (
+                String::new(),
+                class
+                    .super_class
+                    .as_deref()
+                    .map(|s| names.spell(s))
+                    .unwrap_or_default(),
+                class.interfaces.iter().map(|i| names.spell(i)).collect(),
+            ),
+        };
+
+        let mut signature = format!("{} {}{}", header.join(" "), simple, type_params);
+        if !is_interface && !is_enum && !super_class.is_empty() && super_class != "Object" {
+            signature.push_str(&format!(" extends {}", super_class));
+        }
+        let interfaces: Vec<_> = interfaces
+            .into_iter()
+            .filter(|i| !(is_annotation && i == "Annotation"))
+            .collect();
+        if !interfaces.is_empty() {
+            let keyword = if is_interface {
+                "extends"
+            } else {
+                "implements"
+            };
+            signature.push_str(&format!(" {} {}", keyword, interfaces.join(", ")));
+        }
+        let start = self.push_line(&format!("{}{} {{", indent, signature));
+
+        let member_indent = "    ".repeat(depth + 1);
+        if is_enum {
+            let constants: Vec<_> = class
+                .fields
+                .iter()
+                .filter(|f| f.access_flags & ACC_ENUM != 0)
+                .collect();
+            for (i, constant) in constants.iter().enumerate() {
+                let separator = if i + 1 == constants.len() { ";" } else { "," };
+                let line =
+                    self.push_line(&format!("{}{}{}", member_indent, constant.name, separator));
+                self.members.push(StubMember {
+                    class_name: simple.to_string(),
+                    name: constant.name.clone(),
+                    range: Self::name_range(line, member_indent.len(), &constant.name),
+                    signature: format!("{} {}", simple, constant.name),
+                    is_field: true,
+                    is_constructor: false,
+                    is_varargs: false,
+                    param_types: Vec::new(),
+                    param_names: Vec::new(),
+                    field_type: Some(InferredType::from_descriptor(&constant.descriptor)),
+                    return_type: None,
+                    modifiers: member_modifiers(constant.access_flags),
+                    declaring_kind: kind,
+                });
+            }
+            if constants.is_empty() {
+                self.push_line(&format!("{};", member_indent));
+            }
+        }
+
+        for field in &class.fields {
+            if field.access_flags & ACC_ENUM != 0 || !is_accessible(field.access_flags) {
+                continue;
+            }
+            self.write_field(field, simple, kind, &member_indent);
+        }
+
+        // Constructors of inner (non-static) classes take the outer instance
+        // first in their descriptor, but not in their generic signature.
+        let inner_instance = depth > 0 && flags & ACC_STATIC == 0 && !is_interface && !is_enum;
+        for method in &class.methods {
+            if method.name == "<clinit>"
+                || method.access_flags & ACC_BRIDGE != 0
+                || !is_accessible(method.access_flags)
+            {
+                continue;
+            }
+            self.write_method(method, simple, kind, inner_instance, &member_indent);
+        }
+
+        for inner in &class.inner_classes {
+            if inner.outer_class.as_deref() != Some(class.this_class.as_str())
+                || !is_accessible(inner.access_flags)
+            {
+                continue;
+            }
+            let (Some(inner_simple), Some(inner_class)) =
+                (inner.simple_name.as_deref(), lookup(&inner.inner_class))
+            else {
+                continue;
+            };
+            self.write_class(
+                &inner_class,
+                inner.access_flags,
+                inner_simple,
+                depth + 1,
+                lookup,
+            );
+        }
+
+        let closing = format!("{}}}", indent);
+        let end = self.push_line(&closing);
+        self.classes.push(StubClass {
+            simple_name: simple.to_string(),
+            range: Range::new(
+                Position::new(start, indent.len() as u32),
+                Position::new(end, closing.len() as u32),
+            ),
+            signature,
+            kind,
+            superclass: class
+                .super_class
+                .as_deref()
+                .filter(|_| !is_interface)
+                .map(indexed_fqcn),
+            interfaces: class.interfaces.iter().map(|i| indexed_fqcn(i)).collect(),
+        });
+    }
+
+    fn write_field(&mut self, field: &MemberInfo, class_name: &str, kind: TypeKind, indent: &str) {
+        let mut mods = access_modifiers(field.access_flags);
+        if field.access_flags & ACC_STATIC != 0 {
+            mods.push("static");
+        }
+        if field.access_flags & ACC_FINAL != 0 {
+            mods.push("final");
+        }
+        let ty = self.names.field_type(field);
+        let signature = format!("{} {} {}", mods.join(" "), ty, field.name);
+        let line = self.push_line(&format!("{}{};", indent, signature));
+        let column = indent.len() + signature.len() - field.name.len();
+
+        self.members.push(StubMember {
+            class_name: class_name.to_string(),
+            name: field.name.clone(),
+            range: Self::name_range(line, column, &field.name),
+            signature,
+            is_field: true,
+            is_constructor: false,
+            is_varargs: false,
+            param_types: Vec::new(),
+            param_names: Vec::new(),
+            field_type: Some(InferredType::from_descriptor(&field.descriptor)),
+            return_type: None,
+            modifiers: member_modifiers(field.access_flags),
+            declaring_kind: kind,
+        });
+    }
+
+    fn write_method(
+        &mut self,
+        method: &MemberInfo,
+        class_name: &str,
+        kind: TypeKind,
+        inner_instance: bool,
+        indent: &str,
+    ) {
+        let in_interface = matches!(kind, TypeKind::Interface | TypeKind::Annotation);
+        let is_constructor = method.name == "<init>";
+        let Some((mut erased, returns)) = split_method_descriptor(&method.descriptor) else {
+            return;
+        };
+        if is_constructor && inner_instance && !erased.is_empty() {
+            erased.remove(0);
+        }
+
+        let names = &mut self.names;
+        let from_signature = method
+            .signature
+            .as_deref()
+            .and_then(|sig| SignatureParser::new(sig, &mut |n| names.spell(n)).method_type())
+            .filter(|ty| ty.params.len() == erased.len());
+        let Some(mut ty) = from_signature.or_else(|| {
+            SignatureParser::new(&method.descriptor, &mut |n| names.spell(n)).method_type()
+        }) else {
+            return;
+        };
+        if ty.params.len() > erased.len() {
+            ty.params.remove(0);
+        }
+
+        let flags = method.access_flags;
+        let is_abstract = flags & ACC_ABSTRACT != 0;
+        let is_varargs = flags & ACC_VARARGS != 0;
+        let mut mods = Vec::new();
+        if !in_interface {
+            mods.extend(access_modifiers(flags));
+        } else if flags & ACC_PROTECTED != 0 {
+            mods.push("protected");
+        }
+        if flags & ACC_STATIC != 0 {
+            mods.push("static");
+        } else if in_interface && !is_abstract {
+            mods.push("default");
+        }
+        if is_abstract && !in_interface {
+            mods.push("abstract");
+        }
+        if flags & ACC_FINAL != 0 {
+            mods.push("final");
+        }
+        if flags & ACC_NATIVE != 0 {
+            mods.push("native");
+        }
+
+        let param_names: Vec<String> = (0..ty.params.len())
+            .map(|i| {
+                method
+                    .param_names
+                    .get(i)
+                    .filter(|n| !n.is_empty())
+                    .cloned()
+                    .unwrap_or_else(|| format!("arg{}", i))
+            })
+            .collect();
+        let params: Vec<_> = ty
+            .params
+            .iter()
+            .zip(&param_names)
+            .enumerate()
+            .map(|(i, (param, name))| match param.strip_suffix("[]") {
+                Some(element) if is_varargs && i + 1 == ty.params.len() => {
+                    format!("{}... {}", element, name)
+                }
+                _ => format!("{} {}", param, name),
+            })
+            .collect();
+
+        let mut signature = String::new();
+        if !mods.is_empty() {
+            signature.push_str(&mods.join(" "));
+            signature.push(' ');
+        }
+        if !ty.type_params.is_empty() {
+            signature.push_str(&ty.type_params);
+            signature.push(' ');
+        }
+        let name = if is_constructor {
+            class_name.to_string()
+        } else {
+            signature.push_str(&ty.return_type);
+            signature.push(' ');
+            method.name.clone()
+        };
+        let column = indent.len() + signature.len();
+        signature.push_str(&format!("{}({})", name, params.join(", ")));
+
+        let body = if is_abstract || flags & ACC_NATIVE != 0 {
+            ";"
+        } else {
+            " {}"
+        };
+        let line = self.push_line(&format!("{}{}{}", indent, signature, body));
+
+        self.members.push(StubMember {
+            class_name: class_name.to_string(),
+            range: Self::name_range(line, column, &name),
+            name,
+            signature,
+            is_field: false,
+            is_constructor,
+            is_varargs,
+            // Like source declarations, varargs record their element type.
+            param_types: erased
+                .iter()
+                .enumerate()
+                .map(|(i, d)| match d.strip_prefix('[') {
+                    Some(element) if is_varargs && i + 1 == erased.len() => {
+                        InferredType::from_descriptor(element)
+                    }
+                    _ => InferredType::from_descriptor(d),
+                })
+                .collect(),
+            param_names,
+            field_type: None,
+            return_type: (!is_constructor && returns != "V")
+                .then(|| InferredType::from_descriptor(returns)),
+            modifiers: Modifiers {
+                is_default: in_interface && !is_abstract && flags & (ACC_STATIC | ACC_PRIVATE) == 0,
+                ..member_modifiers(flags)
+            },
+            declaring_kind: kind,
+        });
+    }
+}
+
+fn member_modifiers(flags: u16) -> Modifiers {
+    Modifiers {
+        visibility: if flags & ACC_PUBLIC != 0 {
+            Visibility::Public
+        } else if flags & ACC_PROTECTED != 0 {
+            Visibility::Protected
+        } else if flags & ACC_PRIVATE != 0 {
+            Visibility::Private
+        } else {
+            Visibility::Package
+        },
+        is_static: flags & ACC_STATIC != 0,
+        is_abstract: flags & ACC_ABSTRACT != 0,
+        is_default: false,
+    }
+}
+
+fn access_modifiers(flags: u16) -> Vec<&'static str> {
+    if flags & ACC_PUBLIC != 0 {
+        vec!["public"]
+    } else if flags & ACC_PROTECTED != 0 {
+        vec!["protected"]
+    } else if flags & ACC_PRIVATE != 0 {
+        vec!["private"]
+    } else {
+        Vec::new()
+    }
+}
@@ -4,8 +4,9 @@ use crate::{
     state::GlobalIndex,
 };
 use ropey::Rope;
+use std::collections::HashSet;
 use tower_lsp::lsp_types::{Position, Range};
-use tree_sitter::Node;
+use tree_sitter::{Node, Point};
 
 pub fn get_node_text(node: tree_sitter::Node, rope: &Rope) -> String {
     let start_char = rope.byte_to_char(node.start_byte());
@@ -13,6 +14,24 @@ pub fn get_node_text(node: tree_sitter::Node, rope: &Rope) -> String {
     rope.slice(start_char..end_char).to_string()
 }
 
+/// Text of every `identifier` and `type_identifier` under `node`, skipping
+/// subtrees whose kind is listed in `skip`.
+pub fn collect_identifiers(node: Node, rope: &Rope, skip: &[&str]) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        if skip.contains(&node.kind()) {
+            continue;
+        }
+        if matches!(node.kind(), "identifier" | "type_identifier") {
+            names.insert(get_node_text(node, rope));
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+    names
+}
+
 pub fn node_range(node: tree_sitter::Node, _rope: &Rope) -> Range {
     let start_pos = node.start_position();
     let end_pos = node.end_position();
@@ -23,6 +42,29 @@ pub fn node_range(node: tree_sitter::Node, _rope: &Rope) -> Range {
     }
 }
 
+/// Char index of an LSP position, clamped the way the spec asks: past the
+/// end of a line means its end, past the last line the end of the text.
+pub fn position_to_char(text: &Rope, position: Position) -> usize {
+    let line = position.line as usize;
+    if line >= text.len_lines() {
+        return text.len_chars();
+    }
+    let content = text.line(line);
+    let mut len = content.len_chars();
+    for ending in ['\n', '\r'] {
+        if len > 0 && content.char(len - 1) == ending {
+            len -= 1;
+        }
+    }
+    text.line_to_char(line) + (position.character as usize).min(len)
+}
+
+/// Tree-sitter point (row, byte column) of a byte offset.
+pub fn byte_to_point(text: &Rope, byte: usize) -> Point {
+    let row = text.byte_to_line(byte);
+    Point::new(row, byte - text.line_to_byte(row))
+}
+
 pub fn get_node_at_pos<'a>(
     tree: &'a tree_sitter::Tree,
     rope: &Rope,
@@ -149,7 +191,11 @@ pub fn search_fields_in_class(class_body: Node, target_name: &str, rope: &Rope)
     None
 }
 
-pub fn calculate_score(arg_type: &InferredType, param_type: &InferredType) -> i32 {
+pub fn calculate_score(
+    arg_type: &InferredType,
+    param_type: &InferredType,
+    index: &GlobalIndex,
+) -> i32 {
     if arg_type == param_type {
         return 100;
     }
@@ -170,13 +216,51 @@ pub fn calculate_score(arg_type: &InferredType, param_type: &InferredType) -> i3
         (InferredType::Double, InferredType::Int) => -100,
 
         (InferredType::Class(a), InferredType::Class(b)) => {
-            if a == b { 100 } else { 0 } // TODO: handle class inherits
+            if a == b {
+                100
+            } else {
+                subtype_score(a, b, index).unwrap_or(0)
+            }
+        }
+        (InferredType::String, InferredType::Class(b)) => {
+            subtype_score("String", b, index).unwrap_or(-100)
         }
 
         _ => -100,
     }
 }
 
+/// How well an argument of class `sub` fits a parameter of class `sup`
+/// when `sub` extends or implements it: nearer supertypes score higher,
+/// `Object` lowest. `None` when the index knows of no such relation.
+fn subtype_score(sub: &str, sup: &str, index: &GlobalIndex) -> Option<i32> {
+    let erase = |name: &str| {
+        let name = name.split('<').next().unwrap_or(name).trim();
+        name.rsplit('.').next().unwrap_or(name).to_string()
+    };
+    let (sub, sup) = (erase(sub), erase(sup));
+    if sup == "Object" {
+        return Some(10);
+    }
+
+    let targets: Vec<_> = index
+        .classes_by_short_name(&sup)
+        .into_iter()
+        .map(|c| c.fqcn)
+        .collect();
+    index
+        .classes_by_short_name(&sub)
+        .iter()
+        .filter_map(|class| {
+            index
+                .supertype_closure(&class.fqcn)
+                .iter()
+                .position(|s| targets.contains(s))
+        })
+        .min()
+        .map(|depth| 60 - (depth as i32).min(40))
+}
+
 fn search_class_member(
     class_body: Node,
     target_name: &str,
@@ -192,10 +276,8 @@ fn search_class_member(
     let mut max_score = -9999;
 
     for child in class_body.children(&mut cursor) {
-        if prefer_field {
-            if let Some(range) = find_field_in_declaration(child, target_name, rope) {
-                return Some(range);
-            }
+        if prefer_field && let Some(range) = find_field_in_declaration(child, target_name, rope) {
+            return Some(range);
         }
 
         if child.kind() == "method_declaration" {
@@ -247,7 +329,7 @@ fn search_class_member(
 
                 let param_type = parse_java_type(def_type_node, rope);
 
-                let score = calculate_score(&arg_type, &param_type);
+                let score = calculate_score(&arg_type, &param_type, index);
 
                 if score < 0 {
                     mismatch = true;
@@ -272,10 +354,8 @@ fn search_class_member(
             }
         }
 
-        if !prefer_field {
-            if let Some(range) = find_field_in_declaration(child, target_name, rope) {
-                return Some(range);
-            }
+        if !prefer_field && let Some(range) = find_field_in_declaration(child, target_name, rope) {
+            return Some(range);
         }
     }
 
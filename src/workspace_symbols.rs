@@ -0,0 +1,171 @@
+use tower_lsp::lsp_types::{Location, SymbolInformation, SymbolKind, Url};
+
+use crate::state::{ClassLocation, GlobalIndex, MemberLocation, TypeKind};
+
+/// Upper bound on the symbols returned for a single query.
+pub const MAX_WORKSPACE_SYMBOLS: usize = 256;
+
+/// Only the best-scoring names are expanded into declarations; a short query
+/// such as `a` matches most of the JDK.
+const MAX_CANDIDATE_NAMES: usize = 1024;
+
+/// Searches indexed classes and members whose name fuzzily matches `query`.
+///
+/// Results are ranked workspace files first, then library sources, then the
+/// JDK, and by match quality within each group. An empty query returns
+/// nothing rather than dumping the whole index.
+pub fn workspace_symbols(index: &GlobalIndex, query: &str) -> Vec<SymbolInformation> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut names: Vec<(i32, String)> =
+        index.filter_map_declared_names(|name| Some((fuzzy_score(query, name)?, name.to_string())));
+    names.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.len().cmp(&b.1.len())));
+    names.truncate(MAX_CANDIDATE_NAMES);
+
+    let mut ranked = Vec::new();
+    for (score, name) in &names {
+        for class in index.classes_by_short_name(name) {
+            ranked.push((uri_rank(&class.uri), -score, class_symbol(name, class)));
+        }
+        for member in index.members_by_name(name) {
+            if member.is_constructor {
+                continue;
+            }
+            ranked.push((uri_rank(&member.uri), -score, member_symbol(name, member)));
+        }
+    }
+
+    ranked.sort_by(|a, b| {
+        (a.0, a.1, a.2.name.len(), &a.2.name).cmp(&(b.0, b.1, b.2.name.len(), &b.2.name))
+    });
+    ranked
+        .into_iter()
+        .take(MAX_WORKSPACE_SYMBOLS)
+        .map(|(_, _, symbol)| symbol)
+        .collect()
+}
+
+/// Scores `candidate` against `query`, or `None` if the query characters do
+/// not appear in order.
+///
+/// Matching is case-insensitive. Characters landing on a word start (the
+/// first character, an upper-case letter after a lower-case one, or the
+/// character after `_`/`$`) and runs of consecutive characters score higher,
+/// so `HSM` prefers `HashSetMap` over `hashsum`.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let query: Vec<char> = query.chars().collect();
+    let cand: Vec<char> = candidate.chars().collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    if query.len() > cand.len() {
+        return None;
+    }
+
+    const CONSECUTIVE: i32 = 5;
+
+    // best[j]: best score with the current query character matched at cand[j].
+    let mut best: Vec<Option<i32>> = vec![None; cand.len()];
+    for (i, &q) in query.iter().enumerate() {
+        let mut next = vec![None; cand.len()];
+        let mut running: Option<i32> = if i == 0 { Some(0) } else { None };
+        for j in 0..cand.len() {
+            let prev_consecutive = (i > 0 && j > 0)
+                .then(|| best[j - 1])
+                .flatten()
+                .map(|s| s + CONSECUTIVE);
+            if chars_match(q, cand[j]) {
+                let base = running.max(prev_consecutive);
+                next[j] = base.map(|s| s + char_score(q, &cand, j));
+            }
+            if i > 0 {
+                running = running.max(best[j]);
+            }
+        }
+        best = next;
+    }
+
+    let mut score = best.into_iter().flatten().max()?;
+    if query.iter().collect::<String>().to_lowercase() == candidate.to_lowercase() {
+        score += 100;
+    }
+    Some(score - (cand.len() - query.len()) as i32 / 4)
+}
+
+fn chars_match(q: char, c: char) -> bool {
+    q == c || q.to_lowercase().eq(c.to_lowercase())
+}
+
+fn char_score(q: char, cand: &[char], j: usize) -> i32 {
+    let c = cand[j];
+    let boundary = if j == 0 {
+        10
+    } else {
+        let prev = cand[j - 1];
+        if prev == '_' || prev == '$' || (c.is_uppercase() && !prev.is_uppercase()) {
+            8
+        } else if c.is_ascii_digit() && !prev.is_ascii_digit() {
+            4
+        } else {
+            0
+        }
+    };
+    let exact_case = if q == c { 1 } else { 0 };
+    1 + boundary + exact_case
+}
+
+fn uri_rank(uri: &Url) -> u8 {
+    match uri.scheme() {
+        "file" | "untitled" => 0,
+        "jrsls-std" => 2,
+        _ => 1,
+    }
+}
+
+fn class_kind(kind: TypeKind) -> SymbolKind {
+    match kind {
+        TypeKind::Interface | TypeKind::Annotation => SymbolKind::INTERFACE,
+        TypeKind::Enum => SymbolKind::ENUM,
+        TypeKind::Class | TypeKind::Record => SymbolKind::CLASS,
+    }
+}
+
+fn container(qualified: &str, name: &str) -> Option<String> {
+    qualified
+        .strip_suffix(name)
+        .and_then(|prefix| prefix.strip_suffix('.'))
+        .filter(|prefix| !prefix.is_empty())
+        .map(str::to_string)
+}
+
+#[allow(deprecated)]
+fn class_symbol(name: &str, class: ClassLocation) -> SymbolInformation {
+    SymbolInformation {
+        name: name.to_string(),
+        kind: class_kind(class.kind),
+        tags: None,
+        deprecated: None,
+        container_name: container(&class.fqcn, name),
+        location: Location::new(class.uri, class.range),
+    }
+}
+
+#[allow(deprecated)]
+fn member_symbol(name: &str, member: MemberLocation) -> SymbolInformation {
+    let kind = if member.is_field {
+        SymbolKind::FIELD
+    } else {
+        SymbolKind::METHOD
+    };
+    SymbolInformation {
+        name: name.to_string(),
+        kind,
+        tags: None,
+        deprecated: None,
+        container_name: container(&member.fqmn, name),
+        location: Location::new(member.uri, member.range),
+    }
+}
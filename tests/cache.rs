@@ -0,0 +1,100 @@
+mod common;
+
+use std::path::PathBuf;
+
+use jrsls::{
+    cache::{CachedSource, FileStamp, IndexCache},
+    state::GlobalIndex,
+};
+
+use common::parse_and_index;
+
+struct Fixture {
+    dir: PathBuf,
+}
+
+impl Fixture {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("jrsls-cache-{}-{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        Self { dir }
+    }
+
+    fn cache(&self) -> IndexCache {
+        IndexCache::new(self.dir.join("cache"))
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+const GREETER: &str = "package demo;\n\nimport java.util.List;\n\npublic class Greeter {\n    int count;\n    String greet(String name) { return name; }\n}\n";
+
+#[test]
+fn workspace_entries_round_trip() {
+    let fx = Fixture::new("workspace");
+    let path = fx.dir.join("Greeter.java");
+    std::fs::write(&path, GREETER).unwrap();
+    let uri = "file:///workspace/Greeter.java";
+
+    let index = GlobalIndex::new();
+    parse_and_index(GREETER, uri, &index);
+    let entry = index.file_entry(uri).unwrap();
+    let stamp = FileStamp::of(&path).unwrap();
+    fx.cache().store_workspace(
+        &fx.dir,
+        vec![CachedSource {
+            path: path.clone(),
+            stamp,
+            entry: entry.clone(),
+        }],
+    );
+
+    let mut loaded = fx.cache().load_workspace(&fx.dir);
+    let source = loaded.remove(&path).unwrap();
+    assert_eq!(source.stamp, stamp);
+    assert_eq!(source.entry, entry);
+    assert!(
+        fx.cache()
+            .load_workspace(&fx.dir.join("elsewhere"))
+            .is_empty()
+    );
+
+    let warm = GlobalIndex::new();
+    warm.insert_entry(source.entry);
+    assert_eq!(
+        warm.classes_by_short_name("Greeter")[0].fqcn,
+        "demo.Greeter"
+    );
+    let greet = &warm.members_by_name("greet")[0];
+    assert_eq!(greet.param_names, vec!["name"]);
+    assert_eq!(
+        warm.file_info(uri).unwrap().imports,
+        vec!["java.util.List".to_string()]
+    );
+}
+
+#[test]
+fn archive_entries_expire_when_the_archive_changes() {
+    let fx = Fixture::new("archive");
+    let archive = fx.dir.join("lib.jar");
+    std::fs::write(&archive, "v1").unwrap();
+    let cache = fx.cache();
+    assert!(cache.load_archive(&archive).is_none());
+
+    let index = GlobalIndex::new();
+    let uri = "jrsls-jar:///lib.jar!/demo/Greeter.java";
+    parse_and_index(GREETER, uri, &index);
+    cache.store_archive(&archive, vec![index.file_entry(uri).unwrap()]);
+
+    let entries = cache.load_archive(&archive).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].uri, uri);
+
+    std::fs::write(&archive, "v2 with a different size").unwrap();
+    assert!(cache.load_archive(&archive).is_none());
+}
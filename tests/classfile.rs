@@ -0,0 +1,515 @@
+use std::io::Write;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::Location;
+
+use jrsls::{
+    ast::InferredType,
+    classfile::{ACC_INTERFACE, ACC_PUBLIC, ACC_STATIC, ACC_VARARGS, ClassFile, SignatureParser},
+    indexer::Indexer,
+    library::{
+        JAR_SCHEME, LibraryFile, SourceArchiveRegistry, attached_sources, count_entries,
+        for_each_attached_source, for_each_class_stub, for_each_source_file, jar_entry_uri,
+    },
+    state::GlobalIndex,
+    stubs::render_stub,
+};
+
+/// Assembles just enough of a class file for the reader: constant pool,
+/// supertypes, members and `Signature`/`InnerClasses` attributes.
+#[derive(Default)]
+struct ClassBuilder {
+    pool: Vec<Vec<u8>>,
+    body: Vec<u8>,
+}
+
+struct Member<'a> {
+    flags: u16,
+    name: &'a str,
+    descriptor: &'a str,
+    signature: Option<&'a str>,
+}
+
+impl ClassBuilder {
+    fn utf8(&mut self, s: &str) -> u16 {
+        let mut entry = vec![1];
+        entry.extend((s.len() as u16).to_be_bytes());
+        entry.extend(s.as_bytes());
+        self.push(entry)
+    }
+
+    fn class(&mut self, name: &str) -> u16 {
+        let name = self.utf8(name);
+        let mut entry = vec![7];
+        entry.extend(name.to_be_bytes());
+        self.push(entry)
+    }
+
+    fn push(&mut self, entry: Vec<u8>) -> u16 {
+        self.pool.push(entry);
+        self.pool.len() as u16
+    }
+
+    fn u2(&mut self, v: u16) {
+        self.body.extend(v.to_be_bytes());
+    }
+
+    fn signature_attr(&mut self, signature: &str) {
+        let attr = self.utf8("Signature");
+        let value = self.utf8(signature);
+        self.u2(attr);
+        self.body.extend(2u32.to_be_bytes());
+        self.u2(value);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build(
+        mut self,
+        flags: u16,
+        name: &str,
+        interfaces: &[&str],
+        fields: &[Member],
+        methods: &[Member],
+        inner: &[(&str, &str, &str, u16)],
+    ) -> Vec<u8> {
+        self.u2(flags);
+        let this = self.class(name);
+        self.u2(this);
+        let sup = self.class("java/lang/Object");
+        self.u2(sup);
+        self.u2(interfaces.len() as u16);
+        for i in interfaces {
+            let idx = self.class(i);
+            self.u2(idx);
+        }
+        for members in [fields, methods] {
+            self.u2(members.len() as u16);
+            for m in members {
+                self.u2(m.flags);
+                let n = self.utf8(m.name);
+                self.u2(n);
+                let d = self.utf8(m.descriptor);
+                self.u2(d);
+                match m.signature {
+                    Some(sig) => {
+                        self.u2(1);
+                        self.signature_attr(sig);
+                    }
+                    None => self.u2(0),
+                }
+            }
+        }
+        if inner.is_empty() {
+            self.u2(0);
+        } else {
+            self.u2(1);
+            let attr = self.utf8("InnerClasses");
+            self.u2(attr);
+            self.body.extend((2 + 8 * inner.len() as u32).to_be_bytes());
+            self.u2(inner.len() as u16);
+            for (inner_name, outer_name, simple, flags) in inner {
+                let i = self.class(inner_name);
+                let o = self.class(outer_name);
+                let s = self.utf8(simple);
+                for v in [i, o, s, *flags] {
+                    self.u2(v);
+                }
+            }
+        }
+
+        let mut out = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61];
+        out.extend((self.pool.len() as u16 + 1).to_be_bytes());
+        for entry in &self.pool {
+            out.extend(entry);
+        }
+        out.extend(self.body);
+        out
+    }
+}
+
+fn greeter_class() -> Vec<u8> {
+    ClassBuilder::default().build(
+        ACC_PUBLIC,
+        "demo/Greeter",
+        &["java/lang/Runnable"],
+        &[Member {
+            flags: ACC_PUBLIC | ACC_STATIC,
+            name: "count",
+            descriptor: "I",
+            signature: None,
+        }],
+        &[
+            Member {
+                flags: ACC_PUBLIC,
+                name: "<init>",
+                descriptor: "()V",
+                signature: None,
+            },
+            Member {
+                flags: ACC_PUBLIC | ACC_VARARGS,
+                name: "greet",
+                descriptor: "([Ljava/lang/String;)Ljava/lang/String;",
+                signature: None,
+            },
+            Member {
+                flags: ACC_PUBLIC | ACC_STATIC,
+                name: "first",
+                descriptor: "(Ljava/util/List;)Ljava/lang/Object;",
+                signature: Some("<T:Ljava/lang/Object;>(Ljava/util/List<+TT;>;)TT;"),
+            },
+            Member {
+                flags: 0x0002,
+                name: "secret",
+                descriptor: "()V",
+                signature: None,
+            },
+        ],
+        &[(
+            "demo/Greeter$Style",
+            "demo/Greeter",
+            "Style",
+            ACC_PUBLIC | ACC_STATIC | ACC_INTERFACE,
+        )],
+    )
+}
+
+fn style_class() -> Vec<u8> {
+    ClassBuilder::default().build(
+        ACC_PUBLIC | ACC_INTERFACE | 0x0400,
+        "demo/Greeter$Style",
+        &[],
+        &[],
+        &[Member {
+            flags: ACC_PUBLIC | 0x0400,
+            name: "apply",
+            descriptor: "(Ljava/lang/String;)Ljava/lang/String;",
+            signature: None,
+        }],
+        &[(
+            "demo/Greeter$Style",
+            "demo/Greeter",
+            "Style",
+            ACC_PUBLIC | ACC_STATIC | ACC_INTERFACE,
+        )],
+    )
+}
+
+#[test]
+fn reads_class_structure() {
+    let class = ClassFile::parse(&greeter_class()).unwrap();
+    assert_eq!(class.this_class, "demo/Greeter");
+    assert_eq!(class.super_class.as_deref(), Some("java/lang/Object"));
+    assert_eq!(class.interfaces, vec!["java/lang/Runnable"]);
+    assert_eq!(class.fields[0].name, "count");
+    assert_eq!(class.methods.len(), 4);
+    assert_eq!(class.inner_classes[0].simple_name.as_deref(), Some("Style"));
+
+    assert!(ClassFile::parse(b"not a class").is_err());
+    assert!(ClassFile::parse(&greeter_class()[..40]).is_err());
+}
+
+#[test]
+fn parses_descriptors_and_generic_signatures() {
+    let mut spell = |internal: &str| internal.rsplit('/').next().unwrap().replace('$', ".");
+    let ty = SignatureParser::new("([[IJLjava/util/Map$Entry;)V", &mut spell)
+        .method_type()
+        .unwrap();
+    assert_eq!(ty.params, vec!["int[][]", "long", "Map.Entry"]);
+    assert_eq!(ty.return_type, "void");
+
+    let ty = SignatureParser::new(
+        "<K::Ljava/lang/Comparable<-TK;>;V:Ljava/lang/Object;>(Ljava/util/Map<TK;*>;)TV;",
+        &mut spell,
+    )
+    .method_type()
+    .unwrap();
+    assert_eq!(ty.type_params, "<K extends Comparable<? super K>, V>");
+    assert_eq!(ty.params, vec!["Map<K, ?>"]);
+    assert_eq!(ty.return_type, "V");
+
+    assert!(
+        SignatureParser::new("Ljava/lang/String", &mut spell)
+            .field_type()
+            .is_none()
+    );
+}
+
+#[test]
+fn renders_indexable_stub() {
+    let greeter = ClassFile::parse(&greeter_class()).unwrap();
+    let style = ClassFile::parse(&style_class()).unwrap();
+    let lookup = |name: &str| (name == "demo/Greeter$Style").then(|| style.clone());
+    let stub = render_stub(&greeter, &lookup).expect("public class");
+    let source = stub.source.clone();
+
+    assert!(
+        source.starts_with("package demo;\n\nimport java.util.List;\n"),
+        "{}",
+        source
+    );
+    assert!(
+        source.contains("public class Greeter implements Runnable {"),
+        "{}",
+        source
+    );
+    assert!(source.contains("public static int count;"), "{}", source);
+    assert!(
+        source.contains("public String greet(String... arg0) {}"),
+        "{}",
+        source
+    );
+    assert!(
+        source.contains("public static <T> T first(List<? extends T> arg0) {}"),
+        "{}",
+        source
+    );
+    assert!(!source.contains("secret"), "{}", source);
+    assert!(source.contains("public interface Style {"), "{}", source);
+    assert!(source.contains("String apply(String arg0);"), "{}", source);
+
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&tree_sitter_java::LANGUAGE.into())
+        .unwrap();
+    let tree = parser.parse(&source, None).unwrap();
+    assert!(!tree.root_node().has_error(), "{}", source);
+
+    let index = GlobalIndex::new();
+    Indexer::update_stub(&index, "jrsls-std:///demo/Greeter.java", &stub);
+
+    let greet = &index.members_by_name("greet")[0];
+    assert_eq!(greet.fqmn, "demo.Greeter.greet");
+    assert!(greet.is_varargs);
+    assert_eq!(greet.param_types, vec![InferredType::String]);
+    assert_eq!(
+        index.classes_by_short_name("Greeter")[0].fqcn,
+        "demo.Greeter"
+    );
+
+    // Recorded ranges must point at the names in the rendered source.
+    let rope = Rope::from_str(&source);
+    for member in index.members_of_class("demo.Greeter") {
+        let line = rope.line(member.range.start.line as usize).to_string();
+        let name = member.fqmn.rsplit('.').next().unwrap();
+        let start = member.range.start.character as usize;
+        let end = member.range.end.character as usize;
+        let expected = if name == "<init>" { "Greeter" } else { name };
+        assert_eq!(&line[start..end], expected, "{}", line);
+    }
+    let style = &index.classes_by_short_name("Style")[0];
+    assert_eq!(style.fqcn, "demo.Style");
+    assert!(
+        rope.line(style.range.start.line as usize)
+            .to_string()
+            .contains("interface Style")
+    );
+    assert_eq!(
+        index.supertypes("demo.Greeter"),
+        vec!["java.lang.Object", "java.lang.Runnable"]
+    );
+    assert_eq!(index.supertypes("demo.Style"), vec!["java.lang.Object"]);
+}
+
+fn greeter_archive(entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::FileOptions::default();
+    for (name, bytes) in entries {
+        zip.start_file(*name, options).unwrap();
+        zip.write_all(bytes).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+#[test]
+fn reads_stubs_from_jmod_with_header() {
+    let dir = std::env::temp_dir().join(format!("jrsls-classfile-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let jmod = dir.join("demo.jmod");
+
+    let archive = greeter_archive(&[
+        ("classes/demo/Greeter.class", greeter_class()),
+        ("classes/demo/Greeter$Style.class", style_class()),
+        ("lib/libdemo.so", b"\x7fELF".to_vec()),
+    ]);
+    let mut file = b"JM\x01\x00".to_vec();
+    file.extend(archive);
+    std::fs::write(&jmod, file).unwrap();
+
+    let mut stubs = Vec::new();
+    for_each_class_stub(&jmod, |path, stub| stubs.push((path, stub.source))).unwrap();
+    // Progress estimates count top-level classes only, like the stubs.
+    assert_eq!(count_entries(&jmod, "class").unwrap(), 1);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(
+        stubs.len(),
+        1,
+        "nested classes render inside their outer class"
+    );
+    assert_eq!(stubs[0].0, "demo/Greeter.java");
+    assert!(stubs[0].1.contains("interface Style"));
+}
+
+#[test]
+fn indexes_classpath_jar() {
+    let dir = std::env::temp_dir().join(format!("jrsls-jar-index-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let jar = dir.join("greeter lib.jar");
+    std::fs::write(
+        &jar,
+        greeter_archive(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\n".to_vec()),
+            ("demo/Greeter.class", greeter_class()),
+            ("demo/Greeter$Style.class", style_class()),
+        ]),
+    )
+    .unwrap();
+
+    let index = GlobalIndex::new();
+    for_each_class_stub(&jar, |path, stub| {
+        let uri = jar_entry_uri(&jar, &path).unwrap();
+        Indexer::update_stub(&index, uri.as_str(), &stub);
+    })
+    .unwrap();
+
+    let greeter = &index.classes_by_short_name("Greeter")[0];
+    assert_eq!(greeter.uri.scheme(), JAR_SCHEME);
+    assert!(
+        greeter
+            .uri
+            .path()
+            .ends_with("greeter%20lib.jar!/demo/Greeter.java"),
+        "{}",
+        greeter.uri
+    );
+
+    let count = index
+        .members_of_class("demo.Greeter")
+        .into_iter()
+        .find(|m| m.fqmn == "demo.Greeter.count")
+        .unwrap();
+    assert_eq!(count.field_type, Some(InferredType::Int));
+    let apply = &index.members_by_name("apply")[0];
+    assert_eq!(apply.param_types, vec![InferredType::String]);
+
+    let registry = SourceArchiveRegistry::new();
+    registry.register_jar(jar.clone(), None);
+    let materialized = registry
+        .materialize(&Location::new(greeter.uri.clone(), greeter.range))
+        .expect("stub served from the jar");
+    let source = std::fs::read_to_string(materialized.uri.to_file_path().unwrap()).unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(source.contains("public class Greeter implements Runnable {"));
+}
+
+#[test]
+fn prefers_attached_sources_jar() {
+    let dir = std::env::temp_dir().join(format!("jrsls-sources-jar-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let jar = dir.join("greeter-1.0.jar");
+    let other = dir.join("other-1.0.jar");
+    let source = "package demo;\n\n/** Says hello. */\npublic class Greeter {\n    public String greet(String... names) { return \"hi\"; }\n}\n";
+    for path in [&jar, &other] {
+        std::fs::write(
+            path,
+            greeter_archive(&[
+                ("demo/Greeter.class", greeter_class()),
+                ("demo/Greeter$Style.class", style_class()),
+            ]),
+        )
+        .unwrap();
+    }
+    std::fs::write(
+        dir.join("greeter-1.0-sources.jar"),
+        greeter_archive(&[("demo/Greeter.java", source.as_bytes().to_vec())]),
+    )
+    .unwrap();
+
+    let sources = attached_sources(&jar).expect("sibling sources jar");
+    assert!(attached_sources(&other).is_none());
+
+    let mut files = Vec::new();
+    for_each_source_file(&sources, |name, contents| {
+        files.push((name.to_string(), contents))
+    })
+    .unwrap();
+    assert_eq!(
+        files,
+        vec![("demo/Greeter.java".to_string(), source.to_string())]
+    );
+
+    // Both jars are served side by side, each from its own archive.
+    let registry = SourceArchiveRegistry::new();
+    registry.register_jar(jar.clone(), Some(sources));
+    registry.register_jar(other.clone(), None);
+    let read = |jar: &std::path::Path| {
+        let uri = jar_entry_uri(jar, "demo/Greeter.java").unwrap();
+        let location = registry
+            .materialize(&Location::new(uri, Default::default()))
+            .expect("materialized");
+        std::fs::read_to_string(location.uri.to_file_path().unwrap()).unwrap()
+    };
+    let from_sources = read(&jar);
+    let from_stub = read(&other);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(from_sources, source);
+    assert!(from_stub.contains("public String greet(String... arg0) {}"));
+}
+
+#[test]
+fn stubs_classes_missing_from_sources_jar() {
+    let dir = std::env::temp_dir().join(format!("jrsls-partial-sources-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let jar = dir.join("greeter-1.0.jar");
+    let sources = dir.join("greeter-1.0-sources.jar");
+    let source = "package demo;\n\npublic class Greeter {}\n";
+    let shaded = ClassBuilder::default().build(ACC_PUBLIC, "demo/Shaded", &[], &[], &[], &[]);
+    std::fs::write(
+        &jar,
+        greeter_archive(&[
+            ("demo/Greeter.class", greeter_class()),
+            ("demo/Shaded.class", shaded),
+        ]),
+    )
+    .unwrap();
+    std::fs::write(
+        &sources,
+        greeter_archive(&[("demo/Greeter.java", source.as_bytes().to_vec())]),
+    )
+    .unwrap();
+
+    let mut files = Vec::new();
+    for_each_attached_source(&jar, &sources, |path, file| {
+        let kind = match file {
+            LibraryFile::Source(_) => "source",
+            LibraryFile::Stub(_) => "stub",
+        };
+        files.push((path.to_string(), kind));
+    })
+    .unwrap();
+    assert_eq!(
+        files,
+        vec![
+            ("demo/Greeter.java".to_string(), "source"),
+            ("demo/Shaded.java".to_string(), "stub"),
+        ]
+    );
+
+    let registry = SourceArchiveRegistry::new();
+    registry.register_jar(jar.clone(), Some(sources));
+    let read = |path: &str| {
+        let uri = jar_entry_uri(&jar, path).unwrap();
+        let location = registry
+            .materialize(&Location::new(uri, Default::default()))
+            .expect("materialized");
+        std::fs::read_to_string(location.uri.to_file_path().unwrap()).unwrap()
+    };
+    let greeter = read("demo/Greeter.java");
+    let stub = read("demo/Shaded.java");
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(greeter, source);
+    assert!(stub.contains("public class Shaded {"), "{}", stub);
+}
@@ -0,0 +1,70 @@
+//! Parsing and indexing helpers shared by the integration test suites.
+#![allow(dead_code)]
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{Position, TextEdit};
+
+use jrsls::{
+    indexer::Indexer,
+    state::{Document, GlobalIndex},
+};
+
+/// Parses `code` as Java, like an editor buffer read from disk.
+pub fn parse(code: &str) -> Document {
+    let rope = Rope::from_str(code);
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&tree_sitter_java::LANGUAGE.into())
+        .expect("load java grammar");
+    let tree = parser
+        .parse_with_options(
+            &mut |offset, _| rope.byte_slice(offset..).chunks().next().unwrap_or(""),
+            None,
+            None,
+        )
+        .unwrap();
+    Document {
+        text: rope,
+        tree,
+        version: 0,
+    }
+}
+
+/// Parses `code` and indexes it under `uri`.
+pub fn parse_and_index(code: &str, uri: &str, index: &GlobalIndex) -> tree_sitter::Tree {
+    let doc = parse(code);
+    Indexer::update_file(index, uri, &doc.tree, &doc.text);
+    doc.tree
+}
+
+/// Position of the first occurrence of `needle`, or the start of the file.
+pub fn pos_for(code: &str, needle: &str) -> Position {
+    for (i, l) in code.lines().enumerate() {
+        if let Some(col) = l.find(needle) {
+            return Position::new(i as u32, col as u32);
+        }
+    }
+    Position::new(0, 0)
+}
+
+/// Position just after the first occurrence of `needle`.
+pub fn pos_after(code: &str, needle: &str) -> Position {
+    for (i, l) in code.lines().enumerate() {
+        if let Some(col) = l.find(needle) {
+            return Position::new(i as u32, (col + needle.len()) as u32);
+        }
+    }
+    Position::new(0, 0)
+}
+
+/// `code` with `edit` applied.
+pub fn apply(code: &str, edit: &TextEdit) -> String {
+    let rope = Rope::from_str(code);
+    let start =
+        rope.line_to_char(edit.range.start.line as usize) + edit.range.start.character as usize;
+    let end = rope.line_to_char(edit.range.end.line as usize) + edit.range.end.character as usize;
+    let mut out = rope.clone();
+    out.remove(start..end);
+    out.insert(start, &edit.new_text);
+    out.to_string()
+}
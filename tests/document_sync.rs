@@ -0,0 +1,27 @@
+use ropey::Rope;
+use tower_lsp::lsp_types::Position;
+
+use jrsls::utils::{byte_to_point, position_to_char};
+
+#[test]
+fn positions_past_the_text_are_clamped() {
+    let text = Rope::from_str("class A {\r\n  int x;\n}");
+
+    assert_eq!(position_to_char(&text, Position::new(0, 6)), 6);
+    // Past the line end, before the line break.
+    assert_eq!(position_to_char(&text, Position::new(0, 99)), 9);
+    assert_eq!(position_to_char(&text, Position::new(1, 99)), 19);
+    // Past the last line.
+    assert_eq!(
+        position_to_char(&text, Position::new(7, 0)),
+        text.len_chars()
+    );
+}
+
+#[test]
+fn points_count_bytes_within_the_line() {
+    let text = Rope::from_str("// é\nint x;\n");
+    let byte = text.char_to_byte(position_to_char(&text, Position::new(0, 4)));
+    assert_eq!(byte_to_point(&text, byte), tree_sitter::Point::new(0, 5));
+    assert_eq!(byte_to_point(&text, 10), tree_sitter::Point::new(1, 4));
+}
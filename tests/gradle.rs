@@ -0,0 +1,189 @@
+use std::path::{Path, PathBuf};
+
+use jrsls::library::attached_sources;
+use jrsls::project::gradle::GradleProject;
+
+struct Fixture {
+    dir: PathBuf,
+}
+
+impl Fixture {
+    fn new(name: &str) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("jrsls-gradle-{}-{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        Self { dir }
+    }
+
+    fn cache(&self) -> PathBuf {
+        self.dir.join("files-2.1")
+    }
+
+    fn workspace(&self) -> PathBuf {
+        self.dir.join("workspace")
+    }
+
+    fn write(&self, path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    fn file(&self, path: &str, contents: &str) {
+        self.write(&self.workspace().join(path), contents);
+    }
+
+    /// Stores a cache file for `g:a:v` under its own hash directory, as
+    /// Gradle does.
+    fn cached(&self, gav: &str, file: &str, hash: char, contents: &str) -> PathBuf {
+        let parts: Vec<_> = gav.split(':').collect();
+        let path = self
+            .cache()
+            .join(parts[0])
+            .join(parts[1])
+            .join(parts[2])
+            .join(hash.to_string().repeat(40))
+            .join(file);
+        self.write(&path, contents);
+        path
+    }
+
+    /// Caches `g:a:v` with a POM listing `dependencies` (g:a:v each) and
+    /// an empty jar.
+    fn install(&self, gav: &str, dependencies: &[&str]) -> PathBuf {
+        let parts: Vec<_> = gav.split(':').collect();
+        let dependencies: String = dependencies
+            .iter()
+            .map(|d| {
+                let d: Vec<_> = d.split(':').collect();
+                format!(
+                    "<dependency><groupId>{}</groupId><artifactId>{}</artifactId>\
+                     <version>{}</version></dependency>",
+                    d[0], d[1], d[2]
+                )
+            })
+            .collect();
+        self.cached(
+            gav,
+            &format!("{}-{}.pom", parts[1], parts[2]),
+            'a',
+            &format!(
+                "<project><groupId>{}</groupId><artifactId>{}</artifactId><version>{}</version>\
+                 <dependencies>{}</dependencies></project>",
+                parts[0], parts[1], parts[2], dependencies
+            ),
+        );
+        self.cached(gav, &format!("{}-{}.jar", parts[1], parts[2]), 'b', "")
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+#[test]
+fn resolves_multi_project_build_from_gradle_cache() {
+    let fx = Fixture::new("projects");
+    let lib_a = fx.install("org.lib:lib-a:2.0", &["org.lib:lib-b:1.0"]);
+    let lib_b = fx.install("org.lib:lib-b:1.0", &[]);
+    let guava = fx.install("com.google.guava:guava:31.0", &[]);
+    let junit = fx.install("junit:junit:4.13", &[]);
+    let processor = fx.install("org.tools:processor:1.0", &[]);
+    let driver = fx.install("org.db:driver:1.0", &[]);
+    let engine = fx.install("org.junit:engine:1.0", &[]);
+
+    fx.file(
+        "settings.gradle",
+        "rootProject.name = 'demo'\n\
+         include 'core', ':app'\n\
+         project(':app').projectDir = file('application')\n",
+    );
+    fx.file(
+        "build.gradle",
+        "ext {\n    junitVersion = '4.13'\n}\n\
+         subprojects {\n    dependencies {\n        \
+         testImplementation \"junit:junit:${junitVersion}\" // shared\n    }\n}\n",
+    );
+    fx.file("gradle.properties", "libAVersion=2.0\n");
+    fx.file(
+        "gradle/libs.versions.toml",
+        "[versions]\nguava = \"31.0\"\n\n\
+         [libraries]\nguava-core = { module = \"com.google.guava:guava\", version.ref = \"guava\" }\n",
+    );
+    fx.file(
+        "core/build.gradle.kts",
+        "plugins { `java-library` }\n\
+         dependencies {\n    \
+         api(\"org.lib:lib-a:$libAVersion\")\n    \
+         implementation(libs.guava.core)\n    \
+         annotationProcessor(\"org.tools:processor:1.0\")\n    \
+         runtimeOnly(\"org.db:driver:1.0\")\n    \
+         testRuntimeOnly(\"org.junit:engine:1.0\")\n}\n\
+         sourceSets {\n    main {\n        java.srcDir(\"src/generated/java\")\n    }\n}\n",
+    );
+    fx.file(
+        "application/build.gradle",
+        "dependencies {\n    \
+         implementation project(':core')\n    \
+         /* implementation 'org.gone:gone:1.0' */\n    \
+         compileOnly fileTree(dir: 'libs', include: ['*.jar'])\n}\n\
+         sourceSets.main.java.srcDirs = ['java']\n",
+    );
+    fx.file("application/libs/local.jar", "");
+
+    let project = GradleProject::load(&fx.workspace(), &fx.cache()).unwrap();
+    let paths: Vec<_> = project.modules.iter().map(|m| m.path.as_str()).collect();
+    assert_eq!(paths, vec![":", ":core", ":app"]);
+
+    let core = &project.modules[1];
+    assert_eq!(
+        core.source_sets[0].java_dirs,
+        vec![
+            fx.workspace().join("core/src/main/java"),
+            fx.workspace().join("core/src/generated/java")
+        ]
+    );
+    let mut classpath = core.classpath.clone();
+    classpath.sort();
+    let mut expected = vec![lib_a.clone(), lib_b.clone(), guava.clone()];
+    expected.sort();
+    assert_eq!(classpath, expected);
+    assert_eq!(core.test_classpath, vec![junit.clone()]);
+
+    // `implementation` dependencies of :core stay out of :app.
+    let app = &project.modules[2];
+    assert_eq!(app.dir, fx.workspace().join("application"));
+    assert_eq!(
+        app.source_sets[0].java_dirs,
+        vec![fx.workspace().join("application/java")]
+    );
+    let mut classpath = app.classpath.clone();
+    classpath.sort();
+    let mut expected = vec![
+        lib_a,
+        lib_b,
+        fx.workspace().join("application/libs/local.jar"),
+    ];
+    expected.sort();
+    assert_eq!(classpath, expected);
+    assert_eq!(app.test_classpath, vec![junit.clone()]);
+
+    assert!(project.classpath().contains(&guava));
+    assert_eq!(project.test_classpath(), vec![junit]);
+    for jar in [&processor, &driver, &engine] {
+        assert!(!project.classpath().contains(jar));
+        assert!(!project.test_classpath().contains(jar));
+    }
+}
+
+#[test]
+fn finds_sources_jar_in_sibling_cache_directory() {
+    let fx = Fixture::new("sources");
+    let jar = fx.install("org.lib:lib-a:2.0", &[]);
+    assert_eq!(attached_sources(&jar), None);
+
+    let sources = fx.cached("org.lib:lib-a:2.0", "lib-a-2.0-sources.jar", 'c', "");
+    assert_eq!(attached_sources(&jar), Some(sources));
+}
@@ -0,0 +1,228 @@
+mod common;
+
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{CallHierarchyItem, Url};
+
+use jrsls::{
+    hierarchy,
+    indexer::Indexer,
+    lang::java::JavaService,
+    state::{Document, GlobalIndex},
+};
+
+use common::{parse, pos_for};
+
+struct Workspace {
+    index: GlobalIndex,
+    sources: HashMap<String, String>,
+}
+
+impl Workspace {
+    fn new(files: &[(&str, &str)]) -> Self {
+        let index = GlobalIndex::new();
+        let mut sources = HashMap::new();
+        for (uri, code) in files {
+            let doc = parse(code);
+            Indexer::update_file(&index, uri, &doc.tree, &doc.text);
+            sources.insert(uri.to_string(), code.to_string());
+        }
+        Self { index, sources }
+    }
+
+    fn load(&self, uri: &Url) -> Option<Document> {
+        self.sources.get(uri.as_str()).map(|code| parse(code))
+    }
+
+    fn call_item(&self, uri: &str, needle: &str) -> CallHierarchyItem {
+        let code = &self.sources[uri];
+        let doc = parse(code);
+        let items = hierarchy::prepare_call_hierarchy(
+            &JavaService,
+            &doc.tree,
+            &doc.text,
+            pos_for(code, needle),
+            &self.index,
+            uri,
+        )
+        .expect("call hierarchy item");
+        assert_eq!(items.len(), 1);
+        items.into_iter().next().unwrap()
+    }
+
+    fn incoming(&self, item: &CallHierarchyItem) -> Vec<(String, usize)> {
+        hierarchy::incoming_calls(&JavaService, &self.index, item, &|uri| self.load(uri))
+            .into_iter()
+            .map(|call| (call.from.name, call.from_ranges.len()))
+            .collect()
+    }
+
+    fn outgoing(&self, item: &CallHierarchyItem) -> Vec<(String, usize)> {
+        hierarchy::outgoing_calls(&JavaService, &self.index, item, &|uri| self.load(uri))
+            .into_iter()
+            .map(|call| (call.to.name, call.from_ranges.len()))
+            .collect()
+    }
+}
+
+const ANIMAL: &str = r#"package zoo.model;
+
+public interface Animal {
+    String name();
+}
+"#;
+
+const PET: &str = r#"package zoo;
+
+import zoo.model.Animal;
+
+public abstract class Pet implements Animal {
+}
+"#;
+
+const DOG: &str = r#"package zoo;
+
+public class Dog extends Pet {
+    public String name() { return "dog"; }
+}
+"#;
+
+const CAT: &str = r#"package zoo;
+
+public class Cat extends Pet {
+    public String name() { return "cat"; }
+    Dog chase() { return new Dog(); }
+}
+"#;
+
+#[test]
+fn walks_supertypes_and_subtypes() {
+    let ws = Workspace::new(&[
+        ("file:///ws/zoo/model/Animal.java", ANIMAL),
+        ("file:///ws/zoo/Pet.java", PET),
+        ("file:///ws/zoo/Dog.java", DOG),
+        ("file:///ws/zoo/Cat.java", CAT),
+    ]);
+    let uri = "file:///ws/zoo/Cat.java";
+    let doc = parse(CAT);
+
+    // From a type reference.
+    let items = hierarchy::prepare_type_hierarchy(
+        &JavaService,
+        &doc.tree,
+        &doc.text,
+        pos_for(CAT, "Dog chase"),
+        &ws.index,
+        uri,
+    )
+    .expect("type item");
+    assert_eq!(items.len(), 1);
+    let dog = &items[0];
+    assert_eq!(dog.name, "Dog");
+    assert_eq!(dog.detail.as_deref(), Some("zoo"));
+    assert_eq!(dog.uri.as_str(), "file:///ws/zoo/Dog.java");
+
+    let supertypes = hierarchy::supertypes(&ws.index, dog);
+    let names: Vec<_> = supertypes.iter().map(|item| item.name.as_str()).collect();
+    assert_eq!(names, vec!["Pet"]);
+
+    // Up to the interface, then back down through both subclasses.
+    let animal = hierarchy::supertypes(&ws.index, &supertypes[0])
+        .into_iter()
+        .find(|item| item.name == "Animal")
+        .expect("Pet implements Animal");
+    let pets = hierarchy::subtypes(&ws.index, &animal);
+    assert_eq!(pets.len(), 1);
+    let mut subclasses: Vec<_> = hierarchy::subtypes(&ws.index, &pets[0])
+        .into_iter()
+        .map(|item| item.name)
+        .collect();
+    subclasses.sort();
+    assert_eq!(subclasses, vec!["Cat", "Dog"]);
+
+    // From the declaration name.
+    let items = hierarchy::prepare_type_hierarchy(
+        &JavaService,
+        &doc.tree,
+        &doc.text,
+        pos_for(CAT, "Cat extends"),
+        &ws.index,
+        uri,
+    )
+    .expect("type item");
+    assert_eq!(items[0].name, "Cat");
+}
+
+const REPO: &str = r#"package shop;
+
+public class Repo {
+    public static Repo open() { return new Repo(); }
+    public Order find(int id) { return load(id); }
+    private Order load(int id) { return new Order(id); }
+}
+"#;
+
+const ORDER: &str = r#"package shop;
+
+public class Order {
+    public Order(int id) {}
+}
+"#;
+
+const SERVICE: &str = r#"package shop;
+
+class OrderService {
+    Order get(int id) {
+        Repo repo = Repo.open();
+        return repo.find(id);
+    }
+
+    void audit() {
+        get(1);
+        get(2);
+    }
+}
+"#;
+
+fn shop() -> Workspace {
+    Workspace::new(&[
+        ("file:///ws/shop/Repo.java", REPO),
+        ("file:///ws/shop/Order.java", ORDER),
+        ("file:///ws/shop/OrderService.java", SERVICE),
+    ])
+}
+
+#[test]
+fn incoming_calls_group_call_sites_by_caller() {
+    let ws = shop();
+
+    // Prepared from a call site in another file.
+    let find = ws.call_item("file:///ws/shop/OrderService.java", "find(id)");
+    assert_eq!(find.name, "find");
+    assert_eq!(find.uri.as_str(), "file:///ws/shop/Repo.java");
+    assert_eq!(ws.incoming(&find), vec![("get".to_string(), 1)]);
+
+    let get = ws.call_item("file:///ws/shop/OrderService.java", "get(int");
+    assert_eq!(ws.incoming(&get), vec![("audit".to_string(), 2)]);
+
+    let constructor = ws.call_item("file:///ws/shop/Order.java", "Order(int");
+    assert_eq!(ws.incoming(&constructor), vec![("load".to_string(), 1)]);
+}
+
+#[test]
+fn outgoing_calls_resolve_callees_across_files() {
+    let ws = shop();
+
+    let get = ws.call_item("file:///ws/shop/OrderService.java", "get(int");
+    assert_eq!(
+        ws.outgoing(&get),
+        vec![("open".to_string(), 1), ("find".to_string(), 1)]
+    );
+
+    let find = ws.call_item("file:///ws/shop/Repo.java", "find(int");
+    assert_eq!(ws.outgoing(&find), vec![("load".to_string(), 1)]);
+
+    // `new` resolves to the constructor.
+    let load = ws.call_item("file:///ws/shop/Repo.java", "load(int");
+    assert_eq!(ws.outgoing(&load), vec![("Order".to_string(), 1)]);
+}
@@ -0,0 +1,228 @@
+mod common;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::Location;
+
+use jrsls::{
+    implementation::find_implementations,
+    lang::{LanguageService, java::JavaService},
+    state::GlobalIndex,
+};
+
+use common::{parse_and_index, pos_for};
+
+/// `(file name, line)` of each location, sorted.
+fn places(locations: Vec<Location>) -> Vec<(String, u32)> {
+    let mut places: Vec<_> = locations
+        .into_iter()
+        .map(|loc| {
+            let file = loc.uri.path().rsplit('/').next().unwrap().to_string();
+            (file, loc.range.start.line)
+        })
+        .collect();
+    places.sort();
+    places
+}
+
+const SHAPE: &str = r#"package geo;
+
+public interface Shape {
+    double area();
+    default String label() { return "shape"; }
+}
+"#;
+
+const SOLID: &str = r#"package geo;
+
+public interface Solid extends Shape {
+    double volume();
+}
+"#;
+
+const BASE: &str = r#"package geo;
+
+public abstract class Base implements Shape {
+    public String label() { return "base"; }
+}
+"#;
+
+const CIRCLE: &str = r#"package geo;
+
+public class Circle extends Base {
+    public double area() { return 3.14; }
+}
+"#;
+
+const SQUARE: &str = r#"package geo;
+
+public class Square implements Shape {
+    public double area() { return 1.0; }
+    public double area(double scale) { return scale; }
+}
+"#;
+
+const CANVAS: &str = r#"package geo;
+
+class Canvas {
+    Shape shape;
+
+    double paint(Circle circle) {
+        Square square = new Square();
+        square.area();
+        this.shape.label();
+        return shape.area() + circle.area();
+    }
+
+    Circle biggest() { return null; }
+
+    void use() {
+        biggest().area();
+    }
+}
+"#;
+
+fn index_geo() -> GlobalIndex {
+    let index = GlobalIndex::new();
+    for (file, code) in [
+        ("Shape", SHAPE),
+        ("Solid", SOLID),
+        ("Base", BASE),
+        ("Circle", CIRCLE),
+        ("Square", SQUARE),
+    ] {
+        parse_and_index(code, &format!("file:///ws/geo/{}.java", file), &index);
+    }
+    index
+}
+
+fn implementations(index: &GlobalIndex, uri: &str, code: &str, needle: &str) -> Vec<(String, u32)> {
+    let tree = parse_and_index(code, uri, index);
+    places(find_implementations(
+        &JavaService,
+        &tree,
+        &Rope::from_str(code),
+        pos_for(code, needle),
+        index,
+        uri,
+    ))
+}
+
+fn type_definition(index: &GlobalIndex, needle: &str) -> Vec<(String, u32)> {
+    let uri = "file:///ws/geo/Canvas.java";
+    let tree = parse_and_index(CANVAS, uri, index);
+    places(JavaService.type_definition(
+        &tree,
+        &Rope::from_str(CANVAS),
+        pos_for(CANVAS, needle),
+        index,
+        uri,
+    ))
+}
+
+#[test]
+fn lists_implementing_classes_of_an_interface() {
+    let index = index_geo();
+
+    // Interfaces extending `Shape` are not implementations.
+    assert_eq!(
+        implementations(&index, "file:///ws/geo/Shape.java", SHAPE, "Shape {"),
+        vec![
+            ("Base.java".to_string(), 2),
+            ("Circle.java".to_string(), 2),
+            ("Square.java".to_string(), 2)
+        ]
+    );
+    assert_eq!(
+        implementations(&index, "file:///ws/geo/Canvas.java", CANVAS, "Shape shape"),
+        implementations(&index, "file:///ws/geo/Shape.java", SHAPE, "Shape {"),
+    );
+}
+
+#[test]
+fn lists_concrete_overrides_of_a_method() {
+    let index = index_geo();
+
+    // From the abstract declaration: both overrides, not the overload.
+    assert_eq!(
+        implementations(&index, "file:///ws/geo/Shape.java", SHAPE, "area()"),
+        vec![
+            ("Circle.java".to_string(), 3),
+            ("Square.java".to_string(), 3)
+        ]
+    );
+    // From a call on the interface type.
+    assert_eq!(
+        implementations(
+            &index,
+            "file:///ws/geo/Canvas.java",
+            CANVAS,
+            "area() + circle"
+        ),
+        vec![
+            ("Circle.java".to_string(), 3),
+            ("Square.java".to_string(), 3)
+        ]
+    );
+    // A default method is overridden by `Base` only.
+    assert_eq!(
+        implementations(&index, "file:///ws/geo/Shape.java", SHAPE, "label()"),
+        vec![("Base.java".to_string(), 3)]
+    );
+}
+
+#[test]
+fn jumps_to_the_static_type() {
+    let index = index_geo();
+
+    // Field, parameter and local variable.
+    assert_eq!(
+        type_definition(&index, "shape.area"),
+        vec![("Shape.java".to_string(), 2)]
+    );
+    assert_eq!(
+        type_definition(&index, "circle.area"),
+        vec![("Circle.java".to_string(), 2)]
+    );
+    assert_eq!(
+        type_definition(&index, "square.area"),
+        vec![("Square.java".to_string(), 2)]
+    );
+    // Field access and calls: the type of the whole expression.
+    assert_eq!(
+        type_definition(&index, "shape.label"),
+        vec![("Shape.java".to_string(), 2)]
+    );
+    assert_eq!(
+        type_definition(&index, "biggest()."),
+        vec![("Circle.java".to_string(), 2)]
+    );
+    assert!(type_definition(&index, "area() + circle").is_empty());
+}
+
+#[test]
+fn returns_every_declaration_of_the_type() {
+    let index = index_geo();
+    parse_and_index(SQUARE, "jrsls-jar:///repo/geo.jar!/geo/Square.java", &index);
+
+    let uri = "file:///ws/geo/Canvas.java";
+    let tree = parse_and_index(CANVAS, uri, &index);
+    let mut uris: Vec<_> = JavaService
+        .type_definition(
+            &tree,
+            &Rope::from_str(CANVAS),
+            pos_for(CANVAS, "square.area"),
+            &index,
+            uri,
+        )
+        .into_iter()
+        .map(|loc| loc.uri.to_string())
+        .collect();
+    uris.sort();
+    assert_eq!(
+        uris,
+        vec![
+            "file:///ws/geo/Square.java",
+            "jrsls-jar:///repo/geo.jar!/geo/Square.java"
+        ]
+    );
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{CodeActionOrCommand, Range};
+
+use jrsls::{
+    imports::import_edit,
+    lang::{LanguageService, java::JavaService},
+    state::GlobalIndex,
+};
+
+use common::{apply, parse_and_index, pos_for};
+
+fn library(index: &GlobalIndex) {
+    for (uri, code) in [
+        (
+            "jrsls-std:///java/util/List.java",
+            "package java.util;\npublic interface List<E> {}\n",
+        ),
+        (
+            "jrsls-std:///java/awt/List.java",
+            "package java.awt;\npublic class List {}\n",
+        ),
+        (
+            "jrsls-std:///java/util/Map.java",
+            "package java.util;\npublic interface Map<K, V> {}\n",
+        ),
+        (
+            "file:///ws/app/model/Lister.java",
+            "package app.model;\npublic class Lister {}\n",
+        ),
+    ] {
+        parse_and_index(code, uri, index);
+    }
+}
+
+const MAIN: &str = r#"package app;
+
+import java.io.File;
+import java.util.Map;
+
+class Main {
+    List<String> names;
+}
+"#;
+
+#[test]
+fn quickfix_offers_each_candidate_and_sorts_the_import() {
+    let index = GlobalIndex::new();
+    library(&index);
+    let uri = "file:///ws/app/Main.java";
+    let tree = parse_and_index(MAIN, uri, &index);
+    let rope = Rope::from_str(MAIN);
+
+    let at = pos_for(MAIN, "List<String>");
+    let actions = JavaService.code_actions(&tree, &rope, Range::new(at, at), &index, uri);
+    let actions: Vec<_> = actions
+        .into_iter()
+        .map(|a| match a {
+            CodeActionOrCommand::CodeAction(action) => action,
+            other => panic!("unexpected {:?}", other),
+        })
+        .collect();
+    let titles: Vec<_> = actions.iter().map(|a| a.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        vec!["Import 'java.awt.List'", "Import 'java.util.List'"]
+    );
+
+    let changes = actions[1].edit.as_ref().unwrap().changes.as_ref().unwrap();
+    let edits = changes.values().next().unwrap();
+    assert_eq!(
+        apply(MAIN, &edits[0]),
+        MAIN.replace(
+            "import java.util.Map;",
+            "import java.util.List;\nimport java.util.Map;"
+        )
+    );
+}
+
+#[test]
+fn import_goes_after_package_when_file_has_none() {
+    let code = "package app;\n\nclass Main {}\n";
+    let index = GlobalIndex::new();
+    let tree = parse_and_index(code, "file:///ws/app/Main.java", &index);
+    let rope = Rope::from_str(code);
+
+    let edit = import_edit(&tree, &rope, "java.util.List").unwrap();
+    assert_eq!(
+        apply(code, &edit),
+        "package app;\n\nimport java.util.List;\n\nclass Main {}\n"
+    );
+
+    let code = "// Licensed under MIT.\n\n/**\n * Entry point.\n */\nclass Main {}\n";
+    let tree = parse_and_index(code, "file:///ws/Main.java", &index);
+    let edit = import_edit(&tree, &Rope::from_str(code), "java.util.List").unwrap();
+    assert_eq!(
+        apply(code, &edit),
+        "// Licensed under MIT.\n\nimport java.util.List;\n\n/**\n * Entry point.\n */\nclass Main {}\n"
+    );
+
+    let code = "import java.util.*;\nclass Main {}\n";
+    let tree = parse_and_index(code, "file:///ws/Main.java", &index);
+    assert!(import_edit(&tree, &Rope::from_str(code), "java.util.List").is_none());
+}
+
+#[test]
+fn class_completion_carries_import_edit() {
+    let code = r#"package app;
+
+import java.util.Map;
+
+class Main {
+    void demo() {
+        Lis
+    }
+}
+"#;
+    let index = GlobalIndex::new();
+    library(&index);
+    let uri = "file:///ws/app/Main.java";
+    let tree = parse_and_index(code, uri, &index);
+    let rope = Rope::from_str(code);
+
+    let mut at = pos_for(code, "Lis");
+    at.character += 3;
+    let items = JavaService
+        .completion(&tree, &rope, at, &index, uri, &[])
+        .expect("completions");
+
+    let lister = items
+        .iter()
+        .find(|i| i.detail.as_deref() == Some("app.model.Lister"))
+        .expect("index-wide class offered");
+    let edits = lister.additional_text_edits.as_ref().expect("import edit");
+    assert_eq!(edits[0].new_text, "import app.model.Lister;\n");
+    assert!(
+        items
+            .iter()
+            .any(|i| i.detail.as_deref() == Some("java.util.List")),
+        "{:?}",
+        items
+    );
+}
@@ -0,0 +1,220 @@
+mod common;
+
+use ropey::Rope;
+
+use jrsls::{
+    diagnostics::syntax_diagnostics,
+    lang::{LanguageService, java::JavaService},
+    state::GlobalIndex,
+};
+
+use common::{parse, parse_and_index};
+
+fn diagnose(code: &str) -> Vec<(u32, String)> {
+    let doc = parse(code);
+    syntax_diagnostics(&doc.tree, &doc.text)
+        .into_iter()
+        .map(|d| (d.range.start.line, d.message))
+        .collect()
+}
+
+#[test]
+fn reports_missing_semicolon() {
+    let code = r#"
+class Main {
+    void run() {
+        int a = 1
+    }
+}
+"#;
+    assert_eq!(diagnose(code), vec![(3, "missing ';'".to_string())]);
+}
+
+#[test]
+fn reports_unexpected_token() {
+    let code = r#"
+class Main {
+    void run() {
+        foo(1));
+    }
+}
+"#;
+    let diagnostics = diagnose(code);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0], (3, "unexpected token `)`".to_string()));
+}
+
+#[test]
+fn clean_file_has_no_diagnostics() {
+    let code = r#"
+class Main {
+    void run() {
+        int a = 1;
+    }
+}
+"#;
+    assert!(diagnose(code).is_empty());
+}
+
+fn jdk_stub_index() -> GlobalIndex {
+    let index = GlobalIndex::new();
+    let stubs = [
+        (
+            "java/lang/Object.java",
+            "package java.lang; public class Object {}",
+        ),
+        (
+            "java/lang/System.java",
+            "package java.lang; import java.io.PrintStream; public final class System { public static final PrintStream out = null; }",
+        ),
+        (
+            "java/io/PrintStream.java",
+            "package java.io; public class PrintStream extends FilterOutputStream { public void println(int x) {} }",
+        ),
+        (
+            "java/io/FilterOutputStream.java",
+            "package java.io; public class FilterOutputStream extends OutputStream { public void flush() {} }",
+        ),
+        (
+            "java/io/OutputStream.java",
+            "package java.io; public abstract class OutputStream { public abstract void write(int b); }",
+        ),
+        (
+            "java/lang/Enum.java",
+            "package java.lang; public abstract class Enum<E extends Enum<E>> { public final String name() { return null; } }",
+        ),
+        (
+            "java/lang/Record.java",
+            "package java.lang; public abstract class Record {}",
+        ),
+        (
+            "java/util/List.java",
+            "package java.util; public interface List<E> {}",
+        ),
+    ];
+    for (path, code) in stubs {
+        parse_and_index(code, &format!("jrsls-std:///{}", path), &index);
+    }
+    index
+}
+
+fn semantic(index: &GlobalIndex, code: &str) -> Vec<(u32, String)> {
+    let uri = "file:///ws/Main.java";
+    let tree = parse_and_index(code, uri, index);
+    let rope = Rope::from_str(code);
+    JavaService
+        .semantic_diagnostics(&tree, &rope, index, uri)
+        .into_iter()
+        .map(|d| (d.range.start.line, d.message))
+        .collect()
+}
+
+#[test]
+fn reports_unresolved_types_imports_and_members() {
+    let code = r#"
+import java.util.List;
+import java.util.Missing;
+
+class Main<T> {
+    List<T> items;
+    Strin name;
+    void run() {
+        System.out.println(1);
+        System.out.printz(1);
+    }
+}
+"#;
+    let index = jdk_stub_index();
+    assert_eq!(
+        semantic(&index, code),
+        vec![
+            (2, "cannot resolve import `java.util.Missing`".to_string()),
+            (6, "cannot resolve type `Strin`".to_string()),
+            (
+                9,
+                "`printz` is not a member of `java.io.PrintStream`".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn resolves_members_inherited_from_supertypes() {
+    let code = r#"
+class Main {
+    void run() {
+        System.out.flush();
+        System.out.write(1);
+        System.out.printz(1);
+        Widget widget = new Widget();
+        widget.repaint();
+    }
+}
+
+class Widget extends javax.swing.JComponent {}
+"#;
+    let index = jdk_stub_index();
+    // `Widget` extends a class the index has not seen, so its members are
+    // not checked.
+    assert_eq!(
+        semantic(&index, code),
+        vec![(
+            5,
+            "`printz` is not a member of `java.io.PrintStream`".to_string()
+        )]
+    );
+}
+
+#[test]
+fn stays_quiet_without_jdk() {
+    let code = r#"
+import java.util.List;
+
+class Main {
+    String name;
+    List items;
+}
+"#;
+    let index = GlobalIndex::new();
+    assert!(semantic(&index, code).is_empty());
+}
+
+#[test]
+fn var_is_not_an_unresolved_type() {
+    let code = r#"
+import java.util.List;
+
+class Main {
+    void run(List<Object> items) {
+        var copy = items;
+        for (var item : items) {}
+    }
+}
+"#;
+    let index = jdk_stub_index();
+    assert_eq!(semantic(&index, code), vec![]);
+}
+
+#[test]
+fn resolves_implicit_enum_and_record_members() {
+    let code = r#"
+class Main {
+    void run(Point p) {
+        Color[] all = Color.values();
+        Color red = Color.valueOf("RED");
+        red.name();
+        int x = p.x();
+        int z = p.z();
+    }
+}
+
+enum Color { RED, GREEN }
+
+record Point(int x, int y) {}
+"#;
+    let index = jdk_stub_index();
+    assert_eq!(
+        semantic(&index, code),
+        vec![(7, "`z` is not a member of `Point`".to_string())]
+    );
+}
@@ -1,37 +1,14 @@
+mod common;
+
 use ropey::Rope;
 use tower_lsp::lsp_types::{Location, Position, Url};
 
 use jrsls::{
-    indexer::Indexer,
     lang::{LanguageService, java::JavaService},
     state::GlobalIndex,
 };
 
-fn parse_and_index(code: &str, uri: &str, index: &GlobalIndex) -> tree_sitter::Tree {
-    let rope = Rope::from_str(code);
-    let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&tree_sitter_java::LANGUAGE.into())
-        .expect("load java grammar");
-    let tree = parser
-        .parse_with_options(
-            &mut |offset, _| rope.byte_slice(offset..).chunks().next().unwrap_or(""),
-            None,
-            None,
-        )
-        .unwrap();
-    Indexer::update_file(index, uri, &tree, &rope);
-    tree
-}
-
-fn pos_for(code: &str, needle: &str) -> Position {
-    for (i, l) in code.lines().enumerate() {
-        if let Some(col) = l.find(needle) {
-            return Position::new(i as u32, col as u32);
-        }
-    }
-    Position::new(0, 0)
-}
+use common::{parse_and_index, pos_for};
 
 fn goto(
     service: &JavaService,
@@ -276,7 +253,11 @@ public class ArrayList {
     public void clear() {}
 }
 "#;
-    parse_and_index(arraylist, "file:///workspace/java/util/ArrayList.java", &index);
+    parse_and_index(
+        arraylist,
+        "file:///workspace/java/util/ArrayList.java",
+        &index,
+    );
 
     let uri = "file:///workspace/Main.java";
     let service = JavaService;
@@ -302,3 +283,287 @@ public class ArrayList {
         labels
     );
 }
+
+#[test]
+fn test_sources_are_hidden_from_main_code() {
+    let index = GlobalIndex::new();
+    let service = JavaService;
+    index.set_test_roots(vec![
+        Url::parse("file:///workspace/src/test/java/").unwrap(),
+    ]);
+    let helper = "package demo;\n\nclass TestHelper {}\n";
+    parse_and_index(
+        helper,
+        "file:///workspace/src/test/java/demo/TestHelper.java",
+        &index,
+    );
+
+    let main = "package demo;\n\nclass App {\n    TestHelper helper;\n}\n";
+    let main_uri = "file:///workspace/src/main/java/demo/App.java";
+    let tree = parse_and_index(main, main_uri, &index);
+    let rope = Rope::from_str(main);
+    let position = pos_for(main, "TestHelper helper");
+    assert!(
+        service
+            .goto_definition(&tree, &rope, position, &index, main_uri)
+            .is_none()
+    );
+
+    let test = "package demo;\n\nclass AppTest {\n    TestHelper helper;\n}\n";
+    let test_uri = "file:///workspace/src/test/java/demo/AppTest.java";
+    let loc = goto(&service, &index, test_uri, test, "TestHelper helper");
+    assert_eq!(
+        loc.uri.as_str(),
+        "file:///workspace/src/test/java/demo/TestHelper.java"
+    );
+}
+
+#[test]
+fn test_scoped_jars_are_hidden_from_main_code() {
+    let index = GlobalIndex::new();
+    let service = JavaService;
+    index.set_test_roots(vec![
+        Url::parse("file:///workspace/src/test/java/").unwrap(),
+        Url::parse("jrsls-jar:///repo/junit.jar!/").unwrap(),
+    ]);
+    parse_and_index(
+        "package org.junit;\n\npublic class Assert {}\n",
+        "jrsls-jar:///repo/junit.jar!/org/junit/Assert.java",
+        &index,
+    );
+
+    let main = "package demo;\n\nimport org.junit.Assert;\n\nclass App {\n    Assert check;\n}\n";
+    let main_uri = "file:///workspace/src/main/java/demo/App.java";
+    let tree = parse_and_index(main, main_uri, &index);
+    let rope = Rope::from_str(main);
+    let position = pos_for(main, "Assert check");
+    assert!(
+        service
+            .goto_definition(&tree, &rope, position, &index, main_uri)
+            .is_none()
+    );
+
+    let test =
+        "package demo;\n\nimport org.junit.Assert;\n\nclass AppTest {\n    Assert check;\n}\n";
+    let test_uri = "file:///workspace/src/test/java/demo/AppTest.java";
+    let loc = goto(&service, &index, test_uri, test, "Assert check");
+    assert_eq!(
+        loc.uri.as_str(),
+        "jrsls-jar:///repo/junit.jar!/org/junit/Assert.java"
+    );
+}
+
+fn completion_labels(
+    service: &JavaService,
+    index: &GlobalIndex,
+    uri: &str,
+    code: &str,
+    needle: &str,
+) -> Vec<String> {
+    let tree = parse_and_index(code, uri, index);
+    let caret = pos_for(code, needle);
+    let caret = Position::new(caret.line, caret.character + needle.len() as u32);
+    service
+        .completion(&tree, &Rope::from_str(code), caret, index, uri, &[])
+        .unwrap_or_default()
+        .into_iter()
+        .map(|item| item.label)
+        .collect()
+}
+
+fn index_order_model(index: &GlobalIndex) {
+    parse_and_index(
+        r#"package shop;
+
+public class Repo {
+    public static Repo open() { return new Repo(); }
+    public Order find(int id) { return null; }
+    private void evict() {}
+}
+"#,
+        "file:///workspace/shop/Repo.java",
+        index,
+    );
+    parse_and_index(
+        r#"package shop;
+
+public class Order {
+    public Order(int id) {}
+    public Order(String code) {}
+    public Customer customer() { return null; }
+}
+"#,
+        "file:///workspace/shop/Order.java",
+        index,
+    );
+    parse_and_index(
+        r#"package shop;
+
+public interface Customer {
+    String name();
+    default String label() { return name(); }
+    int LIMIT = 3;
+}
+"#,
+        "file:///workspace/shop/Customer.java",
+        index,
+    );
+}
+
+#[test]
+fn indexes_return_types_and_modifiers() {
+    use jrsls::ast::InferredType;
+    use jrsls::state::{TypeKind, Visibility};
+
+    let index = GlobalIndex::new();
+    index_order_model(&index);
+
+    let find = &index.members_by_name("find")[0];
+    assert_eq!(find.return_type, Some(InferredType::Class("Order".into())));
+    assert_eq!(find.modifiers.visibility, Visibility::Public);
+    assert!(!find.modifiers.is_static);
+    assert!(index.members_by_name("open")[0].modifiers.is_static);
+    assert_eq!(
+        index.members_by_name("evict")[0].modifiers.visibility,
+        Visibility::Private
+    );
+
+    let constructors: Vec<_> = index
+        .members_of_class("shop.Order")
+        .into_iter()
+        .filter(|m| m.is_constructor)
+        .collect();
+    assert_eq!(constructors.len(), 2);
+    assert!(constructors.iter().all(|c| c.return_type.is_none()));
+
+    let name = &index.members_by_name("name")[0];
+    assert_eq!(name.declaring_kind, TypeKind::Interface);
+    assert_eq!(name.modifiers.visibility, Visibility::Public);
+    assert!(name.modifiers.is_abstract);
+    assert_eq!(name.return_type, Some(InferredType::String));
+    let label = &index.members_by_name("label")[0];
+    assert!(label.modifiers.is_default && !label.modifiers.is_abstract);
+    assert!(index.members_by_name("LIMIT")[0].modifiers.is_static);
+}
+
+#[test]
+fn parameter_lists_line_up_with_param_count() {
+    let index = GlobalIndex::new();
+    parse_and_index(
+        r#"
+class Broken {
+    void receiver(Broken this, int a) {}
+    void unnamed(int a, b) {}
+    void spread(int a, String... rest) {}
+    void stray(int a, ... b) {}
+}
+"#,
+        "file:///workspace/Broken.java",
+        &index,
+    );
+
+    let members = index.members_of_class("Broken");
+    assert_eq!(members.len(), 4);
+    for member in members {
+        assert_eq!(
+            member.param_types.len(),
+            member.param_count,
+            "{}",
+            member.fqmn
+        );
+        assert_eq!(
+            member.param_names.len(),
+            member.param_count,
+            "{}",
+            member.fqmn
+        );
+    }
+}
+
+#[test]
+fn completes_through_method_return_types() {
+    let index = GlobalIndex::new();
+    index_order_model(&index);
+    let service = JavaService;
+    let code = r#"package shop;
+
+class Checkout {
+    void run(Repo repo) {
+        repo.find(1).customer().
+    }
+}
+"#;
+    let labels = completion_labels(
+        &service,
+        &index,
+        "file:///workspace/shop/Checkout.java",
+        code,
+        "repo.find(1).customer().",
+    );
+    assert!(labels.contains(&"name".to_string()), "{:?}", labels);
+    assert!(labels.contains(&"label".to_string()), "{:?}", labels);
+}
+
+#[test]
+fn completion_respects_static_context_and_visibility() {
+    let index = GlobalIndex::new();
+    index_order_model(&index);
+    let service = JavaService;
+    let uri = "file:///workspace/shop/Checkout.java";
+
+    let code = "package shop;\n\nclass Checkout {\n    void run() {\n        Repo.\n    }\n}\n";
+    let labels = completion_labels(&service, &index, uri, code, "Repo.");
+    assert_eq!(labels, vec!["open".to_string()]);
+
+    let code =
+        "package shop;\n\nclass Checkout {\n    void run(Repo repo) {\n        repo.\n    }\n}\n";
+    let mut labels = completion_labels(&service, &index, uri, code, "repo.");
+    labels.sort();
+    assert_eq!(labels, vec!["find".to_string(), "open".to_string()]);
+}
+
+#[test]
+fn goto_follows_call_chains_across_files() {
+    let index = GlobalIndex::new();
+    index_order_model(&index);
+    let service = JavaService;
+    let code = r#"package shop;
+
+class Checkout {
+    void run(Repo repo) {
+        repo.find(1).customer().name();
+    }
+}
+"#;
+    let loc = goto(
+        &service,
+        &index,
+        "file:///workspace/shop/Checkout.java",
+        code,
+        "name();",
+    );
+    assert_eq!(loc.uri.as_str(), "file:///workspace/shop/Customer.java");
+    assert_eq!(loc.range.start.line, 3);
+}
+
+#[test]
+fn goto_on_new_picks_constructor_overload() {
+    let index = GlobalIndex::new();
+    index_order_model(&index);
+    let service = JavaService;
+    let code = r#"package shop;
+
+class Checkout {
+    void run() {
+        Object a = new Order("A-1");
+        Object b = new Order(7);
+    }
+}
+"#;
+    let uri = "file:///workspace/shop/Checkout.java";
+    let by_code = goto(&service, &index, uri, code, "Order(\"A-1\")");
+    assert_eq!(by_code.uri.as_str(), "file:///workspace/shop/Order.java");
+    assert_eq!(by_code.range.start.line, 4);
+    let by_id = goto(&service, &index, uri, code, "Order(7)");
+    assert_eq!(by_id.range.start.line, 3);
+}
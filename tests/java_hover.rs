@@ -0,0 +1,109 @@
+mod common;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::HoverContents;
+
+use jrsls::{
+    javadoc::javadoc_to_markdown,
+    lang::{LanguageService, java::JavaService},
+    state::GlobalIndex,
+};
+
+use common::{parse_and_index, pos_for};
+
+fn hover_text(index: &GlobalIndex, uri: &str, code: &str, needle: &str) -> String {
+    let tree = parse_and_index(code, uri, index);
+    let rope = Rope::from_str(code);
+    let hover = JavaService
+        .hover(&tree, &rope, pos_for(code, needle), index, uri)
+        .expect("hover");
+    match hover.contents {
+        HoverContents::Markup(markup) => markup.value,
+        other => panic!("unexpected hover contents {:?}", other),
+    }
+}
+
+#[test]
+fn hover_shows_indexed_signature_and_javadoc() {
+    let lib = r#"
+package java.io;
+
+public class PrintStream {
+    /**
+     * Prints an integer and then terminates the line.
+     *
+     * @param x  The {@code int} to be printed.
+     */
+    @Override
+    public void println(int x) {}
+}
+"#;
+    let code = r#"
+import java.io.PrintStream;
+
+class Main {
+    void demo(PrintStream out) {
+        out.println(1);
+    }
+}
+"#;
+    let index = GlobalIndex::new();
+    parse_and_index(lib, "jrsls-std:///java/io/PrintStream.java", &index);
+
+    let text = hover_text(&index, "file:///workspace/Main.java", code, "println(1)");
+    assert!(
+        text.contains("public void println(int x)"),
+        "missing signature in {}",
+        text
+    );
+    assert!(!text.contains("@Override"), "annotations leaked: {}", text);
+    assert!(
+        text.contains("Prints an integer"),
+        "missing doc in {}",
+        text
+    );
+    assert!(
+        text.contains("- `x` The `int` to be printed."),
+        "param tag not converted: {}",
+        text
+    );
+}
+
+#[test]
+fn hover_on_local_variable_uses_declaration() {
+    let code = r#"
+class Main {
+    void demo() {
+        final String greeting = "hi";
+        greeting.length();
+    }
+}
+"#;
+    let index = GlobalIndex::new();
+    let text = hover_text(
+        &index,
+        "file:///workspace/Main.java",
+        code,
+        "greeting.length",
+    );
+    assert!(
+        text.contains("final String greeting"),
+        "unexpected hover {}",
+        text
+    );
+}
+
+#[test]
+fn javadoc_block_tags_become_sections() {
+    let md = javadoc_to_markdown(
+        "/**\n * Returns the length.<p>\n * See {@link java.lang.String#length()}.\n * @return the <code>length</code>\n * @throws IllegalStateException when closed\n */",
+    );
+    assert!(md.starts_with("Returns the length."), "{}", md);
+    assert!(md.contains("`java.lang.String.length()`"), "{}", md);
+    assert!(md.contains("**Returns:** the `length`"), "{}", md);
+    assert!(
+        md.contains("- `IllegalStateException` when closed"),
+        "{}",
+        md
+    );
+}
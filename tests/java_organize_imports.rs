@@ -0,0 +1,107 @@
+mod common;
+
+use ropey::Rope;
+
+use jrsls::{
+    imports::{ImportGroup, ImportLayout},
+    lang::{LanguageService, java::JavaService},
+    state::GlobalIndex,
+};
+
+use common::{apply, parse_and_index};
+
+fn organize(code: &str, layout: &ImportLayout) -> Option<String> {
+    let index = GlobalIndex::new();
+    let uri = "file:///ws/com/acme/app/Main.java";
+    let tree = parse_and_index(code, uri, &index);
+    let rope = Rope::from_str(code);
+    JavaService
+        .organize_imports(&tree, &rope, &index, uri, layout)
+        .map(|edit| apply(code, &edit))
+}
+
+const MESSY: &str = r#"package com.acme.app;
+
+import static org.junit.Assert.assertEquals;
+import org.junit.Test;
+import com.acme.util.Strings;
+import java.util.Map;
+import javax.inject.Inject;
+import java.util.List;
+import java.util.List;
+import java.io.File;
+import java.util.concurrent.*;
+
+/** Uses {@link Map} in docs only. */
+class MainTest {
+    @Inject Strings strings;
+
+    @Test
+    void run() {
+        List<String> names = null;
+        assertEquals(1, 1);
+    }
+}
+"#;
+
+#[test]
+fn removes_unused_and_groups_by_default_layout() {
+    let organized = organize(MESSY, &ImportLayout::default()).expect("changes");
+    let expected_imports = "\
+import java.util.List;
+import java.util.Map;
+import java.util.concurrent.*;
+
+import javax.inject.Inject;
+
+import org.junit.Test;
+
+import com.acme.util.Strings;
+
+import static org.junit.Assert.assertEquals;
+";
+    assert!(
+        organized.contains(&format!(
+            "package com.acme.app;\n\n{}\n/**",
+            expected_imports
+        )),
+        "{}",
+        organized
+    );
+    assert!(!organized.contains("java.io.File"));
+
+    assert_eq!(organize(&organized, &ImportLayout::default()), None);
+}
+
+#[test]
+fn custom_layout_puts_statics_first() {
+    let layout: ImportLayout = "static,project,java".parse().unwrap();
+    assert_eq!(
+        layout.groups,
+        vec![
+            ImportGroup::Static,
+            ImportGroup::Project,
+            ImportGroup::Prefix("java".to_string()),
+            ImportGroup::ThirdParty,
+        ]
+    );
+
+    let organized = organize(MESSY, &layout).expect("changes");
+    let expected_imports = "\
+import static org.junit.Assert.assertEquals;
+
+import com.acme.util.Strings;
+
+import java.util.List;
+import java.util.Map;
+import java.util.concurrent.*;
+
+import javax.inject.Inject;
+import org.junit.Test;
+";
+    assert!(organized.contains(expected_imports), "{}", organized);
+
+    assert!("java,,static".parse::<ImportLayout>().is_ok());
+    assert!("java,java".parse::<ImportLayout>().is_err());
+    assert!("java..util".parse::<ImportLayout>().is_err());
+}
@@ -0,0 +1,195 @@
+mod common;
+
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{Location, Url};
+
+use jrsls::{
+    indexer::Indexer,
+    lang::{LanguageService, java::JavaService},
+    references::find_references,
+    state::GlobalIndex,
+};
+
+use common::{parse, pos_for};
+
+struct Workspace {
+    index: GlobalIndex,
+    sources: HashMap<String, String>,
+}
+
+impl Workspace {
+    fn new(files: &[(&str, &str)]) -> Self {
+        let index = GlobalIndex::new();
+        let mut sources = HashMap::new();
+        for (uri, code) in files {
+            let doc = parse(code);
+            Indexer::update_file(&index, uri, &doc.tree, &doc.text);
+            sources.insert(uri.to_string(), code.to_string());
+        }
+        Self { index, sources }
+    }
+
+    fn references(&self, uri: &str, needle: &str, include_declaration: bool) -> Vec<Location> {
+        let code = &self.sources[uri];
+        let doc = parse(code);
+        let position = pos_for(code, needle);
+        let service = JavaService;
+        let target = service
+            .declaration_location(&doc.tree, &doc.text, position, &self.index, uri)
+            .expect("declaration");
+
+        let name = needle
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .next()
+            .unwrap()
+            .to_string();
+
+        find_references(
+            &service,
+            &self.index,
+            &target,
+            &name,
+            include_declaration,
+            &|uri: &Url| self.sources.get(uri.as_str()).map(|code| parse(code)),
+        )
+    }
+}
+
+const GREETER: &str = r#"
+package app;
+
+public class Greeter {
+    public void greet() {}
+}
+"#;
+
+const OTHER: &str = r#"
+package app;
+
+public class Other {
+    public void greet() {}
+}
+"#;
+
+const MAIN: &str = r#"
+package app;
+
+class Main {
+    void run(Greeter g, Other o) {
+        g.greet();
+        o.greet();
+        Greeter again = new Greeter();
+        again.greet();
+    }
+}
+"#;
+
+#[test]
+fn finds_method_references_across_files() {
+    let ws = Workspace::new(&[
+        ("file:///ws/app/Greeter.java", GREETER),
+        ("file:///ws/app/Other.java", OTHER),
+        ("file:///ws/app/Main.java", MAIN),
+    ]);
+
+    let refs = ws.references("file:///ws/app/Main.java", "greet();", false);
+    let lines: Vec<_> = refs
+        .iter()
+        .map(|l| (l.uri.path().to_string(), l.range.start.line))
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            (
+                "/ws/app/Main.java".to_string(),
+                pos_for(MAIN, "g.greet").line
+            ),
+            (
+                "/ws/app/Main.java".to_string(),
+                pos_for(MAIN, "again.greet").line
+            ),
+        ],
+        "unexpected references {:?}",
+        refs
+    );
+
+    let with_decl = ws.references("file:///ws/app/Main.java", "greet();", true);
+    assert!(
+        with_decl
+            .iter()
+            .any(|l| l.uri.path() == "/ws/app/Greeter.java"),
+        "declaration missing from {:?}",
+        with_decl
+    );
+    assert_eq!(with_decl.len(), 3);
+}
+
+#[test]
+fn finds_class_references_from_declaration() {
+    let ws = Workspace::new(&[
+        ("file:///ws/app/Greeter.java", GREETER),
+        ("file:///ws/app/Main.java", MAIN),
+    ]);
+
+    let refs = ws.references("file:///ws/app/Greeter.java", "Greeter {", false);
+    assert_eq!(refs.len(), 3, "unexpected references {:?}", refs);
+    assert!(refs.iter().all(|l| l.uri.path() == "/ws/app/Main.java"));
+}
+
+#[test]
+fn local_references_stay_in_scope() {
+    let code = r#"
+class Scopes {
+    void a() {
+        int count = 1;
+        count++;
+    }
+    void b() {
+        int count = 2;
+        count--;
+    }
+}
+"#;
+    let ws = Workspace::new(&[("file:///ws/Scopes.java", code)]);
+
+    let refs = ws.references("file:///ws/Scopes.java", "count++", true);
+    let lines: Vec<_> = refs.iter().map(|l| l.range.start.line).collect();
+    assert_eq!(
+        lines,
+        vec![
+            pos_for(code, "count = 1").line,
+            pos_for(code, "count++").line
+        ]
+    );
+}
+
+#[test]
+fn occurrence_table_follows_updates_and_removals() {
+    let ws = Workspace::new(&[
+        ("file:///ws/app/Greeter.java", GREETER),
+        ("file:///ws/app/Main.java", MAIN),
+    ]);
+    let files = |name: &str| {
+        let mut uris: Vec<_> = ws
+            .index
+            .occurrences_of(name)
+            .into_iter()
+            .map(|loc| loc.uri.to_string())
+            .collect();
+        uris.dedup();
+        uris
+    };
+    assert_eq!(
+        files("greet"),
+        vec!["file:///ws/app/Greeter.java", "file:///ws/app/Main.java"]
+    );
+
+    let doc = parse("package app;\n\nclass Main {}\n");
+    Indexer::update_file(&ws.index, "file:///ws/app/Main.java", &doc.tree, &doc.text);
+    assert_eq!(files("greet"), vec!["file:///ws/app/Greeter.java"]);
+    assert_eq!(files("Main"), vec!["file:///ws/app/Main.java"]);
+
+    ws.index.remove_file("file:///ws/app/Main.java");
+    assert!(files("Main").is_empty());
+}
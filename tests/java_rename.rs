@@ -0,0 +1,178 @@
+mod common;
+
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{
+    DocumentChangeOperation, DocumentChanges, ResourceOp, TextEdit, Url, WorkspaceEdit,
+};
+
+use jrsls::{
+    indexer::Indexer,
+    lang::java::JavaService,
+    rename::{prepare_rename, rename},
+    state::GlobalIndex,
+};
+
+use common::{parse, pos_for};
+
+fn index_all(files: &[(&str, &str)]) -> (GlobalIndex, HashMap<String, String>) {
+    let index = GlobalIndex::new();
+    let mut sources = HashMap::new();
+    for (uri, code) in files {
+        let doc = parse(code);
+        Indexer::update_file(&index, uri, &doc.tree, &doc.text);
+        sources.insert(uri.to_string(), code.to_string());
+    }
+    (index, sources)
+}
+
+fn run_rename(
+    index: &GlobalIndex,
+    sources: &HashMap<String, String>,
+    uri: &str,
+    needle: &str,
+    new_name: &str,
+) -> anyhow::Result<WorkspaceEdit> {
+    let code = &sources[uri];
+    rename(
+        &JavaService,
+        index,
+        &parse(code),
+        uri,
+        pos_for(code, needle),
+        new_name,
+        &["class".to_string()],
+        &|uri: &Url| sources.get(uri.as_str()).map(|code| parse(code)),
+    )
+}
+
+fn edited_lines(edits: &[TextEdit]) -> Vec<u32> {
+    edits.iter().map(|e| e.range.start.line).collect()
+}
+
+#[test]
+fn renames_local_only_within_its_scope() {
+    let code = r#"
+class Scopes {
+    int total;
+    void a() {
+        int total = 1;
+        total += this.total;
+    }
+    void b() {
+        total = 2;
+    }
+}
+"#;
+    let uri = "file:///ws/Scopes.java";
+    let (index, sources) = index_all(&[(uri, code)]);
+
+    let edit = run_rename(&index, &sources, uri, "total = 1", "sum").unwrap();
+    let changes = edit.changes.expect("plain text edits");
+    let edits = &changes[&Url::parse(uri).unwrap()];
+    assert_eq!(
+        edited_lines(edits),
+        vec![
+            pos_for(code, "total = 1").line,
+            pos_for(code, "total +=").line
+        ],
+        "field or other method touched: {:?}",
+        edits
+    );
+    assert_eq!(edits[1].range.start.character, 8, "this.total renamed");
+}
+
+#[test]
+fn renames_public_class_with_imports_constructors_and_file() {
+    let greeter = r#"
+package app.core;
+
+public class Greeter {
+    public Greeter() {}
+}
+"#;
+    let main = r#"
+package app;
+
+import app.core.Greeter;
+
+class Main {
+    Greeter make() {
+        return new Greeter();
+    }
+}
+"#;
+    let (index, sources) = index_all(&[
+        ("file:///ws/app/core/Greeter.java", greeter),
+        ("file:///ws/app/Main.java", main),
+    ]);
+
+    let edit = run_rename(
+        &index,
+        &sources,
+        "file:///ws/app/Main.java",
+        "Greeter make",
+        "Welcomer",
+    )
+    .unwrap();
+    let Some(DocumentChanges::Operations(ops)) = edit.document_changes else {
+        panic!("expected document changes with a file rename");
+    };
+
+    let mut edited = HashMap::new();
+    let mut file_rename = None;
+    for op in ops {
+        match op {
+            DocumentChangeOperation::Edit(edit) => {
+                assert!(file_rename.is_none(), "rename must come after edits");
+                edited.insert(edit.text_document.uri.path().to_string(), edit.edits.len());
+            }
+            DocumentChangeOperation::Op(ResourceOp::Rename(op)) => {
+                file_rename = Some((op.old_uri.path().to_string(), op.new_uri.path().to_string()))
+            }
+            other => panic!("unexpected operation {:?}", other),
+        }
+    }
+
+    // import + return type + `new` expression
+    assert_eq!(edited.get("/ws/app/Main.java"), Some(&3));
+    // class name + constructor
+    assert_eq!(edited.get("/ws/app/core/Greeter.java"), Some(&2));
+    assert_eq!(
+        file_rename,
+        Some((
+            "/ws/app/core/Greeter.java".to_string(),
+            "/ws/app/core/Welcomer.java".to_string()
+        ))
+    );
+}
+
+#[test]
+fn refuses_jdk_symbols_and_invalid_names() {
+    let lib = r#"
+package java.lang;
+public class StringBuilder {}
+"#;
+    let main = r#"
+class Main {
+    StringBuilder sb;
+}
+"#;
+    let (index, sources) = index_all(&[
+        ("jrsls-std:///java/lang/StringBuilder.java", lib),
+        ("file:///ws/Main.java", main),
+    ]);
+
+    let err = prepare_rename(
+        &JavaService,
+        &index,
+        &parse(main),
+        "file:///ws/Main.java",
+        pos_for(main, "StringBuilder sb"),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("JDK"), "{}", err);
+
+    let err = run_rename(&index, &sources, "file:///ws/Main.java", "sb;", "class").unwrap_err();
+    assert!(err.to_string().contains("not a valid"), "{}", err);
+}
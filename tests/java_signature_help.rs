@@ -0,0 +1,123 @@
+mod common;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{ParameterLabel, SignatureHelp};
+
+use jrsls::{
+    lang::{LanguageService, java::JavaService},
+    state::GlobalIndex,
+};
+
+use common::{parse_and_index, pos_after};
+
+fn signature_help(index: &GlobalIndex, code: &str, needle: &str) -> SignatureHelp {
+    let uri = "file:///workspace/Main.java";
+    let tree = parse_and_index(code, uri, index);
+    let rope = Rope::from_str(code);
+    JavaService
+        .signature_help(&tree, &rope, pos_after(code, needle), index, uri)
+        .expect("signature help")
+}
+
+const FORMATTER: &str = r#"
+package app;
+
+public class Formatter {
+    /** Formats a single value. */
+    public String format(int value) { return ""; }
+    public String format(String pattern, Object... args) { return ""; }
+    public Formatter(String locale, int width) {}
+}
+"#;
+
+#[test]
+fn lists_overloads_and_tracks_active_parameter() {
+    let code = r#"
+package app;
+
+class Main {
+    void demo(Formatter f) {
+        f.format("%s %s", 1, 2);
+    }
+}
+"#;
+    let index = GlobalIndex::new();
+    parse_and_index(FORMATTER, "file:///workspace/app/Formatter.java", &index);
+
+    let help = signature_help(&index, code, "f.format(\"%s %s\", 1, ");
+    let labels: Vec<_> = help.signatures.iter().map(|s| s.label.as_str()).collect();
+    assert_eq!(
+        labels,
+        vec![
+            "format(int value)",
+            "format(String pattern, Object... args)"
+        ]
+    );
+
+    let active = &help.signatures[help.active_signature.unwrap() as usize];
+    assert_eq!(active.label, "format(String pattern, Object... args)");
+    // Third argument of a varargs call still maps onto `args`.
+    assert_eq!(active.active_parameter, Some(1));
+
+    let params = active.parameters.as_ref().unwrap();
+    let ParameterLabel::LabelOffsets([start, end]) = params[1].label else {
+        panic!("expected label offsets");
+    };
+    assert_eq!(
+        &active.label[start as usize..end as usize],
+        "Object... args"
+    );
+}
+
+#[test]
+fn shows_constructor_signatures_for_new_expressions() {
+    let code = r#"
+package app;
+
+class Main {
+    void demo() {
+        Formatter f = new Formatter("en", );
+    }
+}
+"#;
+    let index = GlobalIndex::new();
+    parse_and_index(FORMATTER, "file:///workspace/app/Formatter.java", &index);
+
+    let help = signature_help(&index, code, "new Formatter(\"en\", ");
+    assert_eq!(help.signatures.len(), 1);
+    assert_eq!(
+        help.signatures[0].label,
+        "Formatter(String locale, int width)"
+    );
+    assert_eq!(help.active_parameter, Some(1));
+}
+
+#[test]
+fn innermost_call_wins_and_no_help_outside_arguments() {
+    let code = r#"
+class Main {
+    int twice(int n) { return n * 2; }
+    void log(String message, int level) {}
+    void demo() {
+        log("x", twice(3));
+    }
+}
+"#;
+    let index = GlobalIndex::new();
+    let help = signature_help(&index, code, "\"x\", twice(");
+    assert_eq!(help.signatures[0].label, "twice(int n)");
+    assert_eq!(help.active_parameter, Some(0));
+
+    let help = signature_help(&index, code, "log(\"x\", ");
+    assert_eq!(help.signatures[0].label, "log(String message, int level)");
+    assert_eq!(help.active_parameter, Some(1));
+
+    let uri = "file:///workspace/Main.java";
+    let tree = parse_and_index(code, uri, &index);
+    let rope = Rope::from_str(code);
+    assert!(
+        JavaService
+            .signature_help(&tree, &rope, pos_after(code, "void demo"), &index, uri)
+            .is_none()
+    );
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use tower_lsp::lsp_types::SymbolKind;
+
+use jrsls::{
+    state::GlobalIndex,
+    workspace_symbols::{fuzzy_score, workspace_symbols},
+};
+
+use common::parse_and_index;
+
+#[test]
+fn camel_case_initials_prefer_word_starts() {
+    let humps = fuzzy_score("HSM", "HashSetMap").expect("match");
+    let flat = fuzzy_score("HSM", "Hashsum").expect("match");
+    assert!(humps > flat, "{} <= {}", humps, flat);
+
+    assert!(fuzzy_score("hsm", "HashSetMap").is_some());
+    assert!(fuzzy_score("MSH", "HashSetMap").is_none());
+    assert!(fuzzy_score("Map", "Map").unwrap() > fuzzy_score("Map", "HashMap").unwrap());
+}
+
+#[test]
+fn ranks_workspace_symbols_before_jdk() {
+    let index = GlobalIndex::new();
+    parse_and_index(
+        "package java.util;\npublic class HashSet {}\n",
+        "jrsls-std:///java/util/HashSet.java",
+        &index,
+    );
+    parse_and_index(
+        r#"
+package app.util;
+
+public interface HashSetMap {
+    void put(String key);
+    int HASH_SEED = 7;
+}
+"#,
+        "file:///ws/app/util/HashSetMap.java",
+        &index,
+    );
+
+    let symbols = workspace_symbols(&index, "HashSet");
+    let names: Vec<_> = symbols
+        .iter()
+        .map(|s| (s.name.as_str(), s.location.uri.scheme()))
+        .collect();
+    assert_eq!(
+        names,
+        vec![("HashSetMap", "file"), ("HashSet", "jrsls-std")],
+        "{:?}",
+        symbols
+    );
+    assert_eq!(symbols[0].kind, SymbolKind::INTERFACE);
+    assert_eq!(symbols[0].container_name.as_deref(), Some("app.util"));
+
+    let symbols = workspace_symbols(&index, "HSM");
+    assert_eq!(symbols[0].name, "HashSetMap");
+
+    let symbols = workspace_symbols(&index, "put");
+    assert_eq!(symbols.len(), 1);
+    assert_eq!(symbols[0].kind, SymbolKind::METHOD);
+    assert_eq!(
+        symbols[0].container_name.as_deref(),
+        Some("app.util.HashSetMap")
+    );
+
+    assert!(workspace_symbols(&index, "").is_empty());
+}
+
+#[test]
+fn name_table_follows_file_updates() {
+    let index = GlobalIndex::new();
+    let uri = "file:///ws/Shape.java";
+    parse_and_index("class Circle {}\n", uri, &index);
+    assert_eq!(index.classes_by_short_name("Circle").len(), 1);
+
+    parse_and_index("class Square {}\n", uri, &index);
+    assert!(index.classes_by_short_name("Circle").is_empty());
+    assert_eq!(index.classes_by_short_name("Square").len(), 1);
+    assert!(!index.declared_names().contains(&"Circle".to_string()));
+    assert!(workspace_symbols(&index, "Circle").is_empty());
+}
@@ -0,0 +1,242 @@
+use std::path::{Path, PathBuf};
+
+use jrsls::project::maven::{Coordinates, MavenProject, Repository, artifact_path};
+
+struct Fixture {
+    dir: PathBuf,
+}
+
+impl Fixture {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("jrsls-maven-{}-{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        Self { dir }
+    }
+
+    fn repository(&self) -> PathBuf {
+        self.dir.join("repository")
+    }
+
+    fn workspace(&self) -> PathBuf {
+        self.dir.join("workspace")
+    }
+
+    fn write(&self, path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    fn module(&self, dir: &str, pom: &str) {
+        self.write(&self.workspace().join(dir).join("pom.xml"), pom);
+    }
+
+    /// Installs `g:a:v` into the repository: a POM with `body` (raw XML)
+    /// inside `<project>` and, with `jar`, an empty jar.
+    fn install(&self, gav: &str, body: &str, jar: bool) -> PathBuf {
+        let coordinates = gav_of(gav);
+        let pom = format!(
+            "<project xmlns=\"http://maven.apache.org/POM/4.0.0\">\
+             <modelVersion>4.0.0</modelVersion>\
+             <groupId>{}</groupId><artifactId>{}</artifactId><version>{}</version>{}</project>",
+            coordinates.group_id, coordinates.artifact_id, coordinates.version, body
+        );
+        self.write(
+            &artifact_path(&self.repository(), &coordinates, None, "pom"),
+            &pom,
+        );
+        let jar_path = artifact_path(&self.repository(), &coordinates, None, "jar");
+        if jar {
+            self.write(&jar_path, "");
+        }
+        jar_path
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+fn gav_of(gav: &str) -> Coordinates {
+    let parts: Vec<_> = gav.split(':').collect();
+    Coordinates {
+        group_id: parts[0].to_string(),
+        artifact_id: parts[1].to_string(),
+        version: parts[2].to_string(),
+    }
+}
+
+fn dependency(gav: &str, extra: &str) -> String {
+    let parts: Vec<_> = gav.split(':').collect();
+    let version = parts
+        .get(2)
+        .map(|v| format!("<version>{}</version>", v))
+        .unwrap_or_default();
+    format!(
+        "<dependency><groupId>{}</groupId><artifactId>{}</artifactId>{}{}</dependency>",
+        parts[0], parts[1], version, extra
+    )
+}
+
+#[test]
+fn resolves_multi_module_build_from_local_repository() {
+    let fx = Fixture::new("modules");
+
+    fx.install(
+        "com.acme:corp-parent:3",
+        "<packaging>pom</packaging>\
+         <properties><guava.version>31.0</guava.version></properties>\
+         <dependencyManagement><dependencies>\
+         <dependency><groupId>com.google</groupId><artifactId>guava</artifactId>\
+         <version>${guava.version}</version></dependency>\
+         </dependencies></dependencyManagement>",
+        false,
+    );
+    fx.install(
+        "com.acme:platform-bom:1.0",
+        &format!(
+            "<packaging>pom</packaging><dependencyManagement><dependencies>{}</dependencies></dependencyManagement>",
+            dependency("org.lib:lib-a:2.0", "")
+        ),
+        false,
+    );
+    fx.install("org.lib:lib-a:1.0", "", true);
+    let lib_a = fx.install(
+        "org.lib:lib-a:2.0",
+        &format!(
+            "<dependencies>{}{}{}</dependencies>",
+            dependency("org.lib:lib-b:1.0", ""),
+            dependency("org.lib:lib-c:1.0", "<scope>test</scope>"),
+            dependency("org.lib:lib-opt:1.0", "<optional>true</optional>"),
+        ),
+        true,
+    );
+    let lib_b = fx.install(
+        "org.lib:lib-b:1.0",
+        &format!(
+            "<dependencies>{}</dependencies>",
+            dependency("org.lib:lib-x:1.0", "")
+        ),
+        true,
+    );
+    for gav in [
+        "org.lib:lib-c:1.0",
+        "org.lib:lib-opt:1.0",
+        "org.lib:lib-x:1.0",
+    ] {
+        fx.install(gav, "", true);
+    }
+    let guava = fx.install("com.google:guava:31.0", "", true);
+    let junit = fx.install("junit:junit:4.13", "", true);
+    fx.install("org.lib:lib-rt:1.0", "", true);
+
+    fx.module(
+        "",
+        "<project xmlns=\"http://maven.apache.org/POM/4.0.0\">\
+         <parent><groupId>com.acme</groupId><artifactId>corp-parent</artifactId><version>3</version></parent>\
+         <artifactId>app</artifactId><version>1.0-SNAPSHOT</version><packaging>pom</packaging>\
+         <modules><module>core</module><module>web</module></modules>\
+         <properties><junit.version>4.13</junit.version></properties>\
+         <dependencyManagement><dependencies>\
+         <dependency><groupId>com.acme</groupId><artifactId>platform-bom</artifactId>\
+         <version>1.0</version><type>pom</type><scope>import</scope></dependency>\
+         </dependencies></dependencyManagement></project>",
+    );
+    fx.module(
+        "core",
+        &format!(
+            "<project><parent><groupId>com.acme</groupId><artifactId>app</artifactId>\
+             <version>1.0-SNAPSHOT</version></parent><artifactId>core</artifactId>\
+             <dependencies>{}{}{}{}</dependencies></project>",
+            dependency(
+                "org.lib:lib-a",
+                "<exclusions><exclusion><groupId>org.lib</groupId>\
+                 <artifactId>lib-x</artifactId></exclusion></exclusions>"
+            ),
+            dependency("com.google:guava", ""),
+            dependency("junit:junit:${junit.version}", "<scope>test</scope>"),
+            dependency("org.lib:lib-rt:1.0", "<scope>runtime</scope>"),
+        ),
+    );
+    fx.module(
+        "web",
+        &format!(
+            "<project><parent><groupId>com.acme</groupId><artifactId>app</artifactId>\
+             <version>1.0-SNAPSHOT</version></parent><artifactId>web</artifactId>\
+             <dependencies>{}</dependencies></project>",
+            dependency("com.acme:core:${project.version}", ""),
+        ),
+    );
+
+    let project = MavenProject::load(&fx.workspace(), &Repository::Maven(fx.repository())).unwrap();
+    let modules: Vec<_> = project
+        .modules
+        .iter()
+        .map(|m| m.coordinates.to_string())
+        .collect();
+    assert_eq!(
+        modules,
+        vec![
+            "com.acme:app:1.0-SNAPSHOT",
+            "com.acme:core:1.0-SNAPSHOT",
+            "com.acme:web:1.0-SNAPSHOT"
+        ]
+    );
+
+    let mut classpath = project.classpath();
+    classpath.sort();
+    let mut expected = vec![lib_a, lib_b, guava];
+    expected.sort();
+    assert_eq!(classpath, expected);
+    // Test jars are kept apart; runtime-only jars are not compiled against.
+    assert_eq!(project.test_classpath(), vec![junit]);
+
+    let junit = project
+        .dependencies
+        .iter()
+        .find(|d| d.coordinates.artifact_id == "junit")
+        .unwrap();
+    assert_eq!(junit.scope, "test");
+}
+
+#[test]
+fn resolves_version_ranges_and_system_scope() {
+    let fx = Fixture::new("ranges");
+    fx.install("org.lib:lib-a:1.0", "", true);
+    let lib_a = fx.install("org.lib:lib-a:1.10", "", true);
+    fx.install("org.lib:lib-a:2.0", "", true);
+    let tools = fx.dir.join("tools.jar");
+    fx.write(&tools, "");
+
+    fx.module(
+        "",
+        &format!(
+            "<project><groupId>demo</groupId><artifactId>demo</artifactId><version>1</version>\
+             <build><sourceDirectory>${{project.basedir}}/java</sourceDirectory></build>\
+             <dependencies>{}{}{}</dependencies></project>",
+            dependency("org.lib:lib-a:[1.0,2.0)", ""),
+            dependency(
+                "com.sun:tools:1.8",
+                &format!(
+                    "<scope>system</scope><systemPath>{}</systemPath>",
+                    tools.display()
+                )
+            ),
+            dependency("org.missing:gone:1.0", ""),
+        ),
+    );
+
+    let project = MavenProject::load(&fx.workspace(), &Repository::Maven(fx.repository())).unwrap();
+    assert_eq!(project.classpath(), vec![lib_a, tools]);
+    assert_eq!(project.modules[0].source_dir, fx.workspace().join("java"));
+    assert_eq!(
+        project.modules[0].test_source_dir,
+        fx.workspace().join("src/test/java")
+    );
+
+    assert!(
+        MavenProject::load(&fx.dir.join("nowhere"), &Repository::Maven(fx.repository())).is_err()
+    );
+}
@@ -0,0 +1,64 @@
+use tower_lsp::lsp_types::Url;
+
+use jrsls::{
+    pool::{Job, JobCounter, index_parallel},
+    state::{FileEntry, GlobalIndex},
+};
+
+#[test]
+fn indexes_every_submitted_job() {
+    let dir = std::env::temp_dir().join(format!("jrsls-pool-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("OnDisk.java");
+    std::fs::write(&path, "package disk;\n\nclass OnDisk { void read() {} }\n").unwrap();
+    let file_uri = Url::from_file_path(&path).unwrap().to_string();
+
+    let index = GlobalIndex::new();
+    let counter = JobCounter::default();
+    counter.add_total(1000);
+    let (submitted, mut uris) = index_parallel(&index, &counter, |submit| {
+        for i in 0..1000 {
+            submit(Job::Source {
+                uri: format!("jrsls-std:///gen/C{}.java", i),
+                text: format!("package gen;\n\npublic class C{} {{ int f{}; }}\n", i, i),
+            });
+        }
+        submit(Job::File {
+            uri: file_uri.clone(),
+            path: path.clone(),
+        });
+        submit(Job::Entry(FileEntry {
+            uri: "jrsls-std:///Prebuilt.java".to_string(),
+            package_name: None,
+            imports: Vec::new(),
+            classes: Vec::new(),
+            members: Vec::new(),
+            occurrences: Vec::new(),
+        }));
+        // Unreadable files are skipped, not reported as indexed.
+        submit(Job::File {
+            uri: "file:///nowhere/Missing.java".to_string(),
+            path: dir.join("Missing.java"),
+        });
+        1003
+    });
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(submitted, 1003);
+    // Finished jobs beyond the announced total never show past 100%.
+    assert_eq!(
+        counter.done.load(std::sync::atomic::Ordering::Relaxed),
+        1003
+    );
+    assert_eq!(counter.snapshot(), (1000, 1000));
+    assert_eq!(uris.len(), 1002);
+    uris.sort();
+    uris.dedup();
+    assert_eq!(uris.len(), 1002);
+
+    assert_eq!(index.classes_by_short_name("C999")[0].fqcn, "gen.C999");
+    assert_eq!(index.members_by_name("f0")[0].fqmn, "gen.C0.f0");
+    assert_eq!(index.members_by_name("read")[0].uri.as_str(), file_uri);
+    assert!(index.file_entry("jrsls-std:///Prebuilt.java").is_some());
+    assert_eq!(index.declared_names().len(), 2000 + 2);
+}
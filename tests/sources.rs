@@ -0,0 +1,180 @@
+use std::path::{Path, PathBuf};
+
+use jrsls::filesystem::SourceFilter;
+use jrsls::project::{BuildModel, SourceRoot, collect_sources, is_workspace_source};
+
+struct Fixture {
+    dir: PathBuf,
+}
+
+impl Fixture {
+    fn new(name: &str) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("jrsls-sources-{}-{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        Self { dir }
+    }
+
+    fn file(&self, path: &str, contents: &str) {
+        let path = self.dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    fn java(&self, path: &str, package: &str) {
+        let class = Path::new(path).file_stem().unwrap().to_str().unwrap();
+        self.file(
+            path,
+            &format!("package {};\n\nclass {} {{}}\n", package, class),
+        );
+    }
+
+    fn relative(&self, paths: &[PathBuf]) -> Vec<String> {
+        let mut relative: Vec<_> = paths
+            .iter()
+            .map(|p| {
+                p.strip_prefix(&self.dir)
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        relative.sort();
+        relative
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+#[test]
+fn indexes_conventional_roots_only() {
+    let fx = Fixture::new("convention");
+    fx.file(".gitignore", "generated/\n");
+    fx.java("src/main/java/demo/App.java", "demo");
+    fx.java("src/test/java/demo/AppTest.java", "demo");
+    fx.java("src/test/resources/fixtures/Broken.java", "fixtures");
+    fx.java("target/generated-sources/demo/App.java", "demo");
+    fx.java("generated/demo/Gen.java", "demo");
+    fx.java("node_modules/pkg/demo/Js.java", "demo");
+    fx.java("src/experimental/java/demo/Draft.java", "demo");
+
+    let filter = SourceFilter::new(fx.dir.clone(), &["**/experimental/**".to_string()]).unwrap();
+    let sources = collect_sources(&fx.dir, &BuildModel::default(), &filter);
+    assert_eq!(
+        fx.relative(&sources.files),
+        vec![
+            "src/main/java/demo/App.java",
+            "src/test/java/demo/AppTest.java"
+        ]
+    );
+    assert!(sources.roots.contains(&SourceRoot {
+        dir: fx.dir.join("src/test/java"),
+        is_test: true,
+    }));
+    assert!(sources.roots.contains(&SourceRoot {
+        dir: fx.dir.join("src/main/java"),
+        is_test: false,
+    }));
+}
+
+#[test]
+fn derives_roots_from_package_declarations() {
+    let fx = Fixture::new("packages");
+    fx.java("lib/src/com/acme/Util.java", "com.acme");
+    fx.java("lib/test/com/acme/UtilTest.java", "com.acme");
+    fx.java("scratch/Loose.java", "somewhere.else");
+
+    let filter = SourceFilter::new(fx.dir.clone(), &[]).unwrap();
+    let sources = collect_sources(&fx.dir, &BuildModel::default(), &filter);
+    assert_eq!(sources.files.len(), 3);
+
+    let mut roots: Vec<_> = sources
+        .roots
+        .iter()
+        .map(|r| {
+            (
+                r.dir.strip_prefix(&fx.dir).unwrap().to_path_buf(),
+                r.is_test,
+            )
+        })
+        .collect();
+    roots.sort();
+    assert_eq!(
+        roots,
+        vec![
+            (PathBuf::from("lib/src"), false),
+            (PathBuf::from("lib/test"), true),
+            (PathBuf::from("scratch"), false),
+        ]
+    );
+}
+
+#[test]
+fn build_declared_roots_replace_conventions() {
+    let fx = Fixture::new("declared");
+    fx.java("app/java/demo/App.java", "demo");
+    fx.java("app/src/main/java/demo/Stale.java", "demo");
+    fx.java("app/build/generated/java/demo/Gen.java", "demo");
+
+    let build = BuildModel {
+        classpath: Vec::new(),
+        test_classpath: Vec::new(),
+        module_dirs: vec![fx.dir.join("app")],
+        source_roots: vec![
+            SourceRoot {
+                dir: fx.dir.join("app/java"),
+                is_test: false,
+            },
+            SourceRoot {
+                dir: fx.dir.join("app/build/generated/java"),
+                is_test: false,
+            },
+        ],
+    };
+    let filter = SourceFilter::new(fx.dir.clone(), &[]).unwrap();
+    let sources = collect_sources(&fx.dir, &build, &filter);
+    assert_eq!(
+        fx.relative(&sources.files),
+        vec![
+            "app/build/generated/java/demo/Gen.java",
+            "app/java/demo/App.java"
+        ]
+    );
+}
+
+#[test]
+fn closed_files_outside_the_sources_are_not_restored() {
+    let fx = Fixture::new("closed");
+    fx.java("src/main/java/demo/App.java", "demo");
+    fx.java("app/build/generated/java/demo/Gen.java", "demo");
+
+    let build = BuildModel {
+        source_roots: vec![SourceRoot {
+            dir: fx.dir.join("app/build/generated/java"),
+            is_test: false,
+        }],
+        ..BuildModel::default()
+    };
+    let filter = SourceFilter::new(fx.dir.clone(), &["**/experimental/**".to_string()]).unwrap();
+    let sources = collect_sources(&fx.dir, &build, &filter);
+    let is_source = |path: &str| is_workspace_source(&sources.roots, &filter, &fx.dir.join(path));
+
+    assert!(is_source("src/main/java/demo/App.java"));
+    assert!(is_source("src/main/java/demo/New.java"));
+    assert!(is_source("app/build/generated/java/demo/Gen.java"));
+    // Build output, excluded paths and files next to the roots stay out,
+    // even after an editor opened them.
+    assert!(!is_source("target/classes/demo/App.java"));
+    assert!(!is_source("src/main/java/experimental/Draft.java"));
+    assert!(!is_source("src/test/resources/fixtures/Broken.java"));
+    assert!(!is_workspace_source(
+        &sources.roots,
+        &filter,
+        &std::env::temp_dir().join("jrsls/jrsls-std/java/lang/String.java"),
+    ));
+}
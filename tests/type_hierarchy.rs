@@ -0,0 +1,160 @@
+mod common;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::Position;
+
+use jrsls::{
+    lang::{LanguageService, java::JavaService},
+    state::GlobalIndex,
+};
+
+use common::{parse_and_index, pos_for};
+
+/// `zoo.model.Animal` <- `zoo.Pet` <- `zoo.Dog`, with `Pet` importing the
+/// model package on demand.
+fn index_zoo(index: &GlobalIndex) {
+    parse_and_index(
+        r#"package zoo.model;
+
+public interface Animal {
+    String name();
+    default String sound() { return "..."; }
+}
+"#,
+        "file:///ws/zoo/model/Animal.java",
+        index,
+    );
+    parse_and_index(
+        r#"package zoo;
+
+import zoo.model.*;
+
+public abstract class Pet implements Animal, Comparable<Pet> {
+    public String owner;
+    public String name() { return "pet"; }
+    private void groom() {}
+}
+"#,
+        "file:///ws/zoo/Pet.java",
+        index,
+    );
+    parse_and_index(
+        r#"package zoo;
+
+public class Dog extends Pet {
+    public Dog() {}
+    public void fetch() {}
+}
+"#,
+        "file:///ws/zoo/Dog.java",
+        index,
+    );
+}
+
+#[test]
+fn records_and_resolves_supertypes() {
+    let index = GlobalIndex::new();
+    index_zoo(&index);
+
+    assert_eq!(index.supertypes("zoo.Dog"), vec!["zoo.Pet"]);
+    assert_eq!(
+        index.supertypes("zoo.Pet"),
+        vec!["java.lang.Object", "zoo.model.Animal", "Comparable"]
+    );
+    assert_eq!(
+        index.supertypes("zoo.model.Animal"),
+        vec!["java.lang.Object"]
+    );
+    assert_eq!(
+        index.supertype_closure("zoo.Dog"),
+        vec![
+            "zoo.Dog",
+            "zoo.Pet",
+            "java.lang.Object",
+            "zoo.model.Animal",
+            "Comparable"
+        ]
+    );
+
+    let subtypes: Vec<_> = index
+        .subtypes("zoo.model.Animal")
+        .into_iter()
+        .map(|c| c.fqcn)
+        .collect();
+    assert_eq!(subtypes, vec!["zoo.Pet"]);
+    let all: Vec<_> = index
+        .subtype_closure("zoo.model.Animal")
+        .into_iter()
+        .map(|c| c.fqcn)
+        .collect();
+    assert_eq!(all, vec!["zoo.Pet", "zoo.Dog"]);
+
+    assert!(index.is_subtype("zoo.Dog", "zoo.model.Animal"));
+    assert!(!index.is_subtype("zoo.model.Animal", "zoo.Dog"));
+}
+
+#[test]
+fn completion_includes_inherited_members() {
+    let index = GlobalIndex::new();
+    index_zoo(&index);
+    let code = r#"package zoo;
+
+class Walk {
+    void run(Dog dog) {
+        dog.
+    }
+}
+"#;
+    let uri = "file:///ws/zoo/Walk.java";
+    let tree = parse_and_index(code, uri, &index);
+    let caret = pos_for(code, "dog.");
+    let caret = Position::new(caret.line, caret.character + 4);
+
+    let mut labels: Vec<_> = JavaService
+        .completion(&tree, &Rope::from_str(code), caret, &index, uri, &[])
+        .unwrap_or_default()
+        .into_iter()
+        .map(|item| item.label)
+        .collect();
+    labels.sort();
+    // `name` is overridden by `Pet` and listed once; private `groom` and
+    // the constructor are not inherited.
+    assert_eq!(labels, vec!["fetch", "name", "owner", "sound"]);
+}
+
+#[test]
+fn overloads_accept_subclass_arguments() {
+    let index = GlobalIndex::new();
+    index_zoo(&index);
+    let code = r#"package zoo;
+
+import zoo.model.Animal;
+
+class Keeper {
+    void feed(Animal animal) {}
+    void feed(Pet pet) {}
+    void feed(Object any) {}
+    void greet(Animal animal) {}
+    void greet(Integer count) {}
+
+    void run() {
+        Dog dog = new Dog();
+        feed(dog);
+        greet(dog);
+    }
+}
+"#;
+    let uri = "file:///ws/zoo/Keeper.java";
+    let tree = parse_and_index(code, uri, &index);
+    let rope = Rope::from_str(code);
+
+    let feed = JavaService
+        .goto_definition(&tree, &rope, pos_for(code, "feed(dog)"), &index, uri)
+        .expect("feed resolves");
+    assert_eq!(feed.range.start.line, pos_for(code, "feed(Pet").line);
+
+    let greet = JavaService
+        .goto_definition(&tree, &rope, pos_for(code, "greet(dog)"), &index, uri)
+        .expect("greet resolves");
+    assert_eq!(greet.range.start.line, pos_for(code, "greet(Animal").line);
+}
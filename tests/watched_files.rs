@@ -0,0 +1,51 @@
+mod common;
+
+use std::path::Path;
+
+use jrsls::{filesystem::SourceFilter, state::GlobalIndex};
+
+use common::parse_and_index;
+
+#[test]
+fn removed_files_disappear_from_lookups() {
+    let index = GlobalIndex::new();
+    parse_and_index(
+        "package demo;\n\npublic class Gone { void shared() {} }\n",
+        "file:///ws/src/demo/Gone.java",
+        &index,
+    );
+    parse_and_index(
+        "package demo;\n\npublic class Kept { void shared() {} }\n",
+        "file:///ws/src/demo/Kept.java",
+        &index,
+    );
+
+    assert!(index.remove_file("file:///ws/src/demo/Gone.java"));
+    assert!(!index.remove_file("file:///ws/src/demo/Gone.java"));
+    assert!(index.classes_by_short_name("Gone").is_empty());
+    assert!(index.file_info("file:///ws/src/demo/Gone.java").is_none());
+    assert_eq!(index.members_by_name("shared").len(), 1);
+    assert!(!index.declared_names().contains(&"Gone".to_string()));
+
+    parse_and_index(
+        "package lib;\n\npublic class Dep {}\n",
+        "jrsls-jar:///repo/dep.jar!/lib/Dep.java",
+        &index,
+    );
+    assert_eq!(index.remove_files_under("jrsls-jar:///repo/dep.jar!/"), 1);
+    assert!(index.classes_by_short_name("Dep").is_empty());
+    assert_eq!(index.classes_by_short_name("Kept").len(), 1);
+}
+
+#[test]
+fn single_files_pass_the_source_filter_like_a_walk() {
+    let root = Path::new("/ws");
+    let filter = SourceFilter::new(root.to_path_buf(), &["**/generated/**".to_string()]).unwrap();
+
+    assert!(filter.accepts(&root.join("src/main/java/demo/App.java")));
+    assert!(filter.accepts(&root.join("App.java")));
+    assert!(!filter.accepts(&root.join("target/classes/demo/App.java")));
+    assert!(!filter.accepts(&root.join(".git/demo/App.java")));
+    assert!(!filter.accepts(&root.join("src/generated/demo/App.java")));
+    assert!(!filter.accepts(Path::new("/elsewhere/App.java")));
+}